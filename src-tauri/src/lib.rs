@@ -1,28 +1,453 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 use tauri::Manager;
 use reqwest;
 use std::io::Write;
 use std::fs::File;
 use tauri::Emitter;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
+use fs2::FileExt;
 use url::Url;
 
+// Serializes read-modify-write access to reports.json within this process.
+// The advisory file lock taken in lock_reports_file covers other processes.
+static REPORTS_LOCK: Mutex<()> = Mutex::new(());
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Settings {
     mailchimp_api_key: String,
     mailchimp_audience_id: String,
     advertisers: Vec<String>,
     download_directory: String,
+    // "local" or "utc" - controls filename timestamps and report created dates
+    #[serde(default = "default_timestamp_timezone")]
+    timestamp_timezone: String,
+    // Metric selection pre-applied to new reports in the UI. generate_report
+    // still uses whatever selection the request carries.
+    #[serde(default = "default_metrics_selection")]
+    default_metrics: Metrics,
+    // Styling applied by themed exporters to the header and totals rows.
+    // Either a built-in preset name or "custom" with its own colors.
+    #[serde(default = "default_export_theme")]
+    export_theme: ExportTheme,
+    // Render integer counts as 12,345 instead of 12345 in exports. The
+    // stored JSON always keeps exact integers.
+    #[serde(default)]
+    thousands_separator: bool,
+    // Default tracking URLs per advertiser, used by batch generation in
+    // place of the URLs the single-report form passes explicitly
+    #[serde(default)]
+    advertiser_urls: HashMap<String, Vec<String>>,
+    // Per-client export folders keyed by advertiser name. Exports for an
+    // unmapped advertiser fall back to download_directory.
+    #[serde(default)]
+    per_advertiser_dir: HashMap<String, String>,
+    // Bespoke per-row metrics like "engagement = opens*0.3 + clicks*0.7",
+    // evaluated over the base metric keys and exported as extra columns
+    #[serde(default)]
+    custom_metrics: Vec<CustomMetric>,
+    // How many rotated reports.json backups to keep
+    #[serde(default = "default_max_backups")]
+    max_backups: usize,
+    // How many deleted reports the trash holds before evicting the oldest
+    #[serde(default = "default_trash_size")]
+    trash_size: usize,
+    // Write a sidecar .sha256 file next to every exported file, for clients
+    // that require delivery checksums
+    #[serde(default)]
+    emit_checksum: bool,
+    // Days to look back past the last fetch during incremental runs, so
+    // recently-sent campaigns whose numbers are still settling get re-pulled
+    #[serde(default = "default_settling_days")]
+    settling_days: u32,
+    // Retry a fully failed generate_report run once after a short backoff,
+    // but only for transient network/rate-limit failures
+    #[serde(default)]
+    auto_retry_report: bool,
+    // Starting width for the adaptive click-details fetch. The effective
+    // width halves on a 429 and creeps back toward this cap.
+    #[serde(default = "default_max_concurrency")]
+    max_concurrency: usize,
+    // Floor the adaptive width never drops below
+    #[serde(default = "default_min_concurrency")]
+    min_concurrency: usize,
+    // CSV field delimiter: "," (default), ";" or "\t". Independent of the
+    // decimal separator; when the two collide, numeric cells are quoted so
+    // the file stays parseable.
+    #[serde(default = "default_csv_delimiter")]
+    csv_delimiter: String,
+    // Decimal separator for fractional values in exports: "." or ","
+    #[serde(default = "default_decimal_separator")]
+    decimal_separator: String,
+    // S3-compatible endpoint for direct report delivery, e.g.
+    // "https://s3.us-east-1.amazonaws.com" or a MinIO host with port. Empty
+    // means uploads are not configured.
+    #[serde(default)]
+    s3_endpoint: String,
+    // Region used in the SigV4 signing scope. MinIO accepts any value here.
+    #[serde(default = "default_s3_region")]
+    s3_region: String,
+    #[serde(default)]
+    s3_access_key: String,
+    #[serde(default)]
+    s3_secret_key: String,
+    // Optional team folder (usually a network mount) whose reports are
+    // merged read-only into the local listing. Only the designated writer
+    // machine should ever write there.
+    #[serde(default)]
+    shared_reports_dir: Option<String>,
+    // Agreed cost per click by advertiser. Advertisers priced here get Cost
+    // and CPC columns in their exports.
+    #[serde(default)]
+    rate_card: HashMap<String, f64>,
+    // Replaces the derived https://{dc}.api.mailchimp.com/3.0 base when set,
+    // so integration tests can point the pipeline at a local mock server
+    #[serde(default)]
+    api_base_override: Option<String>,
+    // Opt-in: a MAILCHIMP_API_KEY env var replaces the stored key at load
+    // time, so CI and kiosk machines never write the key to disk
+    #[serde(default)]
+    prefer_env_api_key: bool,
+    // Set by load_settings when the env key is in effect. The settings UI
+    // shows it, and save_settings keeps the env key itself off disk.
+    #[serde(default)]
+    api_key_from_env: bool,
+    // Date ranges longer than this are fetched as separate sub-windows and
+    // merged, so one huge /campaigns query can't hit result caps
+    #[serde(default = "default_campaign_fetch_span_days")]
+    campaign_fetch_span_days: u32,
+    // How CTR and rate columns round at the .5 boundary: "half_even"
+    // (banker's rounding, matching Rust's formatter) or "half_up"
+    #[serde(default = "default_rounding_mode")]
+    rounding_mode: String,
+    // Author stamped into exported documents' metadata; empty omits it
+    #[serde(default)]
+    export_author: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+fn default_campaign_fetch_span_days() -> u32 {
+    90
+}
+
+fn default_rounding_mode() -> String {
+    "half_even".to_string()
+}
+
+fn default_settling_days() -> u32 {
+    3
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+fn default_min_concurrency() -> usize {
+    1
+}
+
+fn default_csv_delimiter() -> String {
+    ",".to_string()
+}
+
+fn default_decimal_separator() -> String {
+    ".".to_string()
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_max_backups() -> usize {
+    5
+}
+
+fn default_trash_size() -> usize {
+    20
+}
+
+// Retention settings beyond this are almost certainly a typo
+const MAX_RETENTION: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CustomMetric {
+    name: String,
+    // Expression over opens, clicks, recipients and ctr
+    expression: String,
+}
+
+// The only variables a custom metric expression may reference
+const CUSTOM_METRIC_VARS: [&str; 4] = ["opens", "clicks", "recipients", "ctr"];
+
+// Checks a custom metric parses and only references known variables, so a
+// typo is caught when settings are saved rather than at export time
+fn validate_custom_metric(metric: &CustomMetric) -> Result<(), String> {
+    if metric.name.trim().is_empty() {
+        return Err("Custom metric name cannot be empty".to_string());
+    }
+
+    let node = evalexpr::build_operator_tree(&metric.expression)
+        .map_err(|e| format!("Invalid expression for '{}': {}", metric.name, e))?;
+
+    for ident in node.iter_variable_identifiers() {
+        if !CUSTOM_METRIC_VARS.contains(&ident) {
+            return Err(format!("Unknown variable '{}' in custom metric '{}'", ident, metric.name));
+        }
+    }
+
+    Ok(())
+}
+
+// Evaluates one custom metric expression against a report row's base
+// metrics. Returns None instead of failing so one bad row can't break a
+// whole export.
+fn eval_custom_metric(expression: &str, entry: &serde_json::Value) -> Option<f64> {
+    use evalexpr::ContextWithMutableVariables;
+
+    let number = |key: &str| entry.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as f64;
+
+    let mut context = evalexpr::HashMapContext::new();
+    context.set_value("opens".into(), evalexpr::Value::Float(number("unique_opens"))).ok()?;
+    context.set_value("clicks".into(), evalexpr::Value::Float(number("total_clicks"))).ok()?;
+    context.set_value("recipients".into(), evalexpr::Value::Float(number("total_recipients"))).ok()?;
+    context.set_value("ctr".into(), evalexpr::Value::Float(entry.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0))).ok()?;
+
+    evalexpr::eval_number_with_context(expression, &context).ok()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct ExportTheme {
+    // "light", "dark", "brand", or "custom"
+    name: String,
+    header_color: String,
+    accent_color: String,
+    font: String,
+}
+
+fn default_export_theme() -> ExportTheme {
+    export_theme_preset("light").unwrap()
+}
+
+// The built-in themes shipped with the app
+fn export_theme_preset(name: &str) -> Option<ExportTheme> {
+    match name {
+        "light" => Some(ExportTheme {
+            name: "light".to_string(),
+            header_color: "#f2f2f2".to_string(),
+            accent_color: "#c0392b".to_string(),
+            font: "Calibri".to_string(),
+        }),
+        "dark" => Some(ExportTheme {
+            name: "dark".to_string(),
+            header_color: "#1a1a2e".to_string(),
+            accent_color: "#e94560".to_string(),
+            font: "Calibri".to_string(),
+        }),
+        "brand" => Some(ExportTheme {
+            name: "brand".to_string(),
+            header_color: "#9e1b32".to_string(),
+            accent_color: "#f4b41a".to_string(),
+            font: "Georgia".to_string(),
+        }),
+        _ => None,
+    }
+}
+
+fn is_valid_hex_color(value: &str) -> bool {
+    let Some(digits) = value.strip_prefix('#') else {
+        return false;
+    };
+    (digits.len() == 6 || digits.len() == 3) && digits.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// Resolves the configured theme to concrete colors. Preset names win over
+// whatever colors are stored; "custom" uses the stored colors if they
+// validate, falling back to the light preset otherwise.
+fn resolve_export_theme(settings: &Settings) -> ExportTheme {
+    if settings.export_theme.name != "custom" {
+        return export_theme_preset(&settings.export_theme.name)
+            .unwrap_or_else(default_export_theme);
+    }
+
+    if is_valid_hex_color(&settings.export_theme.header_color)
+        && is_valid_hex_color(&settings.export_theme.accent_color)
+    {
+        settings.export_theme.clone()
+    } else {
+        default_export_theme()
+    }
+}
+
+// All-true matches what the report form always pre-selected before this
+// became configurable
+fn default_metrics_selection() -> Metrics {
+    Metrics {
+        unique_opens: true,
+        total_opens: true,
+        total_recipients: true,
+        total_clicks: true,
+        ctr: true,
+        clicks_per_thousand: false,
+        tags: false,
+        share_of_clicks: false,
+        admin_url: false,
+        variate_winner: false,
+        mailchimp_rates: false,
+        targeted_count: false,
+        bounce_detail: false,
+    }
+}
+
+fn default_timestamp_timezone() -> String {
+    "local".to_string()
+}
+
+// Formats the current time with the given chrono format string in the
+// configured timezone, so filenames and created dates always agree
+fn format_timestamp_now(timezone: &str, fmt: &str) -> String {
+    if timezone.eq_ignore_ascii_case("utc") {
+        chrono::Utc::now().format(fmt).to_string()
+    } else {
+        chrono::Local::now().format(fmt).to_string()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct ReportRequest {
     newsletter_type: String,
     advertiser: String,
     tracking_urls: Vec<String>,
     date_range: DateRange,
     metrics: Metrics,
+    // "prefix" (substring, the historical behavior), "exact", or "segment"
+    // (tracking path must align on /-delimited segment boundaries)
+    #[serde(default = "default_path_match")]
+    path_match: String,
+    // When set, only campaigns in this Mailchimp folder are fetched, which is
+    // cleaner than relying on the title substring filter alone
+    #[serde(default)]
+    folder_id: Option<String>,
+    // When true, a run that matched zero clicks still saves a report with
+    // empty data instead of failing - "zero this week" is valid data for
+    // regularly scheduled reports
+    #[serde(default)]
+    allow_empty: bool,
+    // When true, the report also fetches the audience's daily activity and
+    // attaches net subscribes/unsubscribes over the window to the report
+    // metadata. Off by default since it costs an extra API call.
+    #[serde(default)]
+    include_list_activity: bool,
+    // "month" or "week": adds a nested report_groups view with per-group
+    // subtotals, and makes the exporters emit subtotal rows between groups
+    #[serde(default)]
+    group_by: Option<String>,
+    // Fetch only campaigns sent since the last successful run for this
+    // advertiser and fold them into the previously saved report
+    #[serde(default)]
+    incremental: bool,
+    // "title" (the historical substring filter on campaign titles) or "tag"
+    // (select campaigns carrying the Mailchimp tag in `tag` instead)
+    #[serde(default = "default_filter_mode")]
+    filter_mode: String,
+    // The Mailchimp tag to select on when filter_mode is "tag"
+    #[serde(default)]
+    tag: Option<String>,
+    // Drop matched campaigns whose recipients.list_id isn't the configured
+    // audience instead of just warning about them
+    #[serde(default)]
+    exclude_wrong_audience: bool,
+    // When set, the title/tag filter only considers campaigns whose
+    // recipients.list_id matches this audience, so same-titled newsletters
+    // on other lists can't sneak in. None keeps the historical behavior.
+    #[serde(default)]
+    audience_scope: Option<String>,
+    // A preview_campaigns token; when set, the run skips fetching and
+    // filtering and analyzes exactly the campaigns that were previewed
+    #[serde(default)]
+    preview_token: Option<String>,
+    // Write the raw /campaigns and click-details responses for this run to
+    // a timestamped folder under the app data dir. Off by default; the
+    // captures are only for chasing down numbers that look wrong.
+    #[serde(default)]
+    debug_capture: bool,
+}
+
+fn default_filter_mode() -> String {
+    "title".to_string()
+}
+
+// The API base every request-building site goes through. Normally derived
+// from the key's data center; the settings override (or the
+// MAILCHIMP_API_BASE env var) redirects everything to a mock server for
+// integration testing.
+fn mailchimp_base_url(settings: &Settings) -> String {
+    if let Some(base) = settings.api_base_override.as_deref() {
+        if !base.trim().is_empty() {
+            return base.trim().trim_end_matches('/').to_string();
+        }
+    }
+    if let Ok(base) = std::env::var("MAILCHIMP_API_BASE") {
+        if !base.trim().is_empty() {
+            return base.trim().trim_end_matches('/').to_string();
+        }
+    }
+
+    let dc = settings.mailchimp_api_key.split('-').next_back().unwrap_or("us1");
+    format!("https://{}.api.mailchimp.com/3.0", dc)
+}
+
+// Builds the /campaigns query for a window, optionally scoped to a folder
+fn build_campaigns_url(base_url: &str, start_iso: &str, end_iso: &str, folder_id: Option<&str>) -> String {
+    let mut url = format!(
+        "{}/campaigns?since_send_time={}&before_send_time={}&count=1000",
+        base_url, start_iso, end_iso
+    );
+    if let Some(folder) = folder_id {
+        url.push_str(&format!("&folder_id={}", folder));
+    }
+    url
+}
+
+fn default_path_match() -> String {
+    "prefix".to_string()
+}
+
+// Parses one side of a date range. Accepts a plain YYYY-MM-DD date (expanded
+// to the start or end of that day, as the app always did) or a full RFC3339
+// timestamp for partial-day ranges.
+fn parse_range_bound(value: &str, is_end: bool) -> Result<String, String> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&chrono::Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    }
+
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|e| format!("Failed to parse date '{}': {}", value, e))?;
+
+    if is_end {
+        Ok(format!("{}T23:59:59Z", date))
+    } else {
+        Ok(format!("{}T00:00:00Z", date))
+    }
+}
+
+// Builds the since_send_time/before_send_time instants for the campaigns query
+fn date_range_bounds(range: &DateRange) -> Result<(String, String), String> {
+    let start = parse_range_bound(&range.start_date, false)?;
+    let end = parse_range_bound(&range.end_date, true)?;
+    Ok((start, end))
+}
+
+// True when the two ranges share at least one instant. Both bounds are
+// inclusive, matching how the campaigns query treats them; purely adjacent
+// calendar days do not overlap.
+fn ranges_overlap(a: &DateRange, b: &DateRange) -> Result<bool, String> {
+    let (a_start, a_end) = date_range_bounds(a)?;
+    let (b_start, b_end) = date_range_bounds(b)?;
+    // The normalized bounds share one fixed UTC format, so string order is
+    // chronological order
+    Ok(a_start <= b_end && b_start <= a_end)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -38,6 +463,39 @@ struct Metrics {
     total_recipients: bool,
     total_clicks: bool,
     ctr: bool,
+    // Normalized engagement: clicks per 1000 recipients. Off by default so
+    // older saved reports keep their existing column set.
+    #[serde(default)]
+    clicks_per_thousand: bool,
+    // Campaign tags pulled from Mailchimp, shown as one joined column.
+    // Off by default since most advertisers don't tag campaigns.
+    #[serde(default)]
+    tags: bool,
+    // Advertiser's matched clicks as a fraction of every click in the
+    // campaign. Off by default like the other added columns.
+    #[serde(default)]
+    share_of_clicks: bool,
+    // Link back to the campaign's report page in the Mailchimp dashboard,
+    // built from web_id. Off by default like the other added columns.
+    #[serde(default)]
+    admin_url: bool,
+    // Winning subject line for variate (A/B-tested) campaigns. Off by
+    // default; most sends aren't subject-tested.
+    #[serde(default)]
+    variate_winner: bool,
+    // Mailchimp's own reported open/click rates next to our computed CTR,
+    // for sanity checking. Off by default; the numbers intentionally differ
+    // since ours is ad-specific and Mailchimp's covers every link.
+    #[serde(default)]
+    mailchimp_rates: bool,
+    // Targeted segment size next to the delivered count. Only interesting
+    // for segmented sends; on full-list sends the two columns match.
+    #[serde(default)]
+    targeted_count: bool,
+    // Hard/soft bounce split and per-campaign delivery rate, for
+    // deliverability-focused advertisers. One flag covers all three columns.
+    #[serde(default)]
+    bounce_detail: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,6 +504,39 @@ struct ProgressUpdate {
     progress: u8,
     message: String,
     time_remaining: Option<u64>,
+    // Wall-clock duration of the whole run; only set on the Complete update
+    #[serde(default)]
+    elapsed_secs: Option<u64>,
+}
+
+// Where progress updates go. The desktop frontend listens for the
+// "report-progress" event. The channel variant lets tests consume updates
+// without a window; it stays test-only for now because run_report still
+// needs an AppHandle to resolve settings and the app data directory.
+#[derive(Clone)]
+enum ProgressSink {
+    Window(tauri::AppHandle),
+    #[cfg(test)]
+    Channel(std::sync::mpsc::Sender<ProgressUpdate>),
+}
+
+impl ProgressSink {
+    fn send(&self, update: ProgressUpdate) {
+        match self {
+            ProgressSink::Window(app) => {
+                if let Err(e) = app.emit("report-progress", update) {
+                    println!("Failed to emit progress update: {}", e);
+                }
+            }
+            #[cfg(test)]
+            ProgressSink::Channel(tx) => {
+                // A dropped receiver shouldn't kill the report run
+                if tx.send(update).is_err() {
+                    println!("Progress receiver dropped; continuing without progress");
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,6 +545,168 @@ struct ReportResponse {
     message: String,
     data: Option<serde_json::Value>,
     progress_updates: Vec<ProgressUpdate>,
+    // "Did you mean?" hints keyed by tracking URLs that matched no clicks
+    #[serde(default)]
+    url_suggestions: Option<serde_json::Value>,
+    // Data-quality notes, e.g. matched campaigns sent to the wrong audience
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CampaignClicks {
+    id: String,
+    title: String,
+    send_date: String,
+    total_clicks: u64,
+}
+
+// One stored-vs-live difference found while reconciling a report
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Discrepancy {
+    campaign_id: String,
+    field: String,
+    stored: u64,
+    current: u64,
+}
+
+// Compares a stored campaign object against its freshly fetched version,
+// reporting the metric fields that have drifted
+fn campaign_discrepancies(stored: &serde_json::Value, current: &serde_json::Value) -> Vec<Discrepancy> {
+    let campaign_id = stored.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let summary_field = |c: &serde_json::Value, key: &str| {
+        c.get("report_summary").and_then(|r| r.get(key)).and_then(|v| v.as_u64()).unwrap_or(0)
+    };
+
+    let checks = [
+        ("unique_opens", summary_field(stored, "unique_opens"), summary_field(current, "unique_opens")),
+        ("total_opens", summary_field(stored, "opens"), summary_field(current, "opens")),
+        (
+            "total_recipients",
+            stored.get("emails_sent").and_then(|v| v.as_u64()).unwrap_or(0),
+            current.get("emails_sent").and_then(|v| v.as_u64()).unwrap_or(0),
+        ),
+    ];
+
+    checks.iter()
+        .filter(|(_, stored_value, current_value)| stored_value != current_value)
+        .map(|(field, stored_value, current_value)| Discrepancy {
+            campaign_id: campaign_id.clone(),
+            field: field.to_string(),
+            stored: *stored_value,
+            current: *current_value,
+        })
+        .collect()
+}
+
+// One subscriber's clicks on a specific link. This is PII (email
+// addresses), so the frontend must gate any feature built on it behind
+// explicit consent and must not write it into shared report files.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MemberClick {
+    email_address: String,
+    clicks: u64,
+}
+
+// One point in a week-over-week engagement series
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct WeeklyCtr {
+    // ISO week label like "2025-W07"
+    week: String,
+    total_clicks: u64,
+    unique_opens: u64,
+    ctr: f64,
+}
+
+// A per-campaign QR section for printed client decks: scanning the code
+// opens the campaign's public Mailchimp archive page
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CampaignQr {
+    title: String,
+    archive_url: String,
+    svg: String,
+}
+
+// An audience merge field, used to show "First Name" instead of FNAME in
+// member-level views
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MergeField {
+    tag: String,
+    name: String,
+    #[serde(rename = "type")]
+    field_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CampaignFolder {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct AdvertiserUrls {
+    advertiser: String,
+    tracking_urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct AdvertiserSummary {
+    advertiser: String,
+    total_clicks: u64,
+    unique_opens: u64,
+    ctr: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CrossAdvertiserReport {
+    rows: Vec<AdvertiserSummary>,
+    csv_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PeriodStats {
+    total_clicks: u64,
+    unique_opens: u64,
+    ctr: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PeriodComparison {
+    period_a: PeriodStats,
+    period_b: PeriodStats,
+    z_score: f64,
+    significant: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ReportIssue {
+    report_id: String,
+    issue: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ApiCostEstimate {
+    matched_campaigns: usize,
+    campaign_list_calls: usize,
+    click_detail_calls: usize,
+    estimated_pages: usize,
+    projected_total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct BatchItem {
+    advertiser: String,
+    success: bool,
+    message: String,
+    report_id: Option<String>,
+    export_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct BatchResult {
+    items: Vec<BatchItem>,
+    succeeded: usize,
+    failed: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -66,6 +719,25 @@ struct SavedReport {
     created: String,
     data: serde_json::Value,
     metrics: Metrics,
+    // Free-form organizational labels like "Q1" or "final", de-duplicated
+    // case-insensitively per report
+    #[serde(default)]
+    tags: Vec<String>,
+    // Set on reports merged in from the shared reports directory; they live
+    // on another machine, so write commands refuse to touch them
+    #[serde(default)]
+    read_only: bool,
+    // Wall-clock duration of the generating run and how many campaigns it
+    // processed, for spotting slowdowns over time. None on reports saved
+    // before these were recorded and on reports not from the live pipeline.
+    #[serde(default)]
+    elapsed_secs: Option<u64>,
+    #[serde(default)]
+    campaign_count: Option<usize>,
+    // Analyst-entered annotation ("numbers low due to holiday send") shown
+    // as a metadata line in exports. Empty when unset.
+    #[serde(default)]
+    notes: String,
 }
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -122,6 +794,34 @@ fn load_settings(app: tauri::AppHandle) -> Result<Settings, String> {
             mailchimp_audience_id: "6732b2b110".to_string(),
             advertisers: default_advertisers,
             download_directory: default_download_dir,
+            timestamp_timezone: default_timestamp_timezone(),
+            default_metrics: default_metrics_selection(),
+            export_theme: default_export_theme(),
+            thousands_separator: false,
+            advertiser_urls: HashMap::new(),
+            per_advertiser_dir: HashMap::new(),
+            custom_metrics: Vec::new(),
+            max_backups: default_max_backups(),
+            trash_size: default_trash_size(),
+            emit_checksum: false,
+            settling_days: default_settling_days(),
+            auto_retry_report: false,
+            max_concurrency: default_max_concurrency(),
+            min_concurrency: default_min_concurrency(),
+            csv_delimiter: default_csv_delimiter(),
+            decimal_separator: default_decimal_separator(),
+            s3_endpoint: String::new(),
+            s3_region: default_s3_region(),
+            s3_access_key: String::new(),
+            s3_secret_key: String::new(),
+            shared_reports_dir: None,
+            rate_card: HashMap::new(),
+            api_base_override: None,
+            prefer_env_api_key: false,
+            api_key_from_env: false,
+            campaign_fetch_span_days: default_campaign_fetch_span_days(),
+            rounding_mode: default_rounding_mode(),
+            export_author: String::new(),
         };
         
         println!("Returning default settings: {:?}", settings);
@@ -167,6 +867,103 @@ fn load_settings(app: tauri::AppHandle) -> Result<Settings, String> {
                     })
                     .unwrap_or_else(Vec::new),
                 download_directory: default_download_dir,
+                timestamp_timezone: json_value.get("timestamp_timezone")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("local")
+                    .to_string(),
+                default_metrics: json_value.get("default_metrics")
+                    .and_then(|m| serde_json::from_value(m.clone()).ok())
+                    .unwrap_or_else(default_metrics_selection),
+                export_theme: json_value.get("export_theme")
+                    .and_then(|t| serde_json::from_value(t.clone()).ok())
+                    .unwrap_or_else(default_export_theme),
+                thousands_separator: json_value.get("thousands_separator")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                advertiser_urls: json_value.get("advertiser_urls")
+                    .and_then(|m| serde_json::from_value(m.clone()).ok())
+                    .unwrap_or_default(),
+                per_advertiser_dir: json_value.get("per_advertiser_dir")
+                    .and_then(|m| serde_json::from_value(m.clone()).ok())
+                    .unwrap_or_default(),
+                custom_metrics: json_value.get("custom_metrics")
+                    .and_then(|m| serde_json::from_value(m.clone()).ok())
+                    .unwrap_or_default(),
+                max_backups: json_value.get("max_backups")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or_else(default_max_backups),
+                trash_size: json_value.get("trash_size")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or_else(default_trash_size),
+                emit_checksum: json_value.get("emit_checksum")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                settling_days: json_value.get("settling_days")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32)
+                    .unwrap_or_else(default_settling_days),
+                auto_retry_report: json_value.get("auto_retry_report")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                max_concurrency: json_value.get("max_concurrency")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or_else(default_max_concurrency),
+                min_concurrency: json_value.get("min_concurrency")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or_else(default_min_concurrency),
+                csv_delimiter: json_value.get("csv_delimiter")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(default_csv_delimiter),
+                decimal_separator: json_value.get("decimal_separator")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(default_decimal_separator),
+                s3_endpoint: json_value.get("s3_endpoint")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                s3_region: json_value.get("s3_region")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(default_s3_region),
+                s3_access_key: json_value.get("s3_access_key")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                s3_secret_key: json_value.get("s3_secret_key")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                shared_reports_dir: json_value.get("shared_reports_dir")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+                rate_card: json_value.get("rate_card")
+                    .and_then(|m| serde_json::from_value(m.clone()).ok())
+                    .unwrap_or_default(),
+                api_base_override: json_value.get("api_base_override")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+                prefer_env_api_key: json_value.get("prefer_env_api_key")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                api_key_from_env: false,
+                campaign_fetch_span_days: json_value.get("campaign_fetch_span_days")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32)
+                    .unwrap_or_else(default_campaign_fetch_span_days),
+                rounding_mode: json_value.get("rounding_mode")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(default_rounding_mode),
+                export_author: json_value.get("export_author")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
             }
         }
     };
@@ -184,6 +981,13 @@ fn load_settings(app: tauri::AppHandle) -> Result<Settings, String> {
         settings.download_directory = default_download_dir;
     }
     
+    // With the opt-in flag set, a key in the environment wins over the
+    // stored one for this session
+    let env_key = std::env::var("MAILCHIMP_API_KEY").ok();
+    let (api_key, from_env) = resolve_api_key(&settings.mailchimp_api_key, settings.prefer_env_api_key, env_key.as_deref());
+    settings.mailchimp_api_key = api_key;
+    settings.api_key_from_env = from_env;
+
     println!("Parsed settings: {:?}", settings);
     
     Ok(settings)
@@ -191,6 +995,42 @@ fn load_settings(app: tauri::AppHandle) -> Result<Settings, String> {
 
 #[tauri::command]
 fn save_settings(app: tauri::AppHandle, settings: Settings) -> Result<(), String> {
+    // Catch bad custom metric expressions before they hit disk
+    for metric in &settings.custom_metrics {
+        validate_custom_metric(metric)?;
+    }
+
+    if settings.min_concurrency == 0 || settings.max_concurrency < settings.min_concurrency {
+        return Err(format!(
+            "Invalid concurrency bounds: min {} / max {}",
+            settings.min_concurrency, settings.max_concurrency
+        ));
+    }
+
+    if !matches!(settings.csv_delimiter.as_str(), "," | ";" | "\t") {
+        return Err(format!("Unsupported CSV delimiter: {:?}", settings.csv_delimiter));
+    }
+    if !matches!(settings.decimal_separator.as_str(), "." | ",") {
+        return Err(format!("Unsupported decimal separator: {:?}", settings.decimal_separator));
+    }
+
+    if settings.max_backups > MAX_RETENTION {
+        return Err(format!("max_backups cannot exceed {}", MAX_RETENTION));
+    }
+    if settings.trash_size > MAX_RETENTION {
+        return Err(format!("trash_size cannot exceed {}", MAX_RETENTION));
+    }
+
+    // Reject a custom theme with malformed colors before it hits disk
+    if settings.export_theme.name == "custom" {
+        if !is_valid_hex_color(&settings.export_theme.header_color) {
+            return Err(format!("Invalid header color: {}", settings.export_theme.header_color));
+        }
+        if !is_valid_hex_color(&settings.export_theme.accent_color) {
+            return Err(format!("Invalid accent color: {}", settings.export_theme.accent_color));
+        }
+    }
+
     // Get the app config directory
     let app_dir = app.path().app_config_dir()
         .map_err(|e| format!("Could not get app directory: {}", e))?;
@@ -213,6 +1053,18 @@ fn save_settings(app: tauri::AppHandle, settings: Settings) -> Result<(), String
     let settings_path = app_dir.join("settings.json");
     println!("Settings file path: {:?}", settings_path);
 
+    // When the env key is in effect the incoming struct carries it; restore
+    // whatever key the file already holds so the env key stays off disk
+    let mut settings = settings;
+    if settings.api_key_from_env {
+        settings.mailchimp_api_key = fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+            .and_then(|v| v.get("mailchimp_api_key").and_then(|k| k.as_str()).map(String::from))
+            .unwrap_or_default();
+        settings.api_key_from_env = false;
+    }
+
     // Serialize the settings to JSON
     let settings_str = serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
@@ -242,40 +1094,480 @@ fn save_settings(app: tauri::AppHandle, settings: Settings) -> Result<(), String
     }
 }
 
+// Picks the API key the pipeline should use. With prefer_env set, a
+// non-empty value from the MAILCHIMP_API_KEY env var wins over the stored
+// key; the bool reports which one won so the UI can say so.
+fn resolve_api_key(stored: &str, prefer_env: bool, env_value: Option<&str>) -> (String, bool) {
+    if prefer_env {
+        if let Some(value) = env_value {
+            let trimmed = value.trim();
+            if !trimmed.is_empty() {
+                return (trimmed.to_string(), true);
+            }
+        }
+    }
+    (stored.to_string(), false)
+}
+
+// Normalizes one advertiser name for storage: trimmed, with internal runs
+// of whitespace collapsed to single spaces
+fn normalize_advertiser_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Cleans an advertiser list: spellings normalized, merges applied, and
+// exact or case-insensitive duplicates dropped keeping the first spelling
+fn dedupe_advertiser_list(advertisers: &[String], merges: &HashMap<String, String>) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cleaned = Vec::new();
+
+    for name in advertisers {
+        let mut normalized = normalize_advertiser_name(name);
+        // Fold near-duplicates ("N.J.U.A." -> "NJUA") before deduping
+        if let Some(target) = merges.get(&normalized) {
+            normalized = normalize_advertiser_name(target);
+        }
+        if normalized.is_empty() {
+            continue;
+        }
+        if seen.insert(normalized.to_lowercase()) {
+            cleaned.push(normalized);
+        }
+    }
+
+    cleaned
+}
+
+// Settings hygiene: collapses the duplicate advertiser entries manual edits
+// accumulate. merges optionally maps near-duplicate spellings to the name
+// they should fold into. Saves and returns the cleaned list.
 #[tauri::command]
-fn load_reports(app: tauri::AppHandle) -> Result<Vec<SavedReport>, String> {
-    let app_dir = app.path().app_config_dir()
-        .map_err(|e| format!("Could not get app directory: {}", e))?;
-    let reports_path = app_dir.join("reports.json");
+fn dedupe_advertisers(app: tauri::AppHandle, merges: Option<HashMap<String, String>>) -> Result<Vec<String>, String> {
+    let mut settings = load_settings(app.clone())?;
 
-    if !reports_path.exists() {
-        return Ok(Vec::new());
+    let cleaned = dedupe_advertiser_list(&settings.advertisers, &merges.unwrap_or_default());
+    println!("Deduped advertisers: {} entries down to {}", settings.advertisers.len(), cleaned.len());
+
+    settings.advertisers = cleaned.clone();
+    save_settings(app, settings)?;
+
+    Ok(cleaned)
+}
+
+// Profile names become file names under the config dir, so restrict them
+// to a safe character set
+fn validate_profile_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if !trimmed.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_')) {
+        return Err(format!("Invalid profile name: {}", trimmed));
     }
+    Ok(trimmed.to_string())
+}
 
-    let reports_str = fs::read_to_string(&reports_path)
-        .map_err(|e| format!("Failed to read reports: {}", e))?;
-    
-    println!("Loading reports from: {:?}", reports_path);
-    
-    // First try to parse as raw JSON to handle missing fields
-    let reports_json: Vec<serde_json::Value> = serde_json::from_str(&reports_str)
-        .map_err(|e| format!("Failed to parse reports JSON: {}", e))?;
-    
-    // Convert each report with proper field handling
-    let mut converted_reports = Vec::new();
-    for report_json in reports_json {
-        let default_metrics = Metrics {
-            unique_opens: true,
-            total_opens: true,
-            total_recipients: true,
-            total_clicks: true,
-            ctr: true,
-        };
+// Snapshots the current settings.json under profiles/<name>.json and marks
+// that profile active, since the live settings and the snapshot now agree
+fn save_profile_in_dir(app_dir: &Path, name: &str) -> Result<(), String> {
+    let name = validate_profile_name(name)?;
 
-        let report = SavedReport {
-            id: report_json.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-            name: report_json.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-            advertiser: report_json.get("advertiser").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+    let settings_str = fs::read_to_string(app_dir.join("settings.json"))
+        .map_err(|e| format!("No saved settings to snapshot: {}", e))?;
+
+    // Catch a corrupt settings file before it becomes a profile
+    serde_json::from_str::<Settings>(&settings_str)
+        .map_err(|e| format!("Current settings are not valid: {}", e))?;
+
+    let profiles = app_dir.join("profiles");
+    fs::create_dir_all(&profiles)
+        .map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+    fs::write(profiles.join(format!("{}.json", name)), &settings_str)
+        .map_err(|e| format!("Failed to write profile: {}", e))?;
+    fs::write(app_dir.join("active_profile"), name.as_bytes())
+        .map_err(|e| format!("Failed to record active profile: {}", e))?;
+
+    println!("Saved settings profile '{}'", name);
+    Ok(())
+}
+
+// Atomically switches settings.json to the named profile. The profile is
+// validated and staged next to the live file, then swapped in with a
+// rename so a crash can't leave half-written settings. load_settings keeps
+// returning the active profile afterwards since it reads settings.json.
+fn load_profile_in_dir(app_dir: &Path, name: &str) -> Result<Settings, String> {
+    let name = validate_profile_name(name)?;
+
+    let profile_path = app_dir.join("profiles").join(format!("{}.json", name));
+    let profile_str = fs::read_to_string(&profile_path)
+        .map_err(|e| format!("Profile '{}' not found: {}", name, e))?;
+
+    let settings: Settings = serde_json::from_str(&profile_str)
+        .map_err(|e| format!("Profile '{}' is not valid: {}", name, e))?;
+
+    let staged = app_dir.join("settings.json.tmp");
+    fs::write(&staged, &profile_str)
+        .map_err(|e| format!("Failed to stage profile: {}", e))?;
+    fs::rename(&staged, app_dir.join("settings.json"))
+        .map_err(|e| format!("Failed to activate profile: {}", e))?;
+    fs::write(app_dir.join("active_profile"), name.as_bytes())
+        .map_err(|e| format!("Failed to record active profile: {}", e))?;
+
+    println!("Switched to settings profile '{}'", name);
+    Ok(settings)
+}
+
+fn list_profiles_in_dir(app_dir: &Path) -> Result<Vec<String>, String> {
+    let profiles = app_dir.join("profiles");
+    if !profiles.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    let entries = fs::read_dir(&profiles)
+        .map_err(|e| format!("Failed to read profiles directory: {}", e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read profiles directory: {}", e))?;
+        let path = entry.path();
+        if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+// The profile recorded as active, surviving restarts via its marker file
+fn active_profile_in_dir(app_dir: &Path) -> Option<String> {
+    fs::read_to_string(app_dir.join("active_profile"))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn delete_profile_in_dir(app_dir: &Path, name: &str) -> Result<(), String> {
+    let name = validate_profile_name(name)?;
+
+    // Deleting the profile the app is running on would strand the marker
+    if active_profile_in_dir(app_dir).as_deref() == Some(name.as_str()) {
+        return Err(format!("Profile '{}' is active; switch profiles before deleting it", name));
+    }
+
+    let profile_path = app_dir.join("profiles").join(format!("{}.json", name));
+    if !profile_path.exists() {
+        return Err(format!("Profile '{}' not found", name));
+    }
+    fs::remove_file(&profile_path)
+        .map_err(|e| format!("Failed to delete profile: {}", e))?;
+
+    println!("Deleted settings profile '{}'", name);
+    Ok(())
+}
+
+#[tauri::command]
+fn save_profile(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    save_profile_in_dir(&app_dir, &name)
+}
+
+#[tauri::command]
+fn load_profile(app: tauri::AppHandle, name: String) -> Result<Settings, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    load_profile_in_dir(&app_dir, &name)
+}
+
+#[tauri::command]
+fn list_profiles(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    list_profiles_in_dir(&app_dir)
+}
+
+#[tauri::command]
+fn delete_profile(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    delete_profile_in_dir(&app_dir, &name)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MigrationSummary {
+    total: usize,
+    upgraded: usize,
+    backup_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ReportSize {
+    id: String,
+    name: String,
+    bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct StorageStats {
+    report_count: usize,
+    total_bytes: u64,
+    largest_reports: Vec<ReportSize>,
+}
+
+// Read-only look at what the stored reports are costing on disk, so users can
+// decide what to purge
+#[tauri::command]
+fn reports_storage_stats(app: tauri::AppHandle) -> Result<StorageStats, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let reports_path = app_dir.join("reports.json");
+
+    let total_bytes = fs::metadata(&reports_path)
+        .map(|m| m.len())
+        .unwrap_or(0);
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report_count = reports.len();
+
+    // Rank reports by their serialized size to find the heavy ones
+    let mut sizes: Vec<ReportSize> = reports.iter()
+        .map(|r| ReportSize {
+            id: r.id.clone(),
+            name: r.name.clone(),
+            bytes: serde_json::to_string(r).map(|s| s.len() as u64).unwrap_or(0),
+        })
+        .collect();
+    sizes.sort_by_key(|s| std::cmp::Reverse(s.bytes));
+    sizes.truncate(5);
+
+    Ok(StorageStats {
+        report_count,
+        total_bytes,
+        largest_reports: sizes,
+    })
+}
+
+// One-time normalization: loads every report through the lenient parser and
+// rewrites the file in the canonical SavedReport shape, so missing fields get
+// their defaults filled once instead of on every load
+fn migrate_reports_in_dir(app_dir: &Path, max_backups: usize) -> Result<MigrationSummary, String> {
+    let reports_path = app_dir.join("reports.json");
+
+    if !reports_path.exists() {
+        return Ok(MigrationSummary {
+            total: 0,
+            upgraded: 0,
+            backup_path: String::new(),
+        });
+    }
+
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    // Back up the file as-is before touching it
+    let backup_path = back_up_reports_file(app_dir, max_backups)?;
+
+    let reports_str = fs::read_to_string(&reports_path)
+        .map_err(|e| format!("Failed to read reports: {}", e))?;
+    let raw_reports: Vec<serde_json::Value> = serde_json::from_str(&reports_str)
+        .map_err(|e| format!("Failed to parse reports JSON: {}", e))?;
+
+    let converted = load_reports_from_dir(app_dir)?;
+
+    // A report counts as upgraded when its canonical shape differs from what
+    // was stored (missing fields, defaults filled, stale keys dropped)
+    let mut upgraded = 0;
+    for (raw, report) in raw_reports.iter().zip(converted.iter()) {
+        let canonical = serde_json::to_value(report)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?;
+        if *raw != canonical {
+            upgraded += 1;
+        }
+    }
+
+    write_reports_to_dir(app_dir, &converted)?;
+
+    println!("Migrated reports file: {} total, {} upgraded", converted.len(), upgraded);
+
+    Ok(MigrationSummary {
+        total: converted.len(),
+        upgraded,
+        backup_path,
+    })
+}
+
+// Copies reports.json into the backups folder with a sortable timestamped
+// name, then prunes the folder down to the configured retention. A lowered
+// max_backups takes effect on the next write.
+fn back_up_reports_file(app_dir: &Path, max_backups: usize) -> Result<String, String> {
+    let reports_path = app_dir.join("reports.json");
+    let backup_dir = app_dir.join("backups");
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create backups directory: {}", e))?;
+
+    let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0);
+    let backup_path = backup_dir.join(format!("reports-{:020}.json", nanos));
+    fs::copy(&reports_path, &backup_path)
+        .map_err(|e| format!("Failed to back up reports: {}", e))?;
+
+    prune_backups(&backup_dir, max_backups)?;
+
+    Ok(backup_path.to_string_lossy().to_string())
+}
+
+// Drops the oldest backups until only max_backups remain. The zero-padded
+// timestamp in the name makes lexicographic order chronological.
+fn prune_backups(backup_dir: &Path, max_backups: usize) -> Result<(), String> {
+    let mut backups: Vec<std::path::PathBuf> = fs::read_dir(backup_dir)
+        .map_err(|e| format!("Failed to read backups directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+        .collect();
+    backups.sort();
+
+    while backups.len() > max_backups {
+        let oldest = backups.remove(0);
+        println!("Pruning old backup: {}", oldest.display());
+        fs::remove_file(&oldest)
+            .map_err(|e| format!("Failed to prune backup: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// Moves a deleted report into trash.json so it can be restored, evicting
+// the oldest entries beyond the configured trash size
+fn push_to_trash(app_dir: &Path, report: SavedReport, trash_size: usize) -> Result<(), String> {
+    let trash_path = app_dir.join("trash.json");
+
+    let mut trash: Vec<SavedReport> = fs::read_to_string(&trash_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+
+    trash.push(report);
+    if trash.len() > trash_size {
+        let excess = trash.len() - trash_size;
+        trash.drain(..excess);
+    }
+
+    let json = serde_json::to_string_pretty(&trash)
+        .map_err(|e| format!("Failed to serialize trash: {}", e))?;
+    fs::write(&trash_path, json)
+        .map_err(|e| format!("Failed to write trash: {}", e))
+}
+
+// Emits a count-based progress update for long-running bulk commands
+// (migrate, export-all, purge), mirroring the report-progress events that
+// generate_report sends so the UI can reuse the same progress bar
+fn emit_bulk_progress(app: &tauri::AppHandle, operation: &str, current: usize, total: usize) {
+    let progress = (current * 100).checked_div(total).unwrap_or(100) as u8;
+
+    let update = ProgressUpdate {
+        stage: operation.to_string(),
+        progress,
+        message: format!("{}: {} of {} items", operation, current, total),
+        time_remaining: None,
+        elapsed_secs: None,
+    };
+
+    if let Err(e) = app.emit("bulk-progress", update) {
+        println!("Failed to emit bulk progress: {:?}", e);
+    }
+}
+
+#[tauri::command]
+fn migrate_reports(app: tauri::AppHandle) -> Result<MigrationSummary, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let settings = load_settings(app.clone())?;
+
+    emit_bulk_progress(&app, "Migrating reports", 0, 1);
+    let summary = migrate_reports_in_dir(&app_dir, settings.max_backups)?;
+    emit_bulk_progress(&app, "Migrating reports", summary.total.max(1), summary.total.max(1));
+
+    Ok(summary)
+}
+
+// Takes an OS advisory lock on a sidecar lock file so concurrent processes
+// can't interleave their read-modify-write of reports.json. The lock is
+// released when the returned File is dropped.
+fn lock_reports_file(app_dir: &Path) -> Result<File, String> {
+    fs::create_dir_all(app_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let lock_path = app_dir.join("reports.json.lock");
+    let lock_file = File::create(&lock_path)
+        .map_err(|e| format!("Failed to create reports lock file: {}", e))?;
+    lock_file.lock_exclusive()
+        .map_err(|e| format!("Failed to lock reports file: {}", e))?;
+    Ok(lock_file)
+}
+
+// Writes the full reports list atomically: temp file in the same directory,
+// then rename over the real file so a crash mid-write can't corrupt it.
+fn write_reports_to_dir(app_dir: &Path, reports: &[SavedReport]) -> Result<(), String> {
+    let reports_str = serde_json::to_string_pretty(reports)
+        .map_err(|e| format!("Failed to serialize reports: {}", e))?;
+
+    let tmp_path = app_dir.join("reports.json.tmp");
+    fs::write(&tmp_path, &reports_str)
+        .map_err(|e| format!("Failed to write reports: {}", e))?;
+    fs::rename(&tmp_path, app_dir.join("reports.json"))
+        .map_err(|e| format!("Failed to replace reports file: {}", e))
+}
+
+fn save_report_to_dir(app_dir: &Path, report: SavedReport) -> Result<(), String> {
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    let mut reports = load_reports_from_dir(app_dir)?;
+    reports.push(report);
+    write_reports_to_dir(app_dir, &reports)
+}
+
+fn load_reports_from_dir(app_dir: &Path) -> Result<Vec<SavedReport>, String> {
+    let reports_path = app_dir.join("reports.json");
+
+    if !reports_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reports_str = fs::read_to_string(&reports_path)
+        .map_err(|e| format!("Failed to read reports: {}", e))?;
+    
+    println!("Loading reports from: {:?}", reports_path);
+    
+    // First try to parse as raw JSON to handle missing fields
+    let reports_json: Vec<serde_json::Value> = serde_json::from_str(&reports_str)
+        .map_err(|e| format!("Failed to parse reports JSON: {}", e))?;
+    
+    // Convert each report with proper field handling
+    let mut converted_reports = Vec::new();
+    for report_json in reports_json {
+        let default_metrics = Metrics {
+            unique_opens: true,
+            total_opens: true,
+            total_recipients: true,
+            total_clicks: true,
+            ctr: true,
+            clicks_per_thousand: false,
+            tags: false,
+            share_of_clicks: false,
+            admin_url: false,
+            variate_winner: false,
+            mailchimp_rates: false,
+            targeted_count: false,
+            bounce_detail: false,
+        };
+
+        let report = SavedReport {
+            id: report_json.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            name: report_json.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            advertiser: report_json.get("advertiser").and_then(|v| v.as_str()).unwrap_or("").to_string(),
             report_type: report_json.get("report_type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
             date_range: match report_json.get("date_range") {
                 Some(dr) => serde_json::from_value(dr.clone())
@@ -293,6 +1585,21 @@ fn load_reports(app: tauri::AppHandle) -> Result<Vec<SavedReport>, String> {
             metrics: report_json.get("metrics")
                 .and_then(|m| serde_json::from_value(m.clone()).ok())
                 .unwrap_or(default_metrics),
+            tags: report_json.get("tags")
+                .and_then(|t| serde_json::from_value(t.clone()).ok())
+                .unwrap_or_default(),
+            read_only: report_json.get("read_only")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            elapsed_secs: report_json.get("elapsed_secs")
+                .and_then(|v| v.as_u64()),
+            campaign_count: report_json.get("campaign_count")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as usize),
+            notes: report_json.get("notes")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
         };
         
         converted_reports.push(report);
@@ -302,879 +1609,9087 @@ fn load_reports(app: tauri::AppHandle) -> Result<Vec<SavedReport>, String> {
     Ok(converted_reports)
 }
 
+// Folds shared-directory reports into the local list for display. Local
+// reports win id collisions, and everything merged in is flagged read-only
+// so write commands can refuse it.
+fn merge_shared_reports(local: Vec<SavedReport>, shared: Vec<SavedReport>) -> Vec<SavedReport> {
+    let mut merged = local;
+    for mut report in shared {
+        if merged.iter().any(|r| r.id == report.id) {
+            continue;
+        }
+        report.read_only = true;
+        merged.push(report);
+    }
+    merged
+}
+
 #[tauri::command]
-fn save_report(app: tauri::AppHandle, report: SavedReport) -> Result<(), String> {
+fn load_reports(app: tauri::AppHandle) -> Result<Vec<SavedReport>, String> {
     let app_dir = app.path().app_config_dir()
         .map_err(|e| format!("Could not get app directory: {}", e))?;
-    
-    // Create the config directory if it doesn't exist
-    fs::create_dir_all(&app_dir)
-        .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    
-    let reports_path = app_dir.join("reports.json");
-    let mut reports = load_reports(app.clone())?;
-    
-    // Add new report
-    reports.push(report);
+    let local = load_reports_from_dir(&app_dir)?;
 
-    let reports_str = serde_json::to_string_pretty(&reports)
-        .map_err(|e| format!("Failed to serialize reports: {}", e))?;
+    let settings = load_settings(app.clone())?;
+    let shared = match settings.shared_reports_dir.as_deref() {
+        Some(dir) if !dir.is_empty() => {
+            // An unreachable network folder shouldn't hide local reports,
+            // so shared load failures only log
+            match load_reports_from_dir(Path::new(dir)) {
+                Ok(reports) => reports,
+                Err(e) => {
+                    println!("Skipping shared reports directory: {}", e);
+                    Vec::new()
+                }
+            }
+        }
+        _ => Vec::new(),
+    };
 
-    fs::write(&reports_path, reports_str)
-        .map_err(|e| format!("Failed to write reports: {}", e))
+    Ok(merge_shared_reports(local, shared))
 }
 
-// Add these validation functions before the generate_report function
-fn validate_tracking_urls(urls: &[String]) -> Result<(), String> {
-    if urls.is_empty() {
-        return Err("No tracking URLs provided".to_string());
-    }
+#[tauri::command]
+fn save_report(app: tauri::AppHandle, report: SavedReport) -> Result<(), String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    save_report_to_dir(&app_dir, report)
+}
 
-    for url in urls {
-        if url.is_empty() {
-            continue; // Skip empty URLs as they're handled separately
-        }
+// Splits one CSV line into cells, honoring double-quoted cells with ""
+// escapes (the format Mailchimp exports use)
+fn parse_csv_line(line: &str) -> Vec<String> {
+    parse_csv_line_with(line, ',')
+}
 
-        // Check if it's a valid URL or URL path component
-        if !url.starts_with("http://") && !url.starts_with("https://") {
-            // If not a full URL, check if it's at least a valid URL path component
-            if url.contains(char::is_whitespace) || url.contains('<') || url.contains('>') {
-                return Err(format!("Invalid tracking URL or path component: {}", url));
+fn parse_csv_line_with(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
             }
-        } else {
-            // If it's meant to be a full URL, validate it properly
-            if let Err(_) = Url::parse(url) {
-                return Err(format!("Invalid tracking URL format: {}", url));
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => {
+                cells.push(current.clone());
+                current.clear();
             }
+            _ => current.push(c),
         }
     }
-    Ok(())
+    cells.push(current);
+    cells
 }
 
-fn validate_campaign_data(campaigns: &[serde_json::Value], newsletter_type: &str) -> Result<(), String> {
-    if campaigns.is_empty() {
-        return Err("No campaigns found for the specified date range".to_string());
+// Parses a campaign export CSV into the same JSON shape the Mailchimp API
+// returns, so the offline pipeline can reuse the regular matching helpers.
+//
+// Expected header columns (case-insensitive): title, send_date,
+// unique_opens, total_opens, total_recipients. Optional: clicked_url and
+// url_clicks, with one row per clicked URL; rows sharing a title and
+// send_date are merged into one campaign.
+fn parse_campaign_csv(contents: &str) -> Result<Vec<serde_json::Value>, String> {
+    let mut lines = contents.lines().filter(|l| !l.trim().is_empty());
+
+    let header_line = lines.next()
+        .ok_or_else(|| "CSV file is empty".to_string())?;
+    let header: Vec<String> = parse_csv_line(header_line)
+        .iter()
+        .map(|h| h.trim().to_lowercase().replace(' ', "_"))
+        .collect();
+
+    let col = |name: &str| header.iter().position(|h| h == name);
+    let required = ["title", "send_date", "unique_opens", "total_opens", "total_recipients"];
+    for name in required {
+        if col(name).is_none() {
+            return Err(format!(
+                "CSV is missing the '{}' column. Expected columns: {} (optionally clicked_url, url_clicks)",
+                name,
+                required.join(", ")
+            ));
+        }
     }
 
-    let newsletter_type_lower = newsletter_type.to_lowercase();
-    let mut matching_campaigns = 0;
+    let title_col = col("title").unwrap();
+    let date_col = col("send_date").unwrap();
+    let uo_col = col("unique_opens").unwrap();
+    let to_col = col("total_opens").unwrap();
+    let tr_col = col("total_recipients").unwrap();
+    let url_col = col("clicked_url");
+    let clicks_col = col("url_clicks");
 
-    for campaign in campaigns {
-        if let Some(settings) = campaign.get("settings") {
-            if let Some(title) = settings.get("title").and_then(|t| t.as_str()) {
-                let title_lower = title.to_lowercase();
-                
-                let matches = if newsletter_type_lower == "hc" {
-                    title_lower.contains("hc") || title_lower.contains("health care")
-                } else {
-                    title_lower.contains(&newsletter_type_lower)
-                };
-                
-                if matches {
-                    matching_campaigns += 1;
+    let mut campaigns: Vec<serde_json::Value> = Vec::new();
+
+    for (line_number, line) in lines.enumerate() {
+        let cells = parse_csv_line(line);
+        let cell = |i: usize| cells.get(i).map(|c| c.trim()).unwrap_or("");
+        let number = |i: usize| cell(i).replace(',', "").parse::<u64>().unwrap_or(0);
+
+        let title = cell(title_col).to_string();
+        let send_date = cell(date_col).to_string();
+        if title.is_empty() || send_date.is_empty() {
+            return Err(format!("CSV row {} is missing a title or send_date", line_number + 2));
+        }
+
+        // Merge per-URL rows into their campaign
+        let existing = campaigns.iter_mut().find(|c| {
+            c.get("settings").and_then(|s| s.get("title")).and_then(|t| t.as_str()) == Some(title.as_str())
+                && c.get("send_time").and_then(|t| t.as_str()) == Some(send_date.as_str())
+        });
+
+        let campaign = match existing {
+            Some(campaign) => campaign,
+            None => {
+                campaigns.push(serde_json::json!({
+                    "settings": { "title": title },
+                    "send_time": send_date,
+                    "emails_sent": number(tr_col),
+                    "report_summary": {
+                        "unique_opens": number(uo_col),
+                        "opens": number(to_col)
+                    },
+                    "urls_clicked": []
+                }));
+                campaigns.last_mut().unwrap()
+            }
+        };
+
+        if let (Some(url_col), Some(clicks_col)) = (url_col, clicks_col) {
+            let url = cell(url_col);
+            if !url.is_empty() {
+                if let Some(urls) = campaign.get_mut("urls_clicked").and_then(|u| u.as_array_mut()) {
+                    urls.push(serde_json::json!({
+                        "url": url,
+                        "total_clicks": number(clicks_col)
+                    }));
                 }
             }
         }
     }
 
-    if matching_campaigns == 0 {
-        return Err(format!(
-            "No campaigns found matching the newsletter type '{}'. Please check if the newsletter type is correct.",
-            newsletter_type
-        ));
-    }
-
-    Ok(())
+    Ok(campaigns)
 }
 
+// Runs the report pipeline against a Mailchimp campaign export CSV instead
+// of the live API, for when there's no usable key. Produces and saves the
+// same report structure as generate_report.
 #[tauri::command]
-async fn generate_report(app: tauri::AppHandle, request: ReportRequest) -> Result<ReportResponse, String> {
-    // Validate tracking URLs first
-    validate_tracking_urls(&request.tracking_urls)?;
+fn generate_report_from_csv(app: tauri::AppHandle, csv_path: String, request: ReportRequest) -> Result<ReportResponse, String> {
+    let tracking_urls = validate_tracking_urls(&request.tracking_urls)?;
+    let settings = load_settings(app.clone())?;
 
-    // Init progress tracking with start time
-    let start_time = std::time::Instant::now();
-    let mut progress_updates = Vec::new();
-    
-    // First progress update
-    let initial_update = ProgressUpdate {
-        stage: "Initializing".to_string(),
-        progress: 0,
-        message: "Starting report generation...".to_string(),
-        time_remaining: None,
-    };
-    
-    // Store in vector and emit to frontend
-    progress_updates.push(initial_update.clone());
-    
-    // Emit the progress update to the frontend
-    if let Err(e) = app.emit("report-progress", initial_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
-
-    // Load settings
-    let settings = load_settings(app.clone())?;
-    
-    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
-        return Ok(ReportResponse {
-            success: false,
-            message: "Mailchimp API settings not configured".to_string(),
-            data: None,
-            progress_updates,
-        });
-    }
-
-    // 10% progress
-    let connecting_update = ProgressUpdate {
-        stage: "FetchingCampaigns".to_string(),
-        progress: 10,
-        message: "Connecting to Mailchimp API...".to_string(),
-        time_remaining: None,
-    };
-    
-    // Store and emit update
-    progress_updates.push(connecting_update.clone());
-    if let Err(e) = app.emit("report-progress", connecting_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
-
-    // Create Mailchimp API client
-    let client = reqwest::Client::new();
-    let dc = settings.mailchimp_api_key.split('-').last().unwrap_or("us1");
-    let base_url = format!("https://{}.api.mailchimp.com/3.0", dc);
-
-    // Format dates for the API call - convert to ISO format
-    let start_date_iso = format!("{}T00:00:00Z", &request.date_range.start_date);
-    let end_date = chrono::NaiveDate::parse_from_str(&request.date_range.end_date, "%Y-%m-%d")
-        .map_err(|e| format!("Failed to parse end date: {}", e))?;
-    // Add one day to end date and subtract one second (as in Python script)
-    let end_date_iso = format!("{}T23:59:59Z", end_date);
-    
-    // Fetch campaigns for the date range
-    let campaigns_url = format!(
-        "{}/campaigns?since_send_time={}&before_send_time={}&count=1000", 
-        base_url, start_date_iso, end_date_iso
-    );
-    
-    // 20% progress
-    let fetching_update = ProgressUpdate {
-        stage: "FetchingCampaigns".to_string(),
-        progress: 20,
-        message: "Fetching campaign data from Mailchimp...".to_string(),
-        time_remaining: None,
-    };
-    
-    // Store and emit update
-    progress_updates.push(fetching_update.clone());
-    if let Err(e) = app.emit("report-progress", fetching_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
-    
-    let campaigns_response = client
-        .get(&campaigns_url)
-        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?;
+    let contents = fs::read_to_string(&csv_path)
+        .map_err(|e| format!("Failed to read CSV file '{}': {}", csv_path, e))?;
+    let campaigns = parse_campaign_csv(&contents)?;
 
-    if !campaigns_response.status().is_success() {
-        let error_text = campaigns_response.text().await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Ok(ReportResponse {
-            success: false,
-            message: format!("Mailchimp API error: {}", error_text),
-            data: None,
-            progress_updates,
-        });
-    }
+    // Same window semantics as the campaigns API query
+    let (range_start, range_end) = date_range_bounds(&request.date_range)?;
 
-    let campaigns_data = campaigns_response.json::<serde_json::Value>().await
-        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
-    
-    // Get the actual campaigns array
-    let campaigns = match campaigns_data.get("campaigns") {
-        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
-        _ => {
-            return Ok(ReportResponse {
-                success: false,
-                message: "No campaigns found in response".to_string(),
-                data: None,
-                progress_updates,
-            });
-        }
-    };
-    
-    // After fetching campaigns, validate the campaign data
-    validate_campaign_data(campaigns, &request.newsletter_type)?;
+    let filtered = filter_campaigns_by_type(&campaigns, &request.newsletter_type);
 
-    // 30% progress
-    let filtering_update = ProgressUpdate {
-        stage: "FilteringCampaigns".to_string(),
-        progress: 30,
-        message: format!("Found {} campaigns. Filtering by newsletter type...", campaigns.len()),
-        time_remaining: None,
-    };
-    
-    // Store and emit update
-    progress_updates.push(filtering_update.clone());
-    if let Err(e) = app.emit("report-progress", filtering_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
-    
-    // Filter campaigns by newsletter type
-    let mut filtered_campaigns = Vec::new();
-    let newsletter_type_lower = request.newsletter_type.to_lowercase();
-    
-    for campaign in campaigns {
-        if let Some(settings) = campaign.get("settings") {
-            if let Some(title) = settings.get("title").and_then(|t| t.as_str()) {
-                let title_lower = title.to_lowercase();
-                
-                // Apply the same filtering logic as in Python
-                let matches = if newsletter_type_lower == "hc" {
-                    title_lower.contains("hc") || title_lower.contains("health care")
-                } else {
-                    title_lower.contains(&newsletter_type_lower)
-                };
-                
-                if matches {
-                    filtered_campaigns.push(campaign.clone());
-                }
-            }
-        }
-    }
-    
-    // 40% progress
-    let initial_processing_update = ProgressUpdate {
-        stage: "ProcessingCampaigns".to_string(),
-        progress: 40,
-        message: format!("Processing {} campaigns...", filtered_campaigns.len()),
-        time_remaining: Some((filtered_campaigns.len() as f64 * 0.5) as u64), // Initial estimate: 0.5 seconds per campaign
-    };
-    
-    progress_updates.push(initial_processing_update.clone());
-    if let Err(e) = app.emit("report-progress", initial_processing_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
-    
-    // Process each filtered campaign to analyze clicks for the specific ad URLs
     let mut report_data = Vec::new();
-    
-    // Calculate progress increment per campaign
-    let campaign_progress_increment = if filtered_campaigns.is_empty() {
-        0.0
-    } else {
-        40.0 / (filtered_campaigns.len() as f64)
-    };
-    
-    for (index, campaign) in filtered_campaigns.iter().enumerate() {
-        // Calculate current progress (40-80% is for campaign processing)
-        let current_progress = 40 + ((index as f64) * campaign_progress_increment) as u8;
-        
-        // Calculate time remaining based on actual processing rate
-        let elapsed = start_time.elapsed().as_secs_f64();
-        let time_remaining = if index > 0 {
-            // Calculate average time per campaign
-            let avg_time_per_campaign = elapsed / (index as f64);
-            // Calculate remaining campaigns
-            let remaining_campaigns = filtered_campaigns.len() - index;
-            // Estimate remaining time
-            let remaining_secs = avg_time_per_campaign * (remaining_campaigns as f64);
-            Some(remaining_secs.ceil() as u64)
-        } else {
-            // Initial estimate
-            Some((filtered_campaigns.len() as f64 * 0.5) as u64)
-        };
-        
-        // Add progress update for individual campaign
-        let campaign_update = ProgressUpdate {
-            stage: "ProcessingCampaigns".to_string(),
-            progress: current_progress,
-            message: format!("Processing campaign {} of {}: {}", 
-                index + 1, 
-                filtered_campaigns.len(),
-                campaign.get("settings")
-                    .and_then(|s| s.get("title"))
-                    .and_then(|t| t.as_str())
-                    .unwrap_or("Untitled")
-            ),
-            time_remaining,
+    for campaign in &filtered {
+        let send_date = campaign.get("send_time").and_then(|t| t.as_str()).unwrap_or("");
+        let send_instant = match parse_range_bound(send_date, false) {
+            Ok(instant) => instant,
+            Err(_) => continue,
         };
-        
-        // Store and emit update
-        progress_updates.push(campaign_update.clone());
-        if let Err(e) = app.emit("report-progress", campaign_update) {
-            println!("Failed to emit progress update: {}", e);
+        if send_instant < range_start || send_instant > range_end {
+            continue;
         }
-        
-        // Extract campaign ID and metrics
-        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
-            Some(id) => id,
-            None => continue, // Skip if no ID
-        };
-        
-        // Get campaign send time
-        let send_time = match campaign.get("send_time").and_then(|st| st.as_str()) {
-            Some(time) => time,
-            None => continue, // Skip if no send time
-        };
-        
-        // Format date as in Python script
-        let formatted_date = match chrono::DateTime::parse_from_rfc3339(send_time) {
-            Ok(dt) => dt.format("%Y-%m-%d").to_string(),
-            Err(_) => continue, // Skip if date can't be parsed
-        };
-        
-        // Extract basic metrics
+
         let report_summary = campaign.get("report_summary").unwrap_or(&serde_json::Value::Null);
         let unique_opens = report_summary.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0);
         let total_opens = report_summary.get("opens").and_then(|v| v.as_u64()).unwrap_or(0);
         let total_recipients = campaign.get("emails_sent").and_then(|v| v.as_u64()).unwrap_or(0);
-        
-        // Now fetch click details for this campaign
-        let mut ad_clicks: u64 = 0;
-        
-        // Set up click details API endpoint
-        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
-        
-        // Get click details
-        let click_response = client
-            .get(&click_url)
-            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
-            .send()
-            .await;
-        
-        if let Ok(response) = click_response {
-            if response.status().is_success() {
-                if let Ok(click_data) = response.json::<serde_json::Value>().await {
-                    if let Some(urls_clicked) = click_data.get("urls_clicked").and_then(|u| u.as_array()) {
-                        for url_item in urls_clicked {
-                            if let Some(url) = url_item.get("url").and_then(|u| u.as_str()) {
-                                // Check if the URL contains any of our tracking URLs
-                                for tracking_url in &request.tracking_urls {
-                                    if !tracking_url.is_empty() && url.contains(tracking_url) {
-                                        ad_clicks += url_item.get("total_clicks").and_then(|c| c.as_u64()).unwrap_or(0);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Calculate CTR
+
+        // The CSV rows carry urls_clicked in the API shape, so the regular
+        // matching helpers apply directly
+        let ad_clicks = count_matched_clicks(campaign, &tracking_urls, &request.path_match);
+        let campaign_total_clicks = count_total_clicks(campaign);
+
         let ctr = if unique_opens > 0 {
             (ad_clicks as f64 / unique_opens as f64) * 100.0
         } else {
             0.0
         };
-        
-        // Only include campaigns that had ad clicks (matching Python logic)
+        let clicks_per_thousand = if total_recipients > 0 {
+            (ad_clicks as f64 / total_recipients as f64) * 1000.0
+        } else {
+            0.0
+        };
+
         if ad_clicks > 0 {
-            let campaign_report = serde_json::json!({
-                "send_date": formatted_date,
+            report_data.push(serde_json::json!({
+                "send_date": send_date,
                 "unique_opens": unique_opens,
-                "total_opens": total_opens, 
+                "total_opens": total_opens,
                 "total_recipients": total_recipients,
                 "total_clicks": ad_clicks,
-                "ctr": ctr
-            });
-            
-            report_data.push(campaign_report);
+                "ctr": ctr,
+                "clicks_per_thousand": clicks_per_thousand,
+                "tags": [],
+                "campaign_total_clicks": campaign_total_clicks,
+                "share_of_clicks": share_of_clicks(ad_clicks, campaign_total_clicks)
+            }));
         }
     }
-    
-    // Modify the final success check to ensure we have actual data
-    if report_data.is_empty() {
+
+    if report_data.is_empty() && !request.allow_empty {
         return Ok(ReportResponse {
             success: false,
             message: format!(
-                "No data found for the specified tracking URLs in campaigns matching '{}'. Please verify your tracking URLs and newsletter type.",
+                "No data found for the specified tracking URLs in the CSV's campaigns matching '{}'.",
                 request.newsletter_type
             ),
             data: None,
-            progress_updates,
+            progress_updates: Vec::new(),
+            url_suggestions: None,
+            warnings: Vec::new(),
         });
     }
+    let no_clicks_found = report_data.is_empty();
 
-    // 80% progress
-    let finalizing_update = ProgressUpdate {
-        stage: "FinalizingReport".to_string(),
-        progress: 80,
-        message: "Processing complete. Organizing report data...".to_string(),
-        time_remaining: Some(15), // Estimate 15 seconds for finalization
-    };
-    
-    // Store and emit update
-    progress_updates.push(finalizing_update.clone());
-    if let Err(e) = app.emit("report-progress", finalizing_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
-    
-    // Sort report data by date
     report_data.sort_by(|a, b| {
         let date_a = a.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
         let date_b = b.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
         date_a.cmp(date_b)
     });
-    
-    // Create the final report data
-    let final_report = serde_json::json!({
-        "campaigns": filtered_campaigns,
+
+    let mut final_report = serde_json::json!({
+        "campaigns": filtered,
         "report_data": report_data,
-        "metrics": request.metrics
+        "metrics": request.metrics,
+        "source": "csv"
     });
 
-    println!("Final report metrics: {:?}", request.metrics);
-    println!("Final report structure: {:?}", final_report);
-
-    // 90% progress
-    let saving_update = ProgressUpdate {
-        stage: "SavingReport".to_string(),
-        progress: 90,
-        message: "Finalizing and saving report...".to_string(),
-        time_remaining: Some(5),
-    };
-    
-    // Store and emit update
-    progress_updates.push(saving_update.clone());
-    if let Err(e) = app.emit("report-progress", saving_update) {
-        println!("Failed to emit progress update: {}", e);
+    // Nested grouped view alongside the flat rows
+    if let Some(group_by) = request.group_by.as_deref() {
+        final_report["report_groups"] = serde_json::json!(group_report_rows(&report_data, group_by));
     }
 
-    // Save the report with metrics
     let report = SavedReport {
         id: format!("report-{}", chrono::Utc::now().timestamp_millis()),
-        name: format!("{}-{}-{}", request.advertiser, request.newsletter_type, chrono::Utc::now().format("%Y-%m-%d")),
+        name: format!("{}-{}-{}", request.advertiser, request.newsletter_type, format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d")),
         advertiser: request.advertiser,
         report_type: request.newsletter_type,
         date_range: request.date_range.clone(),
-        created: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+        created: format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d"),
         data: final_report.clone(),
         metrics: request.metrics.clone(),
+        tags: Vec::new(),
+        read_only: false,
+        elapsed_secs: None,
+        campaign_count: None,
+        notes: String::new(),
     };
 
-    println!("About to save report with metrics: {:?}", report.metrics);
-    save_report(app.clone(), report.clone())?;
-
-    // Emit report-generated event with the complete report data
-    if let Err(e) = app.emit("report-generated", serde_json::json!({
-        "report": report
-    })) {
-        println!("Failed to emit report-generated event: {}", e);
-    }
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    save_report_to_dir(&app_dir, report)?;
 
-    // 100% progress
-    let complete_update = ProgressUpdate {
-        stage: "Complete".to_string(),
-        progress: 100,
-        message: "Report generation complete!".to_string(),
-        time_remaining: Some(0),
+    let message = if no_clicks_found {
+        "Report generated from CSV, but no clicks were found in this period".to_string()
+    } else {
+        "Report generated from CSV successfully".to_string()
     };
-    
-    // Store and emit update
-    progress_updates.push(complete_update.clone());
-    if let Err(e) = app.emit("report-progress", complete_update) {
-        println!("Failed to emit progress update: {}", e);
-    }
 
     Ok(ReportResponse {
         success: true,
-        message: "Report generated successfully".to_string(),
+        message,
         data: Some(final_report),
-        progress_updates,
+        progress_updates: Vec::new(),
+        url_suggestions: None,
+        warnings: Vec::new(),
     })
 }
 
+// Read-only guard the UI calls before generating: returns this
+// advertiser's saved reports whose date ranges intersect the requested one,
+// so the same sends don't get reported twice
 #[tauri::command]
-fn open_report_in_excel(_window: tauri::Window, reportData: serde_json::Value) -> Result<String, String> {
-    // Extract report data for CSV content
-    let report_data = reportData.get("data")
-        .ok_or_else(|| "Invalid report format: missing data field".to_string())?;
-    
-    // Get selected metrics from the report data
-    let metrics = report_data.get("metrics")
-        .ok_or_else(|| "Invalid report format: missing metrics".to_string())?;
-    
-    // Create CSV header based on selected metrics
-    let mut header_fields = vec!["Date"];
-    if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Unique Opens");
+fn find_overlapping_reports(app: tauri::AppHandle, advertiser: String, date_range: DateRange) -> Result<Vec<SavedReport>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let mut overlapping = Vec::new();
+
+    for report in reports {
+        if report.advertiser != advertiser {
+            continue;
+        }
+        // Reports with unparseable stored dates can't be checked; skip them
+        // rather than blocking the whole guard
+        if ranges_overlap(&report.date_range, &date_range).unwrap_or(false) {
+            overlapping.push(report);
+        }
     }
-    if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Total Opens");
+
+    Ok(overlapping)
+}
+
+// QR codes for each campaign in a saved report, for embedding in printed
+// decks. The UI gates this behind an export flag; campaigns lacking an
+// archive URL simply produce no section.
+#[tauri::command]
+fn report_qr_codes(app: tauri::AppHandle, report_id: String) -> Result<Vec<CampaignQr>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    Ok(campaign_qr_sections(&report.data))
+}
+
+// Builds a realistic-looking report from synthetic data so exports and the
+// reports list can be explored without a Mailchimp key. The data carries a
+// "sample": true flag so the UI can label it.
+#[tauri::command]
+fn generate_sample_report(app: tauri::AppHandle, advertiser: String) -> Result<SavedReport, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let settings = load_settings(app.clone())?;
+
+    // A handful of plausible weekly sends with believable ratios
+    let rows = [
+        ("2025-01-06", 9800u64, 3100u64, 4200u64, 41u64),
+        ("2025-01-13", 10150, 3240, 4410, 56),
+        ("2025-01-20", 10090, 2980, 4050, 38),
+        ("2025-01-27", 10320, 3375, 4590, 62),
+        ("2025-02-03", 10410, 3290, 4480, 47),
+    ];
+
+    let report_data: Vec<serde_json::Value> = rows.iter()
+        .map(|(date, recipients, unique_opens, total_opens, clicks)| {
+            let ctr = if *unique_opens > 0 {
+                (*clicks as f64 / *unique_opens as f64) * 100.0
+            } else {
+                0.0
+            };
+            serde_json::json!({
+                "send_date": date,
+                "unique_opens": unique_opens,
+                "total_opens": total_opens,
+                "total_recipients": recipients,
+                "total_clicks": clicks,
+                "ctr": ctr,
+                "clicks_per_thousand": (*clicks as f64 / *recipients as f64) * 1000.0,
+                "tags": [],
+                "campaign_total_clicks": clicks * 4,
+                "share_of_clicks": 25.0
+            })
+        })
+        .collect();
+
+    let report = SavedReport {
+        id: format!("sample-{}", chrono::Utc::now().timestamp_millis()),
+        name: format!("{}-sample-{}", advertiser, format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d")),
+        advertiser,
+        report_type: "AM".to_string(),
+        date_range: DateRange {
+            start_date: "2025-01-06".to_string(),
+            end_date: "2025-02-03".to_string(),
+        },
+        created: format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d"),
+        data: serde_json::json!({
+            "sample": true,
+            "campaigns": [],
+            "report_data": report_data,
+            "metrics": settings.default_metrics,
+        }),
+        metrics: settings.default_metrics.clone(),
+        tags: Vec::new(),
+        read_only: false,
+        elapsed_secs: None,
+        campaign_count: None,
+        notes: String::new(),
+    };
+
+    save_report_to_dir(&app_dir, report.clone())?;
+
+    Ok(report)
+}
+
+#[tauri::command]
+fn update_report_metrics(app: tauri::AppHandle, report_id: String, metrics: Metrics) -> Result<SavedReport, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(&app_dir)?;
+
+    let mut reports = load_reports_from_dir(&app_dir)?;
+
+    // Find the report to update
+    let report = reports.iter_mut()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("No report found with id: {}", report_id))?;
+
+    println!("Updating metrics for report {}: {:?}", report_id, metrics);
+
+    // Update the metrics flags on the report and inside the stored data,
+    // leaving the fetched numbers untouched so exports pick up the new selection
+    report.metrics = metrics.clone();
+    if let Some(data_obj) = report.data.as_object_mut() {
+        let metrics_value = serde_json::to_value(&metrics)
+            .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+        data_obj.insert("metrics".to_string(), metrics_value);
     }
-    if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Total Recipients");
+
+    let updated_report = report.clone();
+
+    write_reports_to_dir(&app_dir, &reports)?;
+
+    Ok(updated_report)
+}
+
+// Rewrites the advertiser field on every report carrying the old name.
+// Historical cleanup for names that drifted ("NJUA" vs "N.J.U.A.") and now
+// fragment per-advertiser filtering. A backup is taken before writing.
+fn rename_advertiser_in_dir(app_dir: &Path, old_name: &str, new_name: &str, max_backups: usize) -> Result<usize, String> {
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    let mut reports = load_reports_from_dir(app_dir)?;
+
+    let mut changed = 0;
+    for report in reports.iter_mut() {
+        if report.advertiser == old_name {
+            report.advertiser = new_name.to_string();
+            changed += 1;
+        }
     }
-    if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Total Clicks");
+
+    if changed == 0 {
+        return Ok(0);
     }
-    if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("CTR");
+
+    if app_dir.join("reports.json").exists() {
+        back_up_reports_file(app_dir, max_backups)?;
     }
-    
-    // Extract report metadata for filename
-    let advertiser = reportData.get("advertiser")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown-advertiser");
-    
-    let newsletter_type = reportData.get("report_type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown-type");
-    
-    // Extract date range for filename
-    let date_range = if let Some(range) = reportData.get("date_range") {
-        let start = range.get("start_date")
-            .and_then(|d| d.as_str())
-            .unwrap_or("");
-        
-        let end = range.get("end_date")
-            .and_then(|d| d.as_str())
-            .unwrap_or("");
-            
-        if !start.is_empty() && !end.is_empty() {
-            format!("{}_{}", start, end)
-        } else {
-            "unknown-dates".to_string()
+    write_reports_to_dir(app_dir, &reports)?;
+
+    println!("Renamed advertiser {:?} -> {:?} on {} reports", old_name, new_name, changed);
+    Ok(changed)
+}
+
+// Scans saved reports for the problems that accumulate over time: duplicate
+// or missing ids, empty or zeroed data, unparseable dates, non-finite CTRs,
+// and a stored metrics selection that disagrees with the report's own copy.
+// Purely diagnostic; nothing is modified.
+fn audit_report_list(reports: &[SavedReport]) -> Vec<ReportIssue> {
+    let mut issues = Vec::new();
+
+    for (index, report) in reports.iter().enumerate() {
+        let mut flag = |issue: String| issues.push(ReportIssue {
+            report_id: report.id.clone(),
+            issue,
+        });
+
+        if report.id.trim().is_empty() {
+            flag("Report has an empty id".to_string());
+        } else if reports[..index].iter().any(|r| r.id == report.id) {
+            flag(format!("Duplicate report id: {}", report.id));
         }
-    } else {
-        "unknown-dates".to_string()
-    };
-    
-    // Create a timestamp for uniqueness if needed
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    
-    // Create a clean advertiser name (remove special chars)
-    let clean_advertiser = advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
-    
-    // Get the system temp directory
-    let temp_dir = std::env::temp_dir();
-    
-    // Format the filename: Advertiser_NewsletterType_DateRange.csv
-    let file_name = format!("{}_{}_{}_{}.csv", 
-        clean_advertiser,
-        newsletter_type,
-        date_range,
-        timestamp
-    );
-    
-    let file_path = temp_dir.join(&file_name);
-    
-    // Create CSV content with dynamic headers
-    let mut csv = String::new();
-    csv.push_str(&header_fields.join(","));
-    csv.push('\n');
-    
-    // The report data is now in the "report_data" field
-    if let Some(report_entries) = report_data.get("report_data").and_then(|d| d.as_array()) {
-        // Report entries are already sorted by date in the backend
-        for entry in report_entries {
-            let mut row_fields = vec![entry.get("send_date").and_then(|d| d.as_str()).unwrap_or("N/A").to_string()];
-            
-            if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
-            }
-            if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("total_opens").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
+
+        if report.date_range.start_date > report.date_range.end_date {
+            flag(format!(
+                "Date range is inverted: {} to {}",
+                report.date_range.start_date, report.date_range.end_date
+            ));
+        }
+
+        let rows = report.data.get("report_data").and_then(|d| d.as_array());
+        let rows = match rows {
+            Some(rows) if !rows.is_empty() => rows,
+            _ => {
+                flag("Report has no data rows".to_string());
+                continue;
             }
-            if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
+        };
+
+        if rows.iter().all(|r| r.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0) == 0) {
+            flag("Every row has zero clicks".to_string());
+        }
+
+        for row in rows {
+            let date = row.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+            if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                flag(format!("Unparseable send_date: {:?}", date));
             }
-            if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
+            if let Some(ctr) = row.get("ctr").and_then(|v| v.as_f64()) {
+                if !ctr.is_finite() {
+                    flag(format!("Non-finite CTR on {}", date));
+                }
             }
-            if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(format!("{:.6}", entry.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0)));
+        }
+
+        // The data object carries its own metrics copy for the exporters;
+        // drift between the two means exports won't match the saved selection
+        if let Some(data_metrics) = report.data.get("metrics") {
+            let report_metrics = serde_json::to_value(&report.metrics).unwrap_or_default();
+            if *data_metrics != report_metrics {
+                flag("Stored metrics disagree with the report's data.metrics".to_string());
             }
-            
-            csv.push_str(&row_fields.join(","));
-            csv.push('\n');
         }
-    } else {
-        // If no report data found, create an empty report with headers only
-        csv.push_str("No campaign data found\n");
     }
-    
-    // Write the CSV content to the file
-    std::fs::write(&file_path, csv.as_bytes())
-        .map_err(|e| format!("Failed to write CSV: {}", e))?;
-    
-    // Return the file path as a string
-    file_path.to_str()
-        .ok_or_else(|| "Failed to get file path".to_string())
-        .map(|s| s.to_string())
+
+    issues
+}
+
+// The email-pasteable recap of one report, built from the stored rows and
+// the shared totals computation. Plain text on purpose; it goes straight
+// to the clipboard.
+fn text_summary_for(report: &SavedReport) -> String {
+    let rows = report.data.get("report_data")
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let totals = compute_totals(&rows);
+
+    let total_clicks = totals.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let ctr = totals.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+    let top = rows.iter()
+        .max_by_key(|r| r.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0));
+
+    let mut summary = format!(
+        "Advertiser: {}\nNewsletter: {}\nPeriod: {} to {}\nCampaigns with clicks: {}\nTotal clicks: {}\nOverall CTR: {:.2}%",
+        report.advertiser,
+        report.report_type,
+        report.date_range.start_date,
+        report.date_range.end_date,
+        rows.len(),
+        total_clicks,
+        ctr
+    );
+
+    if let Some(top) = top {
+        summary.push_str(&format!(
+            "\nTop campaign: {} ({} clicks)",
+            top.get("send_date").and_then(|d| d.as_str()).unwrap_or("N/A"),
+            top.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0)
+        ));
+    }
+
+    summary
 }
 
+// Returns a short text recap of a saved report for pasting into a client
+// email. Nothing is written to disk.
 #[tauri::command]
-fn write_report_file(path: String, report: serde_json::Value) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(&report)
-        .map_err(|e| format!("Failed to serialize report: {}", e))?;
-    let mut file = File::create(&path)
-        .map_err(|e| format!("Failed to create file: {}", e))?;
-    file.write_all(json.as_bytes())
-        .map_err(|e| format!("Failed to write file: {}", e))?;
-    Ok(())
+fn report_text_summary(app: tauri::AppHandle, report_id: String) -> Result<String, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    Ok(text_summary_for(report))
 }
 
+// Maintenance scan over every saved report. Read-only by design: fixes stay
+// in the operator's hands.
 #[tauri::command]
-fn delete_report(app: tauri::AppHandle, report_id: String) -> Result<(), String> {
+fn audit_reports(app: tauri::AppHandle) -> Result<Vec<ReportIssue>, String> {
     let app_dir = app.path().app_config_dir()
         .map_err(|e| format!("Could not get app directory: {}", e))?;
-    let reports_path = app_dir.join("reports.json");
 
-    if !reports_path.exists() {
-        return Ok(());
+    let reports = load_reports_from_dir(&app_dir)?;
+    let issues = audit_report_list(&reports);
+
+    println!("Audited {} reports: {} issues found", reports.len(), issues.len());
+    Ok(issues)
+}
+
+#[tauri::command]
+fn rename_advertiser_in_reports(app: tauri::AppHandle, old_name: String, new_name: String) -> Result<usize, String> {
+    if new_name.trim().is_empty() {
+        return Err("New advertiser name cannot be empty".to_string());
     }
 
-    let reports_str = fs::read_to_string(&reports_path)
-        .map_err(|e| format!("Failed to read reports: {}", e))?;
-    
-    let mut reports: Vec<SavedReport> = serde_json::from_str(&reports_str)
-        .map_err(|e| format!("Failed to parse reports: {}", e))?;
+    let settings = load_settings(app.clone())?;
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
 
-    // Remove the report with matching ID
-    reports.retain(|r| r.id != report_id);
+    rename_advertiser_in_dir(&app_dir, &old_name, &new_name, settings.max_backups)
+}
 
-    let reports_str = serde_json::to_string_pretty(&reports)
-        .map_err(|e| format!("Failed to serialize reports: {}", e))?;
+// Adds an organizational tag to a report. Tags are compared
+// case-insensitively, so "Q1" and "q1" are the same tag.
+fn add_report_tag_in_dir(app_dir: &Path, report_id: &str, tag: &str) -> Result<SavedReport, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    let mut reports = load_reports_from_dir(app_dir)?;
+    let report = reports.iter_mut()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("No report found with id: {}", report_id))?;
+
+    if !report.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        report.tags.push(tag.to_string());
+    }
+    let updated_report = report.clone();
+
+    write_reports_to_dir(app_dir, &reports)?;
+
+    Ok(updated_report)
+}
+
+fn remove_report_tag_in_dir(app_dir: &Path, report_id: &str, tag: &str) -> Result<SavedReport, String> {
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
 
-    fs::write(&reports_path, reports_str)
-        .map_err(|e| format!("Failed to write reports: {}", e))
+    let mut reports = load_reports_from_dir(app_dir)?;
+    let report = reports.iter_mut()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("No report found with id: {}", report_id))?;
+
+    report.tags.retain(|t| !t.eq_ignore_ascii_case(tag));
+    let updated_report = report.clone();
+
+    write_reports_to_dir(app_dir, &reports)?;
+
+    Ok(updated_report)
+}
+
+fn set_report_notes_in_dir(app_dir: &Path, report_id: &str, notes: &str) -> Result<SavedReport, String> {
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    let mut reports = load_reports_from_dir(app_dir)?;
+    let report = reports.iter_mut()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("No report found with id: {}", report_id))?;
+
+    // An empty string clears the annotation
+    report.notes = notes.trim().to_string();
+    let updated_report = report.clone();
+
+    write_reports_to_dir(app_dir, &reports)?;
+
+    Ok(updated_report)
 }
 
 #[tauri::command]
-fn opener_open(_app: tauri::AppHandle, path: String) -> Result<(), String> {
-    // Use a standard method to open the file
-    let path_obj = std::path::Path::new(&path);
-    opener::open(path_obj)
-        .map_err(|e| format!("Failed to open file: {}", e))
+fn add_report_tag(app: tauri::AppHandle, report_id: String, tag: String) -> Result<SavedReport, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    add_report_tag_in_dir(&app_dir, &report_id, &tag)
 }
 
 #[tauri::command]
-fn download_report(app: tauri::AppHandle, report: serde_json::Value) -> Result<String, String> {
-    // Create a timestamp for the file name
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    
-    // Load settings to get the custom download directory
-    let settings = load_settings(app.clone())?;
-    
-    // Debug log the download directory
-    println!("Using download directory from settings: '{}'", settings.download_directory);
-    
-    // Use the download directory from settings
-    let download_dir = std::path::Path::new(&settings.download_directory);
-    
-    // Debug log the download directory exists check
-    println!("Does download directory exist? {}", download_dir.exists());
-    
-    // Create the directory if it doesn't exist
-    if !download_dir.exists() {
-        println!("Download directory doesn't exist, creating it");
-        std::fs::create_dir_all(download_dir)
-            .map_err(|e| format!("Failed to create download directory: {}", e))?;
-    }
-    
-    // Create a file name with the report name if available
-    let report_name = report.get("name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("report");
-    
-    let file_name = format!("{}_{}.json", report_name, timestamp);
-    let file_path = download_dir.join(file_name);
-    
-    // Debug log the file path
-    println!("Writing JSON report to: '{}'", file_path.display());
-    
-    // Serialize report to JSON
-    let report_json = serde_json::to_string_pretty(&report)
-        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+fn remove_report_tag(app: tauri::AppHandle, report_id: String, tag: String) -> Result<SavedReport, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    remove_report_tag_in_dir(&app_dir, &report_id, &tag)
+}
 
-    // Write to file
-    match std::fs::write(&file_path, report_json.as_bytes()) {
-        Ok(_) => println!("Successfully wrote JSON file to {}", file_path.display()),
-        Err(e) => {
-            let error_msg = format!("Failed to write file: {}", e);
-            println!("{}", error_msg);
-            return Err(error_msg);
+#[tauri::command]
+fn set_report_notes(app: tauri::AppHandle, report_id: String, notes: String) -> Result<SavedReport, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    set_report_notes_in_dir(&app_dir, &report_id, &notes)
+}
+
+#[tauri::command]
+fn list_reports_by_tag(app: tauri::AppHandle, tag: String) -> Result<Vec<SavedReport>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    Ok(reports.into_iter()
+        .filter(|r| r.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)))
+        .collect())
+}
+
+// Normalizes a link for display and comparison: trims whitespace, drops any
+// fragment, and strips a trailing slash so equivalent links compare equal.
+fn normalize_link(url: &str) -> String {
+    let trimmed = url.trim();
+    match Url::parse(trimmed) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            parsed.to_string().trim_end_matches('/').to_string()
         }
-    };
+        Err(_) => trimmed.trim_end_matches('/').to_string(),
+    }
+}
 
-    // Return the file path for displaying to the user
-    let path_str = file_path.to_string_lossy().to_string();
-    println!("Returning file path: '{}'", path_str);
-    Ok(path_str)
+// Pulls absolute http(s) links out of a campaign's HTML body
+fn extract_hrefs(html: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut rest = html;
+    while let Some(pos) = rest.find("href=\"") {
+        rest = &rest[pos + 6..];
+        match rest.find('"') {
+            Some(end) => {
+                let link = &rest[..end];
+                if link.starts_with("http://") || link.starts_with("https://") {
+                    links.push(link.to_string());
+                }
+                rest = &rest[end..];
+            }
+            None => break,
+        }
+    }
+    links
 }
 
 #[tauri::command]
-fn download_csv(app: tauri::AppHandle, reportData: serde_json::Value) -> Result<String, String> {
-    // Extract report data for CSV content
-    let report_data = reportData.get("data")
-        .ok_or_else(|| "Invalid report format: missing data field".to_string())?;
-    
-    // Get selected metrics from the report data
-    let metrics = report_data.get("metrics")
-        .ok_or_else(|| "Invalid report format: missing metrics".to_string())?;
-    
-    // Load settings to get the custom download directory
+async fn get_campaign_links(app: tauri::AppHandle, campaign_id: String) -> Result<Vec<String>, String> {
     let settings = load_settings(app.clone())?;
-    
-    // Use the download directory from settings
-    let download_dir = std::path::Path::new(&settings.download_directory);
-    
-    // Create the directory if it doesn't exist
-    if !download_dir.exists() {
-        std::fs::create_dir_all(download_dir)
-            .map_err(|e| format!("Failed to create download directory: {}", e))?;
+
+    if settings.mailchimp_api_key.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
     }
-    
-    // Extract report metadata for filename
-    let advertiser = reportData.get("advertiser")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown-advertiser");
-    
-    let newsletter_type = reportData.get("report_type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown-type");
-    
-    // Extract date range for filename
-    let date_range = if let Some(range) = reportData.get("date_range") {
-        let start = range.get("start_date")
-            .and_then(|d| d.as_str())
-            .unwrap_or("");
-        
-        let end = range.get("end_date")
-            .and_then(|d| d.as_str())
-            .unwrap_or("");
-            
-        if !start.is_empty() && !end.is_empty() {
-            format!("{}_{}", start, end)
-        } else {
-            "unknown-dates".to_string()
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let mut links = Vec::new();
+
+    // First try the campaign content, which shows every link present in the
+    // email regardless of whether anyone clicked it
+    let content_url = format!("{}/campaigns/{}/content", base_url, campaign_id);
+    let content_response = client
+        .get(&content_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaign content: {}", e))?;
+
+    if content_response.status().is_success() {
+        if let Ok(content) = content_response.json::<serde_json::Value>().await {
+            if let Some(html) = content.get("html").and_then(|h| h.as_str()) {
+                links.extend(extract_hrefs(html));
+            }
         }
-    } else {
-        "unknown-dates".to_string()
-    };
-    
-    // Create a timestamp for uniqueness if needed
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    
-    // Create a clean advertiser name (remove special chars)
-    let clean_advertiser = advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
-    
-    // Format the filename: Advertiser_NewsletterType_DateRange.csv
-    let file_name = format!("{}_{}_{}_{}.csv", 
-        clean_advertiser,
-        newsletter_type,
-        date_range,
-        timestamp
-    );
-    
-    let file_path = download_dir.join(&file_name);
-    
-    // Create CSV header based on selected metrics
-    let mut header_fields = vec!["Date"];
-    if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Unique Opens");
     }
-    if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Total Opens");
+
+    // Fall back to the click-details URL list if the content had no links
+    if links.is_empty() {
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        let click_response = client
+            .get(&click_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch click details: {}", e))?;
+
+        if click_response.status().is_success() {
+            if let Ok(click_data) = click_response.json::<serde_json::Value>().await {
+                if let Some(urls_clicked) = click_data.get("urls_clicked").and_then(|u| u.as_array()) {
+                    for url_item in urls_clicked {
+                        if let Some(url) = url_item.get("url").and_then(|u| u.as_str()) {
+                            links.push(url.to_string());
+                        }
+                    }
+                }
+            }
+        }
     }
-    if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Total Recipients");
+
+    // Normalize and de-duplicate so the user sees each link once
+    let mut cleaned: Vec<String> = links.iter().map(|l| normalize_link(l)).collect();
+    cleaned.sort();
+    cleaned.dedup();
+
+    println!("Found {} distinct links in campaign {}", cleaned.len(), campaign_id);
+    Ok(cleaned)
+}
+
+// Renders a URL as an SVG QR code for embedding in exports
+fn qr_svg(url: &str) -> Result<String, String> {
+    let code = qrcode::QrCode::new(url.as_bytes())
+        .map_err(|e| format!("Failed to build QR code: {}", e))?;
+    Ok(code.render::<qrcode::render::svg::Color>()
+        .min_dimensions(160, 160)
+        .build())
+}
+
+// Collects one QR section per campaign that has a public archive URL.
+// Campaigns without one (e.g. never published) are skipped rather than
+// failing the whole export.
+fn campaign_qr_sections(data: &serde_json::Value) -> Vec<CampaignQr> {
+    let mut sections = Vec::new();
+
+    if let Some(campaigns) = data.get("campaigns").and_then(|c| c.as_array()) {
+        for campaign in campaigns {
+            let archive_url = match campaign.get("archive_url").and_then(|u| u.as_str()) {
+                Some(url) if !url.is_empty() => url,
+                _ => continue,
+            };
+            let title = campaign.get("settings")
+                .and_then(|s| s.get("title"))
+                .and_then(|t| t.as_str())
+                .unwrap_or("Untitled campaign");
+
+            if let Ok(svg) = qr_svg(archive_url) {
+                sections.push(CampaignQr {
+                    title: title.to_string(),
+                    archive_url: archive_url.to_string(),
+                    svg,
+                });
+            }
+        }
     }
-    if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("Total Clicks");
+
+    sections
+}
+
+fn iso_week_label(date: chrono::NaiveDate) -> String {
+    use chrono::Datelike;
+    let week = date.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+// Buckets per-campaign (send date, clicks, unique opens) points into ISO
+// weeks covering the whole window, emitting zero-value points for weeks
+// without a matching campaign so the series stays continuous
+fn bucket_weekly_ctr(points: &[(chrono::NaiveDate, u64, u64)], start: chrono::NaiveDate, end: chrono::NaiveDate) -> Vec<WeeklyCtr> {
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for (date, clicks, opens) in points {
+        let bucket = totals.entry(iso_week_label(*date)).or_insert((0, 0));
+        bucket.0 += clicks;
+        bucket.1 += opens;
     }
-    if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
-        header_fields.push("CTR");
+
+    let mut series = Vec::new();
+    let mut cursor = start;
+    while cursor <= end {
+        let week = iso_week_label(cursor);
+        let (total_clicks, unique_opens) = totals.get(&week).copied().unwrap_or((0, 0));
+        let ctr = if unique_opens > 0 {
+            (total_clicks as f64 / unique_opens as f64) * 100.0
+        } else {
+            0.0
+        };
+        series.push(WeeklyCtr { week, total_clicks, unique_opens, ctr });
+        cursor += chrono::Duration::days(7);
     }
-    
-    // Create CSV content with dynamic headers
-    let mut csv = String::new();
-    csv.push_str(&header_fields.join(","));
-    csv.push('\n');
-    
-    if let Some(report_entries) = report_data.get("report_data").and_then(|d| d.as_array()) {
-        for entry in report_entries {
-            let mut row_fields = vec![entry.get("send_date").and_then(|d| d.as_str()).unwrap_or("N/A").to_string()];
-            
-            if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
-            }
-            if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("total_opens").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
-            }
-            if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
-            }
-            if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
+
+    series
+}
+
+// Sums the audience's daily activity entries that fall inside the report
+// window into subscribe/unsubscribe totals and the net change
+fn sum_list_activity(activity: &serde_json::Value, start_day: &str, end_day: &str) -> serde_json::Value {
+    let mut subscribes: i64 = 0;
+    let mut unsubscribes: i64 = 0;
+
+    if let Some(days) = activity.get("activity").and_then(|a| a.as_array()) {
+        for day in days {
+            let date = day.get("day").and_then(|d| d.as_str()).unwrap_or("");
+            if date < start_day || date > end_day {
+                continue;
             }
-            if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
-                row_fields.push(format!("{:.6}", entry.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0)));
+            subscribes += day.get("subs").and_then(|v| v.as_i64()).unwrap_or(0);
+            unsubscribes += day.get("unsubs").and_then(|v| v.as_i64()).unwrap_or(0);
+        }
+    }
+
+    serde_json::json!({
+        "subscribes": subscribes,
+        "unsubscribes": unsubscribes,
+        "net": subscribes - unsubscribes
+    })
+}
+
+// One campaign's include/exclude verdict from the type filter, with the
+// reason spelled out so surprising matches can be debugged from the UI
+#[derive(Debug, Serialize)]
+struct MatchExplanation {
+    campaign_id: String,
+    title: String,
+    included: bool,
+    reason: String,
+}
+
+// The type-filter decision logic (same matching as the original Python
+// script), answering "why" for every fetched campaign instead of silently
+// dropping the non-matches. filter_campaigns_by_type keeps only the
+// included ones.
+fn explain_campaign_matches(
+    campaigns: &[serde_json::Value],
+    newsletter_type: &str,
+    folder_id: Option<&str>,
+) -> Vec<MatchExplanation> {
+    let newsletter_type_lower = newsletter_type.to_lowercase();
+    let mut explanations = Vec::new();
+
+    for campaign in campaigns {
+        let campaign_id = campaign.get("id")
+            .and_then(|i| i.as_str())
+            .unwrap_or("")
+            .to_string();
+        let title = campaign.get("settings")
+            .and_then(|s| s.get("title"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("")
+            .to_string();
+        let campaign_folder = campaign.get("settings")
+            .and_then(|s| s.get("folder_id"))
+            .and_then(|f| f.as_str())
+            .unwrap_or("");
+
+        let (included, reason) = if title.is_empty() {
+            (false, "Campaign has no title".to_string())
+        } else if folder_id.is_some_and(|f| !campaign_folder.is_empty() && campaign_folder != f) {
+            // The campaigns query is already folder-scoped, so this only
+            // fires when explaining a list fetched without the scope
+            (false, format!("In folder '{}', outside the requested folder '{}'", campaign_folder, folder_id.unwrap_or("")))
+        } else {
+            let title_lower = title.to_lowercase();
+            if title_lower.contains(&newsletter_type_lower) {
+                (true, format!("Title contains '{}'", newsletter_type_lower))
+            } else if newsletter_type_lower == "hc" && title_lower.contains("health care") {
+                (true, "Title contains 'health care' (alias for 'hc')".to_string())
+            } else {
+                (false, format!("Title does not contain '{}'", newsletter_type_lower))
             }
-            
-            csv.push_str(&row_fields.join(","));
-            csv.push('\n');
+        };
+
+        explanations.push(MatchExplanation { campaign_id, title, included, reason });
+    }
+
+    explanations
+}
+
+// The tag names attached to a campaign in Mailchimp. Handles both the
+// object form ({"name": ...}) the API returns and plain strings from
+// stored data.
+fn campaign_tag_names(campaign: &serde_json::Value) -> Vec<String> {
+    campaign.get("tags")
+        .and_then(|t| t.as_array())
+        .map(|arr| arr.iter()
+            .filter_map(|tag| tag.get("name").and_then(|n| n.as_str()).or_else(|| tag.as_str()))
+            .map(|s| s.to_string())
+            .collect())
+        .unwrap_or_default()
+}
+
+// Keeps the campaigns carrying the given Mailchimp tag, compared
+// case-insensitively since tagging discipline varies
+fn filter_campaigns_by_tag(campaigns: &[serde_json::Value], tag: &str) -> Vec<serde_json::Value> {
+    campaigns.iter()
+        .filter(|c| campaign_tag_names(c).iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .cloned()
+        .collect()
+}
+
+// The selection step of the report pipeline: title substring matching by
+// default, or tag matching when the request asks for it
+// Scopes campaigns to one audience by recipients.list_id. Campaigns that
+// don't report a list id pass through, mirroring the lenient treatment in
+// check_audience_mismatches; a missing or blank scope keeps everything.
+fn scope_campaigns_to_audience(campaigns: &[serde_json::Value], audience_id: Option<&str>) -> Vec<serde_json::Value> {
+    let target = match audience_id.map(str::trim) {
+        Some(id) if !id.is_empty() => id,
+        _ => return campaigns.to_vec(),
+    };
+    campaigns.iter()
+        .filter(|campaign| {
+            let list_id = campaign.get("recipients")
+                .and_then(|r| r.get("list_id"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            list_id.is_empty() || list_id == target
+        })
+        .cloned()
+        .collect()
+}
+
+fn select_campaigns(campaigns: &[serde_json::Value], request: &ReportRequest) -> Result<Vec<serde_json::Value>, String> {
+    // Same-titled newsletters on another list never make it to the title
+    // or tag filter when the request is scoped to one audience
+    let scoped = scope_campaigns_to_audience(campaigns, request.audience_scope.as_deref());
+    if request.filter_mode == "tag" {
+        let tag = request.tag.as_deref().unwrap_or("").trim().to_string();
+        if tag.is_empty() {
+            return Err("Tag filter mode requires a tag".to_string());
         }
+        Ok(filter_campaigns_by_tag(&scoped, &tag))
     } else {
-        csv.push_str("No campaign data found\n");
+        Ok(filter_campaigns_by_type(&scoped, &request.newsletter_type))
     }
-    
-    // Write the CSV content to the file
-    std::fs::write(&file_path, csv.as_bytes())
-        .map_err(|e| format!("Failed to write CSV: {}", e))?;
-    
-    // Return the file path as a string
-    Ok(file_path.to_string_lossy().to_string())
 }
 
-#[tauri::command]
-fn get_settings_path(app: tauri::AppHandle) -> Result<String, String> {
-    let app_dir = app.path().app_config_dir()
-        .map_err(|e| format!("Could not get app directory: {}", e))?;
-    let settings_path = app_dir.join("settings.json");
-    
-    Ok(settings_path.to_string_lossy().to_string())
+// Flags matched campaigns whose recipients.list_id isn't the configured
+// audience - usually a campaign that borrowed the naming convention but
+// went to the wrong list - and optionally drops them from the run
+fn check_audience_mismatches(
+    campaigns: Vec<serde_json::Value>,
+    audience_id: &str,
+    exclude: bool,
+) -> (Vec<serde_json::Value>, Vec<String>) {
+    let mut kept = Vec::new();
+    let mut warnings = Vec::new();
+
+    for campaign in campaigns {
+        let list_id = campaign.get("recipients")
+            .and_then(|r| r.get("list_id"))
+            .and_then(|l| l.as_str())
+            .unwrap_or("");
+
+        // Campaigns without recipients data can't be checked, so let them
+        // through rather than guessing
+        if !list_id.is_empty() && !audience_id.is_empty() && list_id != audience_id {
+            let title = campaign.get("settings")
+                .and_then(|s| s.get("title"))
+                .and_then(|t| t.as_str())
+                .unwrap_or("Untitled");
+            warnings.push(format!(
+                "Campaign '{}' was sent to audience {} instead of {}{}",
+                title, list_id, audience_id,
+                if exclude { " and was excluded" } else { "" }
+            ));
+            if exclude {
+                continue;
+            }
+        }
+
+        kept.push(campaign);
+    }
+
+    (kept, warnings)
 }
 
-#[tauri::command]
-fn emit_event(app: tauri::AppHandle, event: String, payload: Option<serde_json::Value>) -> Result<(), String> {
-    app.emit(&event, payload)
-        .map_err(|e| format!("Failed to emit event: {}", e))
+// Filters a fetched campaign list down to the ones whose title matches the
+// requested newsletter type
+fn filter_campaigns_by_type(campaigns: &[serde_json::Value], newsletter_type: &str) -> Vec<serde_json::Value> {
+    explain_campaign_matches(campaigns, newsletter_type, None)
+        .iter()
+        .zip(campaigns)
+        .filter(|(decision, _)| decision.included)
+        .map(|(_, campaign)| campaign.clone())
+        .collect()
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .setup(|app| {
-            #[cfg(debug_assertions)]
-            if let Some(window) = app.get_webview_window("main") {
-                window.open_devtools();
+// Returns the closest few candidate URLs by Levenshtein distance, used to
+// turn a dead tracking URL into a "did you mean?" hint
+fn suggest_similar_urls(missing: &str, candidates: &[String], max: usize) -> Vec<String> {
+    let mut scored: Vec<(usize, &String)> = candidates.iter()
+        .map(|c| (strsim::levenshtein(missing, c), c))
+        .collect();
+    scored.sort_by_key(|(distance, _)| *distance);
+    scored.into_iter().take(max).map(|(_, c)| c.clone()).collect()
+}
+
+// Decides whether a clicked URL counts for a tracking URL under the chosen
+// matching mode. "prefix" is the historical substring match; "exact" compares
+// normalized links; "segment" requires the tracking path to align on
+// /-delimited segment boundaries so /offer can't match /offers-expired.
+fn url_matches_tracking(clicked: &str, tracking: &str, path_match: &str) -> bool {
+    if tracking.is_empty() {
+        return false;
+    }
+
+    match path_match {
+        "exact" => normalize_link(clicked) == normalize_link(tracking),
+        "segment" => {
+            // If both sides parse as full URLs, the hosts must agree
+            if let (Ok(t), Ok(c)) = (Url::parse(tracking), Url::parse(clicked)) {
+                if let (Some(th), Some(ch)) = (t.host_str(), c.host_str()) {
+                    if th != ch {
+                        return false;
+                    }
+                }
+            }
+
+            let clicked_path = Url::parse(clicked)
+                .map(|u| u.path().to_string())
+                .unwrap_or_else(|_| clicked.to_string());
+            let tracking_path = Url::parse(tracking)
+                .map(|u| u.path().to_string())
+                .unwrap_or_else(|_| tracking.to_string());
+
+            let clicked_segments: Vec<&str> = clicked_path.split('/').filter(|s| !s.is_empty()).collect();
+            let tracking_segments: Vec<&str> = tracking_path.split('/').filter(|s| !s.is_empty()).collect();
+
+            if tracking_segments.is_empty() {
+                return false;
+            }
+
+            clicked_segments.len() >= tracking_segments.len()
+                && clicked_segments[..tracking_segments.len()] == tracking_segments[..]
+        }
+        // "prefix" and anything unrecognized keep the original substring behavior
+        _ => clicked.contains(tracking),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MatchResult {
+    matches: bool,
+    mode: String,
+    reason: String,
+}
+
+// Runs the same normalization and matching as report generation on one
+// clicked/tracking pair and says why it matched or didn't. Debugging aid
+// for "why didn't this click count?" questions.
+fn explain_url_match(clicked: &str, tracking: &str, mode: &str) -> Result<MatchResult, String> {
+    if !matches!(mode, "prefix" | "exact" | "segment") {
+        return Err(format!("Unknown match mode: {} (expected prefix, exact, or segment)", mode));
+    }
+    if tracking.trim().is_empty() {
+        return Err("Tracking URL is empty".to_string());
+    }
+
+    let matches = url_matches_tracking(clicked, tracking, mode);
+    let reason = match mode {
+        "exact" => {
+            let clicked_norm = normalize_link(clicked);
+            let tracking_norm = normalize_link(tracking);
+            if matches {
+                format!("Normalized links are identical: '{}'", clicked_norm)
+            } else {
+                format!("Normalized links differ: '{}' vs '{}'", clicked_norm, tracking_norm)
+            }
+        }
+        "segment" => {
+            if matches {
+                "Clicked path starts with the tracking path on /-delimited segment boundaries".to_string()
+            } else {
+                "Clicked path does not start with the tracking path on segment boundaries (or the hosts differ)".to_string()
+            }
+        }
+        _ => {
+            if matches {
+                format!("Clicked URL contains '{}'", tracking)
+            } else {
+                format!("Clicked URL does not contain '{}'", tracking)
+            }
+        }
+    };
+
+    Ok(MatchResult {
+        matches,
+        mode: mode.to_string(),
+        reason,
+    })
+}
+
+// Interactive probe for the matching rules, so a single URL pair can be
+// tested without running a whole report
+#[tauri::command]
+fn test_url_match(clicked: String, tracking: String, mode: String) -> Result<MatchResult, String> {
+    explain_url_match(&clicked, &tracking, &mode)
+}
+
+// Sums the clicks from a click-details response for URLs matching any of
+// the given tracking URLs under the chosen path_match mode
+fn count_matched_clicks(click_data: &serde_json::Value, tracking_urls: &[String], path_match: &str) -> u64 {
+    let mut ad_clicks: u64 = 0;
+
+    if let Some(urls_clicked) = click_data.get("urls_clicked").and_then(|u| u.as_array()) {
+        for url_item in urls_clicked {
+            if let Some(url) = url_item.get("url").and_then(|u| u.as_str()) {
+                for tracking_url in tracking_urls {
+                    if url_matches_tracking(url, tracking_url, path_match) {
+                        ad_clicks += url_item.get("total_clicks").and_then(|c| c.as_u64()).unwrap_or(0);
+                    }
+                }
+            }
+        }
+    }
+
+    ad_clicks
+}
+
+// Sums every click in a campaign's click-details response, matched or not.
+// This is the denominator for an advertiser's share-of-clicks.
+fn count_total_clicks(click_data: &serde_json::Value) -> u64 {
+    click_data.get("urls_clicked")
+        .and_then(|u| u.as_array())
+        .map(|urls| urls.iter()
+            .map(|url_item| url_item.get("total_clicks").and_then(|c| c.as_u64()).unwrap_or(0))
+            .sum())
+        .unwrap_or(0)
+}
+
+// The advertiser's fraction of all clicks in a campaign, as a percentage
+fn share_of_clicks(matched: u64, total: u64) -> f64 {
+    if total > 0 {
+        (matched as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+// Add these validation functions before the generate_report function
+// Validates the tracking URLs and returns them with duplicates removed.
+// Duplicates (after normalization) would double count clicks because the
+// matching loop checks each entry independently, so only the first
+// occurrence survives.
+fn validate_tracking_urls(urls: &[String]) -> Result<Vec<String>, String> {
+    if urls.is_empty() {
+        return Err("No tracking URLs provided".to_string());
+    }
+
+    // A vector of only empty/whitespace entries would sail through the loop
+    // below and produce a confusing "No data found" later, so reject it
+    // before any API calls happen
+    if urls.iter().all(|u| u.trim().is_empty()) {
+        return Err("Please provide at least one non-empty tracking URL".to_string());
+    }
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut deduped = Vec::new();
+
+    for url in urls {
+        if url.trim().is_empty() {
+            continue; // Skip empty URLs as they're handled separately
+        }
+
+        // Check if it's a valid URL or URL path component
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            // If not a full URL, check if it's at least a valid URL path component
+            if url.contains(char::is_whitespace) || url.contains('<') || url.contains('>') {
+                return Err(format!("Invalid tracking URL or path component: {}", url));
+            }
+        } else {
+            // If it's meant to be a full URL, validate it properly
+            if let Err(_) = Url::parse(url) {
+                return Err(format!("Invalid tracking URL format: {}", url));
+            }
+        }
+
+        if seen.insert(normalize_link(url)) {
+            deduped.push(url.clone());
+        } else {
+            println!("Dropping duplicate tracking URL: {}", url);
+        }
+    }
+
+    Ok(deduped)
+}
+
+fn validate_campaign_data(campaigns: &[serde_json::Value], newsletter_type: &str) -> Result<(), String> {
+    if campaigns.is_empty() {
+        return Err("No campaigns found for the specified date range".to_string());
+    }
+
+    let newsletter_type_lower = newsletter_type.to_lowercase();
+    let mut matching_campaigns = 0;
+
+    for campaign in campaigns {
+        if let Some(settings) = campaign.get("settings") {
+            if let Some(title) = settings.get("title").and_then(|t| t.as_str()) {
+                let title_lower = title.to_lowercase();
+                
+                let matches = if newsletter_type_lower == "hc" {
+                    title_lower.contains("hc") || title_lower.contains("health care")
+                } else {
+                    title_lower.contains(&newsletter_type_lower)
+                };
+                
+                if matches {
+                    matching_campaigns += 1;
+                }
+            }
+        }
+    }
+
+    if matching_campaigns == 0 {
+        return Err(format!(
+            "No campaigns found matching the newsletter type '{}'. Please check if the newsletter type is correct.",
+            newsletter_type
+        ));
+    }
+
+    Ok(())
+}
+
+// How many diagnostics entries are kept before old ones are dropped
+const DIAGNOSTICS_CAP: usize = 50;
+
+// Masks anything shaped like a Mailchimp API key (long token with a "-usN"
+// datacenter suffix) so diagnostics never store credentials
+fn redact_secrets(text: &str) -> String {
+    text.split(' ')
+        .map(|token| {
+            let trimmed = token.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-');
+            if trimmed.len() >= 30 && trimmed.contains("-us") {
+                token.replace(trimmed, "[redacted]")
+            } else {
+                token.to_string()
             }
-            Ok(())
         })
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            load_settings,
-            save_settings,
-            generate_report,
-            load_reports,
-            save_report,
-            open_report_in_excel,
-            write_report_file,
-            delete_report,
-            opener_open,
-            download_report,
-            download_csv,
-            get_settings_path,
-            emit_event
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Appends one structured entry to diagnostics.json, keeping only the most
+// recent DIAGNOSTICS_CAP entries. Purely local; nothing leaves the machine.
+// Best effort on purpose - a diagnostics failure must never mask the
+// original error.
+fn record_diagnostic(app_dir: &Path, command: &str, summary: &str, error: &str) {
+    let path = app_dir.join("diagnostics.json");
+
+    let mut entries: Vec<serde_json::Value> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+
+    entries.push(serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "command": command,
+        "request": redact_secrets(summary),
+        "error": redact_secrets(error),
+    }));
+
+    if entries.len() > DIAGNOSTICS_CAP {
+        let excess = entries.len() - DIAGNOSTICS_CAP;
+        entries.drain(..excess);
+    }
+
+    if let Ok(json) = serde_json::to_string_pretty(&entries) {
+        let _ = fs::write(&path, json);
+    }
+}
+
+#[tauri::command]
+fn get_diagnostics(app: tauri::AppHandle) -> Result<Vec<serde_json::Value>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let path = app_dir.join("diagnostics.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read diagnostics: {}", e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse diagnostics: {}", e))
+}
+
+// Last successful fetch times keyed "audience|advertiser", backing the
+// incremental since_send_time mode
+fn load_fetch_times(app_dir: &Path) -> HashMap<String, String> {
+    let path = app_dir.join("fetch_times.json");
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn record_fetch_time(app_dir: &Path, key: &str) {
+    let mut times = load_fetch_times(app_dir);
+    times.insert(
+        key.to_string(),
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    );
+    match serde_json::to_string_pretty(&times) {
+        Ok(json) => {
+            if let Err(e) = fs::write(app_dir.join("fetch_times.json"), json) {
+                println!("Failed to write fetch times: {}", e);
+            }
+        }
+        Err(e) => println!("Failed to serialize fetch times: {}", e),
+    }
+}
+
+// Backs a fetch timestamp up by the settling window, so campaigns sent just
+// before the last run are re-fetched in case their numbers were still moving
+fn settle_since(since: &str, settling_days: u32) -> String {
+    match chrono::NaiveDateTime::parse_from_str(since, "%Y-%m-%dT%H:%M:%SZ") {
+        Ok(dt) => (dt - chrono::Duration::days(settling_days as i64))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string(),
+        Err(_) => since.to_string(),
+    }
+}
+
+// Merges an incremental fetch into previously stored rows. A fresh row with
+// the same campaign_id replaces the stored one (it was re-pulled inside the
+// settling window); anything else is appended. Output stays date-sorted.
+fn merge_report_data(existing: &[serde_json::Value], fresh: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let fresh_ids: HashSet<&str> = fresh.iter()
+        .filter_map(|r| r.get("campaign_id").and_then(|v| v.as_str()))
+        .collect();
+
+    let mut merged: Vec<serde_json::Value> = existing.iter()
+        .filter(|r| {
+            r.get("campaign_id")
+                .and_then(|v| v.as_str())
+                .map(|id| !fresh_ids.contains(id))
+                .unwrap_or(true)
+        })
+        .cloned()
+        .collect();
+    merged.extend(fresh.iter().cloned());
+
+    merged.sort_by(|a, b| {
+        let date_a = a.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+        let date_b = b.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+        date_a.cmp(date_b)
+    });
+    merged
+}
+
+// Folds freshly fetched rows into the advertiser's most recent saved report
+// of the same type. Returns None when there is no prior report to merge
+// into, in which case the caller saves a brand-new one.
+fn merge_into_latest_report(
+    app_dir: &Path,
+    advertiser: &str,
+    newsletter_type: &str,
+    fresh: &[serde_json::Value],
+    end_date: &str,
+) -> Result<Option<SavedReport>, String> {
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    let mut reports = load_reports_from_dir(app_dir)?;
+
+    let report = match reports.iter_mut()
+        .filter(|r| r.advertiser == advertiser && r.report_type == newsletter_type)
+        .last()
+    {
+        Some(report) => report,
+        None => return Ok(None),
+    };
+
+    let existing_rows = report.data.get("report_data")
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let merged = merge_report_data(&existing_rows, fresh);
+    println!(
+        "Incremental merge for {}: {} stored + {} fetched -> {} rows",
+        advertiser, existing_rows.len(), fresh.len(), merged.len()
+    );
+
+    report.data["report_data"] = serde_json::json!(merged);
+    if end_date > report.date_range.end_date.as_str() {
+        report.date_range.end_date = end_date.to_string();
+    }
+
+    let updated = report.clone();
+    write_reports_to_dir(app_dir, &reports)?;
+    Ok(Some(updated))
+}
+
+// AIMD controller for the click-details fetch width: halve on a 429
+// (multiplicative decrease), add one step back toward the cap after three
+// clean batches in a row (additive increase)
+struct AimdConcurrency {
+    current: usize,
+    max: usize,
+    min: usize,
+    success_streak: usize,
+}
+
+impl AimdConcurrency {
+    fn new(max: usize, min: usize) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+        AimdConcurrency { current: max, max, min, success_streak: 0 }
+    }
+
+    fn current(&self) -> usize {
+        self.current
+    }
+
+    fn on_rate_limit(&mut self) {
+        self.success_streak = 0;
+        self.current = (self.current / 2).max(self.min);
+    }
+
+    fn on_success_batch(&mut self) {
+        self.success_streak += 1;
+        if self.success_streak >= 3 && self.current < self.max {
+            self.current += 1;
+            self.success_streak = 0;
+        }
+    }
+}
+
+// Fetches click details for many campaigns at once, adapting the batch
+// width with the AIMD controller. A 429 anywhere in a batch halves the
+// width and re-queues the limited campaigns (up to three attempts each);
+// other failures are dropped the way the sequential path dropped them.
+async fn fetch_click_details_adaptive(
+    client: &reqwest::Client,
+    base_url: &str,
+    auth: &str,
+    campaign_ids: Vec<String>,
+    limiter: &mut AimdConcurrency,
+) -> HashMap<String, serde_json::Value> {
+    let mut results = HashMap::new();
+    let mut queue: std::collections::VecDeque<String> = campaign_ids.into();
+    let mut attempts: HashMap<String, u32> = HashMap::new();
+
+    while !queue.is_empty() {
+        let width = limiter.current();
+        let mut handles = Vec::new();
+
+        for _ in 0..width {
+            let campaign_id = match queue.pop_front() {
+                Some(id) => id,
+                None => break,
+            };
+            let client = client.clone();
+            let auth = auth.to_string();
+            let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+
+            handles.push(tokio::spawn(async move {
+                match client.get(&click_url).header("Authorization", auth).send().await {
+                    Ok(response) if response.status().as_u16() == 429 => (campaign_id, None, true),
+                    Ok(response) if response.status().is_success() => {
+                        match response.json::<serde_json::Value>().await {
+                            Ok(data) => (campaign_id, Some(data), false),
+                            Err(_) => (campaign_id, None, false),
+                        }
+                    }
+                    _ => (campaign_id, None, false),
+                }
+            }));
+        }
+
+        let mut rate_limited = false;
+        for handle in handles {
+            if let Ok((campaign_id, data, limited)) = handle.await {
+                if limited {
+                    rate_limited = true;
+                    let tries = attempts.entry(campaign_id.clone()).or_insert(0);
+                    *tries += 1;
+                    if *tries < 3 {
+                        queue.push_back(campaign_id);
+                    } else {
+                        println!("Giving up on click details for {} after repeated 429s", campaign_id);
+                    }
+                } else if let Some(data) = data {
+                    results.insert(campaign_id, data);
+                }
+            }
+        }
+
+        if rate_limited {
+            limiter.on_rate_limit();
+            println!("Rate limited; click-details width now {}", limiter.current());
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        } else {
+            limiter.on_success_batch();
+        }
+    }
+
+    results
+}
+
+// Transient-failure check for the auto-retry path. Errors reach us as
+// strings, so this classifies by message shape: only network and
+// rate-limit failures qualify, since validation and auth failures would
+// fail identically on a second attempt.
+fn is_transient_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+
+    if lower.contains("not configured")
+        || lower.contains("unauthorized")
+        || lower.contains("401")
+        || lower.contains("403")
+        || lower.contains("api key")
+    {
+        return false;
+    }
+
+    lower.contains("failed to fetch")
+        || lower.contains("timed out")
+        || lower.contains("timeout")
+        || lower.contains("connection")
+        || lower.contains("429")
+        || lower.contains("too many requests")
+        || lower.contains("rate limit")
+}
+
+#[tauri::command]
+async fn generate_report(app: tauri::AppHandle, request: ReportRequest) -> Result<ReportResponse, String> {
+    // Captured up front so a failed run can be diagnosed after the fact
+    let request_summary = format!(
+        "advertiser={} type={} range={}..{}",
+        request.advertiser, request.newsletter_type,
+        request.date_range.start_date, request.date_range.end_date
+    );
+
+    let sink = ProgressSink::Window(app.clone());
+    let mut result = run_report(app.clone(), request.clone(), sink.clone()).await;
+
+    // A fully failed run may be worth one more attempt: transient network
+    // or rate-limit failures usually clear after a short wait, and users
+    // were re-clicking the button by hand anyway
+    let failure_message = match &result {
+        Err(error) => Some(error.clone()),
+        Ok(response) if !response.success => Some(response.message.clone()),
+        _ => None,
+    };
+    if let Some(message) = failure_message {
+        let auto_retry = load_settings(app.clone())
+            .map(|s| s.auto_retry_report)
+            .unwrap_or(false);
+        if auto_retry && is_transient_error(&message) {
+            let retry_update = ProgressUpdate {
+                stage: "Retrying".to_string(),
+                progress: 0,
+                message: "Transient failure, retrying report once...".to_string(),
+                time_remaining: None,
+                elapsed_secs: None,
+            };
+            sink.send(retry_update);
+
+            println!("Retrying report after transient failure: {}", message);
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            result = run_report(app.clone(), request, sink.clone()).await;
+        }
+    }
+
+    if let Err(error) = &result {
+        if let Ok(app_dir) = app.path().app_config_dir() {
+            record_diagnostic(&app_dir, "generate_report", &request_summary, error);
+        }
+    }
+
+    result
+}
+
+// Campaign list cache. prefetch_campaigns writes the list for a window here
+// so a later generate_report over the same window skips the slow fetch.
+// Entries go stale after a few hours since campaign stats keep settling.
+const CAMPAIGN_CACHE_TTL_SECS: i64 = 6 * 60 * 60;
+
+fn campaign_cache_path(app_dir: &Path, audience_id: &str, start_iso: &str, end_iso: &str, folder_id: Option<&str>) -> std::path::PathBuf {
+    let raw = format!("{}_{}_{}_{}", audience_id, start_iso, end_iso, folder_id.unwrap_or("all"));
+    let name: String = raw.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    app_dir.join("campaign_cache").join(format!("{}.json", name))
+}
+
+fn write_campaign_cache(path: &Path, campaigns: &[serde_json::Value]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create campaign cache directory: {}", e))?;
+    }
+    let entry = serde_json::json!({
+        "cached_at": chrono::Utc::now().to_rfc3339(),
+        "campaigns": campaigns
+    });
+    fs::write(path, serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize campaign cache: {}", e))?)
+        .map_err(|e| format!("Failed to write campaign cache: {}", e))
+}
+
+// None when the entry is missing, unreadable, or older than the TTL; callers
+// fall back to a live fetch in all three cases
+fn read_campaign_cache(path: &Path, now: chrono::DateTime<chrono::Utc>) -> Option<Vec<serde_json::Value>> {
+    let contents = fs::read_to_string(path).ok()?;
+    let entry: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    let cached_at = chrono::DateTime::parse_from_rfc3339(entry.get("cached_at")?.as_str()?)
+        .ok()?
+        .with_timezone(&chrono::Utc);
+    if now.signed_duration_since(cached_at).num_seconds() > CAMPAIGN_CACHE_TTL_SECS {
+        return None;
+    }
+    entry.get("campaigns")?.as_array().cloned()
+}
+
+// The report pipeline proper. Progress goes through the sink rather than
+// straight to the window so headless callers can run reports too.
+// Splits an ISO window into contiguous sub-windows of at most span_days.
+// Windows at or under the span come back unchanged; unparseable bounds fall
+// back to a single window so the fetch still happens.
+fn split_fetch_window(start_iso: &str, end_iso: &str, span_days: i64) -> Vec<(String, String)> {
+    let start = match chrono::DateTime::parse_from_rfc3339(start_iso) {
+        Ok(t) => t.with_timezone(&chrono::Utc),
+        Err(_) => return vec![(start_iso.to_string(), end_iso.to_string())],
+    };
+    let end = match chrono::DateTime::parse_from_rfc3339(end_iso) {
+        Ok(t) => t.with_timezone(&chrono::Utc),
+        Err(_) => return vec![(start_iso.to_string(), end_iso.to_string())],
+    };
+    if span_days < 1 || (end - start).num_days() <= span_days {
+        return vec![(start_iso.to_string(), end_iso.to_string())];
+    }
+
+    // Adjacent windows share their boundary instant; since_send_time is
+    // inclusive, so nothing falls through and the merge drops the duplicate
+    let mut windows = Vec::new();
+    let mut cursor = start;
+    while (end - cursor).num_days() > span_days {
+        let next = cursor + chrono::Duration::days(span_days);
+        windows.push((
+            if cursor == start {
+                start_iso.to_string()
+            } else {
+                cursor.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            },
+            next.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        ));
+        cursor = next;
+    }
+    windows.push((cursor.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), end_iso.to_string()));
+    windows
+}
+
+// Concatenates per-window campaign pages, dropping boundary duplicates by
+// campaign id while keeping the first occurrence's position
+fn merge_campaign_pages(pages: Vec<Vec<serde_json::Value>>) -> Vec<serde_json::Value> {
+    let mut seen = std::collections::HashSet::new();
+    let mut merged = Vec::new();
+    for page in pages {
+        for campaign in page {
+            match campaign.get("id").and_then(|v| v.as_str()) {
+                Some(id) => {
+                    if seen.insert(id.to_string()) {
+                        merged.push(campaign);
+                    }
+                }
+                None => merged.push(campaign),
+            }
+        }
+    }
+    merged
+}
+
+// Fetches one campaigns sub-window, going through its cache slot the same
+// way the single-window path does
+async fn fetch_campaign_window(
+    client: &reqwest::Client,
+    app: &tauri::AppHandle,
+    settings: &Settings,
+    start_iso: &str,
+    end_iso: &str,
+    folder_id: Option<&str>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let cache_path = app.path().app_config_dir().ok().map(|dir| {
+        campaign_cache_path(&dir, &settings.mailchimp_audience_id, start_iso, end_iso, folder_id)
+    });
+    if let Some(cached) = cache_path.as_deref().and_then(|path| read_campaign_cache(path, chrono::Utc::now())) {
+        println!("Campaign cache hit: {} campaigns for {}..{}", cached.len(), start_iso, end_iso);
+        return Ok(cached);
+    }
+
+    let campaigns_url = build_campaigns_url(&mailchimp_base_url(settings), start_iso, end_iso, folder_id);
+    let campaigns_response = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?;
+
+    if !campaigns_response.status().is_success() {
+        let error_text = campaigns_response.text().await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Mailchimp API error: {}", error_text));
+    }
+
+    let campaigns_data = campaigns_response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+    let fetched = campaigns_data.get("campaigns")
+        .and_then(|c| c.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    if let Some(path) = &cache_path {
+        if let Err(e) = write_campaign_cache(path, &fetched) {
+            println!("Failed to write campaign cache: {}", e);
+        }
+    }
+    Ok(fetched)
+}
+
+async fn run_report(app: tauri::AppHandle, mut request: ReportRequest, sink: ProgressSink) -> Result<ReportResponse, String> {
+    // Validate tracking URLs first and drop duplicates so a pasted-twice
+    // URL can't double count
+    request.tracking_urls = validate_tracking_urls(&request.tracking_urls)?;
+
+    // Init progress tracking with start time
+    let start_time = std::time::Instant::now();
+    let mut progress_updates = Vec::new();
+    
+    // First progress update
+    let initial_update = ProgressUpdate {
+        stage: "Initializing".to_string(),
+        progress: 0,
+        message: "Starting report generation...".to_string(),
+        time_remaining: None,
+        elapsed_secs: None,
+    };
+    
+    // Store in vector and emit to frontend
+    progress_updates.push(initial_update.clone());
+    
+    // Emit the progress update to the frontend
+    sink.send(initial_update);
+
+    // Load settings
+    let settings = load_settings(app.clone())?;
+    
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Ok(ReportResponse {
+            success: false,
+            message: "Mailchimp API settings not configured".to_string(),
+            data: None,
+            progress_updates,
+            url_suggestions: None,
+            warnings: Vec::new(),
+        });
+    }
+
+    // 10% progress
+    let connecting_update = ProgressUpdate {
+        stage: "FetchingCampaigns".to_string(),
+        progress: 10,
+        message: "Connecting to Mailchimp API...".to_string(),
+        time_remaining: None,
+        elapsed_secs: None,
+    };
+    
+    // Store and emit update
+    progress_updates.push(connecting_update.clone());
+    sink.send(connecting_update);
+
+    // Create Mailchimp API client
+    let client = reqwest::Client::new();
+    // dc still feeds the admin-URL links even when the API base is overridden
+    let dc = settings.mailchimp_api_key.split('-').next_back().unwrap_or("us1");
+    let base_url = mailchimp_base_url(&settings);
+
+    // Format dates for the API call - convert to ISO format. Plain dates cover
+    // the whole day; RFC3339 timestamps scope to a partial day.
+    let (mut start_date_iso, end_date_iso) = date_range_bounds(&request.date_range)?;
+
+    // Incremental mode: start from the last successful fetch, backed up by
+    // the settling window so recently-sent campaigns whose numbers may have
+    // moved are re-pulled and replaced during the merge
+    let fetch_key = format!("{}|{}", settings.mailchimp_audience_id, request.advertiser);
+    let mut last_fetch: Option<String> = None;
+    if request.incremental {
+        if let Ok(app_dir) = app.path().app_config_dir() {
+            last_fetch = load_fetch_times(&app_dir).get(&fetch_key).cloned();
+        }
+        if let Some(since) = &last_fetch {
+            let settled = settle_since(since, settings.settling_days);
+            if settled > start_date_iso {
+                println!("Incremental fetch: narrowing window to {}..{}", settled, end_date_iso);
+                start_date_iso = settled;
+            }
+        }
+    }
+
+    // Fetch campaigns for the date range (scoped to a folder when requested)
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, request.folder_id.as_deref());
+
+    // A warm cache from prefetch_campaigns lets us skip the list fetch
+    let cache_path = app.path().app_config_dir().ok().map(|dir| {
+        campaign_cache_path(&dir, &settings.mailchimp_audience_id, &start_date_iso, &end_date_iso, request.folder_id.as_deref())
+    });
+    let cached_campaigns = cache_path.as_deref().and_then(|path| read_campaign_cache(path, chrono::Utc::now()));
+    
+    // 20% progress
+    let fetching_update = ProgressUpdate {
+        stage: "FetchingCampaigns".to_string(),
+        progress: 20,
+        message: "Fetching campaign data from Mailchimp...".to_string(),
+        time_remaining: None,
+        elapsed_secs: None,
+    };
+    
+    // Store and emit update
+    progress_updates.push(fetching_update.clone());
+    sink.send(fetching_update);
+    
+    // Raw-response capture folder for this run, when requested
+    let debug_dir: Option<std::path::PathBuf> = if request.debug_capture {
+        app.path().app_config_dir().ok()
+            .map(|dir| debug_capture_dir(&dir, &chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string()))
+    } else {
+        None
+    };
+
+    // A confirmed preview pins the exact campaigns the user approved, so
+    // the fetch below is skipped entirely
+    let previewed_campaigns: Option<Vec<serde_json::Value>> = match &request.preview_token {
+        Some(token) => {
+            let mut tokens = PREVIEW_TOKENS.lock()
+                .map_err(|e| format!("Preview tokens lock poisoned: {}", e))?;
+            Some(redeem_preview_token(&mut tokens, token, chrono::Utc::now())?)
+        }
+        None => None,
+    };
+
+    // Oversized windows are split into sub-ranges fetched separately (each
+    // with its own cache slot) and merged, so one huge query can't hit the
+    // API's result caps
+    let sub_windows = split_fetch_window(&start_date_iso, &end_date_iso, settings.campaign_fetch_span_days.max(1) as i64);
+
+    let campaigns: Vec<serde_json::Value> = if let Some(previewed) = previewed_campaigns {
+        println!("Confirmed preview token: analyzing {} previewed campaigns", previewed.len());
+        previewed
+    } else if sub_windows.len() > 1 {
+        let mut pages = Vec::new();
+        for (index, (sub_start, sub_end)) in sub_windows.iter().enumerate() {
+            let window_update = ProgressUpdate {
+                stage: "FetchingCampaigns".to_string(),
+                progress: 20,
+                message: format!("Fetching campaigns: window {} of {}...", index + 1, sub_windows.len()),
+                time_remaining: None,
+                elapsed_secs: None,
+            };
+            sink.send(window_update);
+            pages.push(fetch_campaign_window(&client, &app, &settings, sub_start, sub_end, request.folder_id.as_deref()).await?);
+        }
+        merge_campaign_pages(pages)
+    } else if let Some(cached) = cached_campaigns {
+        println!("Campaign cache hit: {} campaigns for this window", cached.len());
+        cached
+    } else {
+        let campaigns_response = client
+            .get(&campaigns_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch campaigns: {}", e))?;
+
+        if !campaigns_response.status().is_success() {
+            let error_text = campaigns_response.text().await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Ok(ReportResponse {
+                success: false,
+                message: format!("Mailchimp API error: {}", error_text),
+                data: None,
+                progress_updates,
+                url_suggestions: None,
+                warnings: Vec::new(),
+            });
+        }
+
+        let campaigns_data = campaigns_response.json::<serde_json::Value>().await
+            .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+        // Get the actual campaigns array
+        let fetched = match campaigns_data.get("campaigns") {
+            Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap().clone(),
+            _ => {
+                return Ok(ReportResponse {
+                    success: false,
+                    message: "No campaigns found in response".to_string(),
+                    data: None,
+                    progress_updates,
+                    url_suggestions: None,
+                    warnings: Vec::new(),
+                });
+            }
+        };
+
+        // Keep the cache warm for a repeat run over the same window
+        if let Some(path) = &cache_path {
+            if let Err(e) = write_campaign_cache(path, &fetched) {
+                println!("Failed to write campaign cache: {}", e);
+            }
+        }
+        fetched
+    };
+    
+    if let Some(dir) = &debug_dir {
+        write_debug_capture(dir, "campaigns", &serde_json::json!(campaigns));
+    }
+
+    // After fetching campaigns, validate the campaign data
+    validate_campaign_data(&campaigns, &request.newsletter_type)?;
+
+    // 30% progress
+    let filtering_update = ProgressUpdate {
+        stage: "FilteringCampaigns".to_string(),
+        progress: 30,
+        message: format!("Found {} campaigns. Filtering by newsletter type...", campaigns.len()),
+        time_remaining: None,
+        elapsed_secs: None,
+    };
+    
+    // Store and emit update
+    progress_updates.push(filtering_update.clone());
+    sink.send(filtering_update);
+    
+    // Select this run's campaigns by title or, in tag mode, by Mailchimp
+    // tag. A token run keeps exactly what was previewed, unfiltered.
+    let filtered_campaigns = if request.preview_token.is_some() {
+        campaigns.clone()
+    } else {
+        select_campaigns(&campaigns, &request)?
+    };
+
+    // Flag (and optionally drop) campaigns that went to the wrong audience,
+    // which would otherwise quietly skew the advertiser's numbers
+    let (filtered_campaigns, mut audience_warnings) = check_audience_mismatches(
+        filtered_campaigns,
+        &settings.mailchimp_audience_id,
+        request.exclude_wrong_audience,
+    );
+    for warning in &audience_warnings {
+        println!("Audience warning: {}", warning);
+    }
+
+    // 40% progress
+    let initial_processing_update = ProgressUpdate {
+        stage: "ProcessingCampaigns".to_string(),
+        progress: 40,
+        message: format!("Processing {} campaigns...", filtered_campaigns.len()),
+        time_remaining: Some((filtered_campaigns.len() as f64 * 0.5) as u64), // Initial estimate: 0.5 seconds per campaign
+        elapsed_secs: None,
+    };
+    
+    progress_updates.push(initial_processing_update.clone());
+    sink.send(initial_processing_update);
+    
+    // Process each filtered campaign to analyze clicks for the specific ad URLs
+    let mut report_data = Vec::new();
+
+    // Track every clicked URL we see and which tracking URLs ever matched,
+    // so zero-coverage URLs can get "did you mean?" suggestions
+    let mut all_clicked_urls: HashSet<String> = HashSet::new();
+    let mut matched_tracking_urls: HashSet<String> = HashSet::new();
+    
+    // Calculate progress increment per campaign
+    let campaign_progress_increment = if filtered_campaigns.is_empty() {
+        0.0
+    } else {
+        40.0 / (filtered_campaigns.len() as f64)
+    };
+
+    // Fetch every campaign's click details up front at an adaptive width,
+    // so the per-campaign loop below only does local processing
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+    let prefetch_ids: Vec<String> = filtered_campaigns.iter()
+        .filter_map(|c| c.get("id").and_then(|id| id.as_str()))
+        .map(|id| id.to_string())
+        .collect();
+    let mut limiter = AimdConcurrency::new(settings.max_concurrency, settings.min_concurrency);
+    let click_details = fetch_click_details_adaptive(&client, &base_url, &auth, prefetch_ids, &mut limiter).await;
+    if let Some(dir) = &debug_dir {
+        for (campaign_id, click_data) in &click_details {
+            write_debug_capture(dir, &format!("click_details_{}", campaign_id), click_data);
+        }
+    }
+    
+    for (index, campaign) in filtered_campaigns.iter().enumerate() {
+        // Calculate current progress (40-80% is for campaign processing)
+        let current_progress = 40 + ((index as f64) * campaign_progress_increment) as u8;
+        
+        // Calculate time remaining based on actual processing rate
+        let elapsed = start_time.elapsed().as_secs_f64();
+        let time_remaining = if index > 0 {
+            // Calculate average time per campaign
+            let avg_time_per_campaign = elapsed / (index as f64);
+            // Calculate remaining campaigns
+            let remaining_campaigns = filtered_campaigns.len() - index;
+            // Estimate remaining time
+            let remaining_secs = avg_time_per_campaign * (remaining_campaigns as f64);
+            Some(remaining_secs.ceil() as u64)
+        } else {
+            // Initial estimate
+            Some((filtered_campaigns.len() as f64 * 0.5) as u64)
+        };
+        
+        // Add progress update for individual campaign
+        let campaign_update = ProgressUpdate {
+            stage: "ProcessingCampaigns".to_string(),
+            progress: current_progress,
+            message: format!("Processing campaign {} of {}: {}", 
+                index + 1, 
+                filtered_campaigns.len(),
+                campaign.get("settings")
+                    .and_then(|s| s.get("title"))
+                    .and_then(|t| t.as_str())
+                    .unwrap_or("Untitled")
+            ),
+            time_remaining,
+            elapsed_secs: None,
+        };
+        
+        // Store and emit update
+        progress_updates.push(campaign_update.clone());
+        sink.send(campaign_update);
+        
+        // Extract campaign ID and metrics
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue, // Skip if no ID
+        };
+        
+        // Get campaign send time
+        let send_time = match campaign.get("send_time").and_then(|st| st.as_str()) {
+            Some(time) => time,
+            None => continue, // Skip if no send time
+        };
+        
+        // Format date as in Python script
+        let formatted_date = match chrono::DateTime::parse_from_rfc3339(send_time) {
+            Ok(dt) => dt.format("%Y-%m-%d").to_string(),
+            Err(_) => continue, // Skip if date can't be parsed
+        };
+        
+        // Extract basic metrics
+        let report_summary = campaign.get("report_summary").unwrap_or(&serde_json::Value::Null);
+        let unique_opens = report_summary.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0);
+        let total_opens = report_summary.get("opens").and_then(|v| v.as_u64()).unwrap_or(0);
+        let total_recipients = campaign.get("emails_sent").and_then(|v| v.as_u64()).unwrap_or(0);
+        // Targeted segment size; a full-list send reports the same number
+        // here as emails_sent
+        let targeted_count = campaign.get("recipients")
+            .and_then(|r| r.get("recipient_count"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(total_recipients);
+        let (hard_bounces, soft_bounces) = bounce_split(campaign);
+        
+        // Click details were prefetched above
+        let mut ad_clicks: u64 = 0;
+        let mut campaign_total_clicks: u64 = 0;
+
+        if let Some(click_data) = click_details.get(campaign_id) {
+            ad_clicks = count_matched_clicks(click_data, &request.tracking_urls, &request.path_match);
+            campaign_total_clicks = count_total_clicks(click_data);
+
+            if let Some(urls_clicked) = click_data.get("urls_clicked").and_then(|u| u.as_array()) {
+                for url_item in urls_clicked {
+                    if let Some(url) = url_item.get("url").and_then(|u| u.as_str()) {
+                        all_clicked_urls.insert(url.to_string());
+                        for tracking_url in &request.tracking_urls {
+                            if url_matches_tracking(url, tracking_url, &request.path_match) {
+                                matched_tracking_urls.insert(tracking_url.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Calculate CTR
+        let ctr = if unique_opens > 0 {
+            (ad_clicks as f64 / unique_opens as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        // Normalized clicks per 1000 recipients (guard against zero recipients)
+        let clicks_per_thousand = if total_recipients > 0 {
+            (ad_clicks as f64 / total_recipients as f64) * 1000.0
+        } else {
+            0.0
+        };
+
+        // Tags the team attached to the campaign in Mailchimp, if any
+        let tags = campaign_tag_names(campaign);
+
+        // A campaign with click tracking turned off can't report clicks at
+        // all - keep it visible and flagged instead of letting it read as
+        // zero performance (or vanish from the report entirely)
+        let tracking_disabled = click_tracking_disabled(campaign);
+        if tracking_disabled {
+            audience_warnings.push(format!(
+                "Click tracking is disabled for campaign sent {}; its clicks are unknowable, not zero",
+                formatted_date
+            ));
+        }
+
+        // Only include campaigns that had ad clicks (matching Python logic)
+        if ad_clicks > 0 || tracking_disabled {
+            // web_id is what the Mailchimp dashboard URLs use, so capture it
+            // (when present) for a direct link back to the campaign report
+            let web_id = campaign.get("web_id").and_then(|v| v.as_u64());
+            let (mc_open_rate, mc_click_rate) = mailchimp_reported_rates(campaign);
+            let mut campaign_report = serde_json::json!({
+                "campaign_id": campaign_id,
+                "send_date": formatted_date,
+                "unique_opens": unique_opens,
+                "total_opens": total_opens,
+                "total_recipients": total_recipients,
+                "targeted_count": targeted_count,
+                "hard_bounces": hard_bounces,
+                "soft_bounces": soft_bounces,
+                "delivery_rate": delivery_rate(total_recipients, hard_bounces + soft_bounces),
+                "total_clicks": ad_clicks,
+                "ctr": ctr,
+                "clicks_per_thousand": clicks_per_thousand,
+                "tags": tags,
+                "campaign_total_clicks": campaign_total_clicks,
+                "share_of_clicks": share_of_clicks(ad_clicks, campaign_total_clicks),
+                "tracking_disabled": tracking_disabled,
+                "web_id": web_id,
+                "admin_url": web_id.map(|id| campaign_admin_url(dc, id)),
+                "mailchimp_open_rate": mc_open_rate,
+                "mailchimp_click_rate": mc_click_rate
+            });
+
+            // Evaluate any configured custom metrics against this row
+            if !settings.custom_metrics.is_empty() {
+                let mut custom = serde_json::Map::new();
+                for metric in &settings.custom_metrics {
+                    if let Some(value) = eval_custom_metric(&metric.expression, &campaign_report) {
+                        custom.insert(metric.name.clone(), serde_json::json!(value));
+                    }
+                }
+                campaign_report["custom"] = serde_json::Value::Object(custom);
+            }
+
+            // Subject-test context for variate campaigns; absent otherwise
+            if let Some(variate) = variate_winner(campaign) {
+                campaign_report["variate"] = variate;
+            }
+
+            report_data.push(campaign_report);
+        }
+    }
+    
+    // Build "did you mean?" suggestions for tracking URLs that matched nothing
+    let clicked_url_list: Vec<String> = all_clicked_urls.into_iter().collect();
+    let mut suggestions_map = serde_json::Map::new();
+    for tracking_url in &request.tracking_urls {
+        if tracking_url.is_empty() || matched_tracking_urls.contains(tracking_url) {
+            continue;
+        }
+        let suggestions = suggest_similar_urls(tracking_url, &clicked_url_list, 3);
+        if !suggestions.is_empty() {
+            suggestions_map.insert(tracking_url.clone(), serde_json::json!(suggestions));
+        }
+    }
+    let url_suggestions = if suggestions_map.is_empty() {
+        None
+    } else {
+        Some(serde_json::Value::Object(suggestions_map))
+    };
+
+    // Modify the final success check to ensure we have actual data
+    if report_data.is_empty() && !request.allow_empty {
+        return Ok(ReportResponse {
+            success: false,
+            message: format!(
+                "No data found for the specified tracking URLs in campaigns matching '{}'. Please verify your tracking URLs and newsletter type.",
+                request.newsletter_type
+            ),
+            data: None,
+            progress_updates,
+            url_suggestions,
+            warnings: audience_warnings,
+        });
+    }
+    let no_clicks_found = report_data.is_empty();
+
+    // 80% progress
+    let finalizing_update = ProgressUpdate {
+        stage: "FinalizingReport".to_string(),
+        progress: 80,
+        message: "Processing complete. Organizing report data...".to_string(),
+        time_remaining: Some(15), // Estimate 15 seconds for finalization
+        elapsed_secs: None,
+    };
+    
+    // Store and emit update
+    progress_updates.push(finalizing_update.clone());
+    sink.send(finalizing_update);
+    
+    // Sort report data by date
+    report_data.sort_by(|a, b| {
+        let date_a = a.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+        let date_b = b.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+        date_a.cmp(date_b)
+    });
+    
+    // Aggregate clicks per 1000 recipients across all included campaigns
+    let agg_clicks: u64 = report_data.iter()
+        .map(|r| r.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0))
+        .sum();
+    let agg_recipients: u64 = report_data.iter()
+        .map(|r| r.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0))
+        .sum();
+    let aggregate_clicks_per_thousand = if agg_recipients > 0 {
+        (agg_clicks as f64 / agg_recipients as f64) * 1000.0
+    } else {
+        0.0
+    };
+
+    // Create the final report data
+    let mut final_report = serde_json::json!({
+        "campaigns": filtered_campaigns,
+        "report_data": report_data,
+        "metrics": request.metrics,
+        "aggregate_clicks_per_thousand": aggregate_clicks_per_thousand
+    });
+
+    // Nested grouped view alongside the flat rows
+    if let Some(group_by) = request.group_by.as_deref() {
+        final_report["report_groups"] = serde_json::json!(group_report_rows(&report_data, group_by));
+    }
+
+    // Optional audience context: net list growth over the report window
+    if request.include_list_activity {
+        let activity_url = format!("{}/lists/{}/activity?count=180", base_url, settings.mailchimp_audience_id);
+        let activity_response = client
+            .get(&activity_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await;
+
+        if let Ok(response) = activity_response {
+            if response.status().is_success() {
+                if let Ok(activity) = response.json::<serde_json::Value>().await {
+                    let (start_iso, end_iso) = date_range_bounds(&request.date_range)?;
+                    final_report["list_activity"] = sum_list_activity(
+                        &activity,
+                        &start_iso[..10],
+                        &end_iso[..10],
+                    );
+                }
+            }
+        }
+    }
+
+    println!("Final report metrics: {:?}", request.metrics);
+    println!("Final report structure: {:?}", final_report);
+
+    // 90% progress
+    let saving_update = ProgressUpdate {
+        stage: "SavingReport".to_string(),
+        progress: 90,
+        message: "Finalizing and saving report...".to_string(),
+        time_remaining: Some(5),
+        elapsed_secs: None,
+    };
+    
+    // Store and emit update
+    progress_updates.push(saving_update.clone());
+    sink.send(saving_update);
+
+    // Incremental runs fold the fresh rows into the advertiser's previous
+    // report instead of writing a new one
+    let mut merged_existing: Option<SavedReport> = None;
+    if request.incremental && last_fetch.is_some() {
+        if let Ok(app_dir) = app.path().app_config_dir() {
+            merged_existing = merge_into_latest_report(
+                &app_dir,
+                &request.advertiser,
+                &request.newsletter_type,
+                &report_data,
+                &request.date_range.end_date,
+            )?;
+        }
+    }
+
+    let report = match merged_existing {
+        Some(updated) => {
+            final_report = updated.data.clone();
+            updated
+        }
+        None => {
+            // Save the report with metrics
+            let report = SavedReport {
+                id: format!("report-{}", chrono::Utc::now().timestamp_millis()),
+                name: format!("{}-{}-{}", request.advertiser, request.newsletter_type, format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d")),
+                advertiser: request.advertiser,
+                report_type: request.newsletter_type,
+                date_range: request.date_range.clone(),
+                created: format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d"),
+                data: final_report.clone(),
+                metrics: request.metrics.clone(),
+                tags: Vec::new(),
+                read_only: false,
+                elapsed_secs: Some(start_time.elapsed().as_secs()),
+                campaign_count: final_report.get("report_data").and_then(|d| d.as_array()).map(|a| a.len()),
+                notes: String::new(),
+            };
+
+            println!("About to save report with metrics: {:?}", report.metrics);
+            save_report(app.clone(), report.clone())?;
+            report
+        }
+    };
+
+    // Remember this run so the next incremental fetch can start from here
+    if let Ok(app_dir) = app.path().app_config_dir() {
+        record_fetch_time(&app_dir, &fetch_key);
+    }
+
+    // Emit report-generated event with the complete report data
+    if let Err(e) = app.emit("report-generated", serde_json::json!({
+        "report": report,
+        "elapsed_secs": start_time.elapsed().as_secs()
+    })) {
+        println!("Failed to emit report-generated event: {}", e);
+    }
+
+    // 100% progress
+    let complete_update = ProgressUpdate {
+        stage: "Complete".to_string(),
+        progress: 100,
+        message: "Report generation complete!".to_string(),
+        time_remaining: Some(0),
+        elapsed_secs: Some(start_time.elapsed().as_secs()),
+    };
+    
+    // Store and emit update
+    progress_updates.push(complete_update.clone());
+    sink.send(complete_update);
+
+    let message = if no_clicks_found {
+        "Report generated successfully, but no clicks were found in this period".to_string()
+    } else {
+        "Report generated successfully".to_string()
+    };
+
+    Ok(ReportResponse {
+        success: true,
+        message,
+        data: Some(final_report),
+        progress_updates,
+        url_suggestions,
+        warnings: audience_warnings,
+    })
+}
+
+// Fetches the configured audience's merge fields, caching them locally so
+// repeated lookups don't hit the API. Pass refresh=true to refetch.
+#[tauri::command]
+async fn get_merge_fields(app: tauri::AppHandle, refresh: Option<bool>) -> Result<Vec<MergeField>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let cache_path = app_dir.join("merge_fields.json");
+
+    if !refresh.unwrap_or(false) && cache_path.exists() {
+        if let Some(cached) = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Vec<MergeField>>(&s).ok())
+        {
+            return Ok(cached);
+        }
+    }
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let fields_url = format!("{}/lists/{}/merge-fields?count=1000", base_url, settings.mailchimp_audience_id);
+    let response = client
+        .get(&fields_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch merge fields: {}", e))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Mailchimp API error: {}", error_text));
+    }
+
+    let fields_data = response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Failed to parse merge fields response: {}", e))?;
+
+    let mut fields = Vec::new();
+    if let Some(list) = fields_data.get("merge_fields").and_then(|f| f.as_array()) {
+        for field in list {
+            fields.push(MergeField {
+                tag: field.get("tag").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                name: field.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                field_type: field.get("type").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            });
+        }
+    }
+
+    // Best-effort cache write; a failure here shouldn't fail the fetch
+    if let Ok(json) = serde_json::to_string_pretty(&fields) {
+        let _ = fs::create_dir_all(&app_dir);
+        let _ = fs::write(&cache_path, json);
+    }
+
+    Ok(fields)
+}
+
+// Lists the account's campaign folders so the UI can offer a folder picker
+#[tauri::command]
+async fn list_folders(app: tauri::AppHandle) -> Result<Vec<CampaignFolder>, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let folders_url = format!("{}/campaign-folders?count=1000", base_url);
+    let response = client
+        .get(&folders_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Mailchimp API error: {}", error_text));
+    }
+
+    let folders_data = response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Failed to parse folders response: {}", e))?;
+
+    let mut folders = Vec::new();
+    if let Some(list) = folders_data.get("folders").and_then(|f| f.as_array()) {
+        for folder in list {
+            let id = folder.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let name = folder.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            if !id.is_empty() {
+                folders.push(CampaignFolder { id, name });
+            }
+        }
+    }
+
+    Ok(folders)
+}
+
+// Ranks advertisers against each other over one period. The campaign list
+// and each campaign's click details are fetched once and reused across every
+// advertiser's URL set, so this costs the same as a single report.
+#[tauri::command]
+async fn cross_advertiser_report(
+    app: tauri::AppHandle,
+    advertisers_with_urls: Vec<AdvertiserUrls>,
+    date_range: DateRange,
+    newsletter_type: String,
+) -> Result<CrossAdvertiserReport, String> {
+    if advertisers_with_urls.is_empty() {
+        return Err("No advertisers provided".to_string());
+    }
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&date_range)?;
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, None);
+
+    let campaigns_response = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?;
+
+    if !campaigns_response.status().is_success() {
+        let error_text = campaigns_response.text().await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Mailchimp API error: {}", error_text));
+    }
+
+    let campaigns_data = campaigns_response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let filtered_campaigns = filter_campaigns_by_type(campaigns, &newsletter_type);
+
+    // Fetch click details once per campaign; every advertiser reuses them
+    let mut campaign_clicks: Vec<serde_json::Value> = Vec::new();
+    let mut total_unique_opens: u64 = 0;
+
+    for (index, campaign) in filtered_campaigns.iter().enumerate() {
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        total_unique_opens += campaign.get("report_summary")
+            .and_then(|rs| rs.get("unique_opens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        let progress = ((index as f64 / filtered_campaigns.len().max(1) as f64) * 100.0) as u8;
+        let update = ProgressUpdate {
+            stage: "ProcessingCampaigns".to_string(),
+            progress,
+            message: format!("Fetching click details {} of {}", index + 1, filtered_campaigns.len()),
+            time_remaining: None,
+            elapsed_secs: None,
+        };
+        if let Err(e) = app.emit("report-progress", update) {
+            println!("Failed to emit progress update: {}", e);
+        }
+
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        let click_response = client
+            .get(&click_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await;
+
+        if let Ok(response) = click_response {
+            if response.status().is_success() {
+                if let Ok(click_data) = response.json::<serde_json::Value>().await {
+                    campaign_clicks.push(click_data);
+                }
+            }
+        }
+    }
+
+    // One summary row per advertiser, all computed from the shared fetches
+    let mut rows: Vec<AdvertiserSummary> = advertisers_with_urls.iter()
+        .map(|entry| {
+            let total_clicks: u64 = campaign_clicks.iter()
+                .map(|cd| count_matched_clicks(cd, &entry.tracking_urls, "prefix"))
+                .sum();
+            let ctr = if total_unique_opens > 0 {
+                (total_clicks as f64 / total_unique_opens as f64) * 100.0
+            } else {
+                0.0
+            };
+            AdvertiserSummary {
+                advertiser: entry.advertiser.clone(),
+                total_clicks,
+                unique_opens: total_unique_opens,
+                ctr,
+            }
+        })
+        .collect();
+
+    rows.sort_by_key(|r| std::cmp::Reverse(r.total_clicks));
+
+    // Write the comparison CSV to the configured download directory
+    let download_dir = std::path::Path::new(&settings.download_directory);
+    if !download_dir.exists() {
+        std::fs::create_dir_all(download_dir)
+            .map_err(|e| format!("Failed to create download directory: {}", e))?;
+    }
+
+    let mut csv = String::from("Advertiser,Total Clicks,Unique Opens,CTR\n");
+    for row in &rows {
+        csv.push_str(&format!("{},{},{},{:.6}\n", csv_escape(&row.advertiser, ','), row.total_clicks, row.unique_opens, row.ctr));
+    }
+
+    let timestamp = format_timestamp_now(&settings.timestamp_timezone, "%Y%m%d_%H%M%S");
+    let file_name = format!("advertiser_comparison_{}_{}_{}.csv", newsletter_type, date_range.start_date, timestamp);
+    let file_path = download_dir.join(&file_name);
+    std::fs::write(&file_path, csv.as_bytes())
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    Ok(CrossAdvertiserReport {
+        rows,
+        csv_path: file_path.to_string_lossy().to_string(),
+    })
+}
+
+// Winning subject line and test criteria for variate (A/B-tested)
+// campaigns. Mailchimp's variate metadata varies by campaign age and test
+// type, so every lookup is defensive; non-variate campaigns return None.
+fn variate_winner(campaign: &serde_json::Value) -> Option<serde_json::Value> {
+    let variate = campaign.get("variate_settings")?;
+
+    let subject_lines: Vec<&str> = variate.get("subject_lines")
+        .and_then(|s| s.as_array())
+        .map(|arr| arr.iter().filter_map(|s| s.as_str()).collect())
+        .unwrap_or_default();
+
+    // The winner is named by combination id; map it back to a subject line
+    // through the combinations list, where subject_line is an index
+    let winning_subject = variate.get("winning_combination_id")
+        .and_then(|v| v.as_str())
+        .and_then(|winner_id| {
+            variate.get("combinations")
+                .and_then(|c| c.as_array())?
+                .iter()
+                .find(|c| c.get("id").and_then(|i| i.as_str()) == Some(winner_id))
+                .and_then(|c| c.get("subject_line").and_then(|s| s.as_u64()))
+                .and_then(|idx| subject_lines.get(idx as usize).copied())
+        })
+        // A single-subject test leaves no ambiguity even without a winner id
+        .or_else(|| if subject_lines.len() == 1 { Some(subject_lines[0]) } else { None });
+
+    Some(serde_json::json!({
+        "winner_criteria": variate.get("winner_criteria").and_then(|v| v.as_str()).unwrap_or("unknown"),
+        "winning_subject": winning_subject
+    }))
+}
+
+// Share of attempted sends that were delivered, as a percentage.
+// emails_sent already excludes bounces, so attempted = sent + bounced.
+fn delivery_rate(emails_sent: u64, bounces: u64) -> f64 {
+    let attempted = emails_sent + bounces;
+    if attempted == 0 {
+        return 0.0;
+    }
+    (emails_sent as f64 / attempted as f64) * 100.0
+}
+
+// Hard and soft bounce counts from a campaign's bounces object; campaigns
+// that don't report one count as zero bounces
+fn bounce_split(campaign: &serde_json::Value) -> (u64, u64) {
+    let bounces = campaign.get("bounces");
+    let hard = bounces.and_then(|b| b.get("hard_bounces")).and_then(|v| v.as_u64()).unwrap_or(0);
+    let soft = bounces.and_then(|b| b.get("soft_bounces")).and_then(|v| v.as_u64()).unwrap_or(0);
+    (hard, soft)
+}
+
+// Mailchimp's own reported open/click rates from the campaign list's
+// report_summary, converted to percentages so they line up with the ctr
+// column. These are all-links, all-recipients figures and intentionally
+// differ from our ad-specific CTR - a sanity check, not a replacement.
+fn mailchimp_reported_rates(campaign: &serde_json::Value) -> (Option<f64>, Option<f64>) {
+    let summary = campaign.get("report_summary");
+    let open_rate = summary
+        .and_then(|s| s.get("open_rate"))
+        .and_then(|v| v.as_f64())
+        .map(|rate| rate * 100.0);
+    let click_rate = summary
+        .and_then(|s| s.get("click_rate"))
+        .and_then(|v| v.as_f64())
+        .map(|rate| rate * 100.0);
+    (open_rate, click_rate)
+}
+
+// The campaign's report page in the Mailchimp dashboard. web_id is the
+// numeric id shown in dashboard URLs, not the API id.
+fn campaign_admin_url(dc: &str, web_id: u64) -> String {
+    format!("https://{}.admin.mailchimp.com/reports/summary?id={}", dc, web_id)
+}
+
+// A weekly "Monday 08:00 in America/New_York" style schedule. This is the
+// spec and the next-fire math only: the app has no background loop, so the
+// UI asks next_scheduled_run for the next instant and sleeps until it
+// instead of busy-polling.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ReportSchedule {
+    // Full weekday name, case-insensitive
+    weekday: String,
+    // 24h local clock time, "HH:MM"
+    time: String,
+    // IANA zone name like "America/New_York"
+    timezone: String,
+}
+
+fn parse_schedule_weekday(weekday: &str) -> Result<chrono::Weekday, String> {
+    match weekday.to_ascii_lowercase().as_str() {
+        "monday" => Ok(chrono::Weekday::Mon),
+        "tuesday" => Ok(chrono::Weekday::Tue),
+        "wednesday" => Ok(chrono::Weekday::Wed),
+        "thursday" => Ok(chrono::Weekday::Thu),
+        "friday" => Ok(chrono::Weekday::Fri),
+        "saturday" => Ok(chrono::Weekday::Sat),
+        "sunday" => Ok(chrono::Weekday::Sun),
+        other => Err(format!("Unknown weekday: {}", other)),
+    }
+}
+
+// The first UTC instant strictly after `after_utc` at which the schedule
+// fires. Evaluated in the schedule's zone, so the clock time holds across
+// DST: a spring-forward gap fires at the first valid instant after the
+// skipped time, and a fall-back repeat fires only at its first occurrence.
+fn next_fire_time(schedule: &ReportSchedule, after_utc: chrono::DateTime<chrono::Utc>) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    use chrono::{Datelike, TimeZone};
+
+    let tz: chrono_tz::Tz = schedule.timezone.parse()
+        .map_err(|_| format!("Unknown timezone: {}", schedule.timezone))?;
+    let weekday = parse_schedule_weekday(&schedule.weekday)?;
+    let (hour, minute) = schedule.time.split_once(':')
+        .and_then(|(h, m)| Some((h.parse::<u32>().ok()?, m.parse::<u32>().ok()?)))
+        .filter(|(h, m)| *h <= 23 && *m <= 59)
+        .ok_or_else(|| format!("Invalid schedule time: {} (expected HH:MM)", schedule.time))?;
+
+    let mut date = after_utc.with_timezone(&tz).date_naive();
+    // At most 8 candidate days: today may already be past this week's slot
+    for _ in 0..9 {
+        if date.weekday() == weekday {
+            let naive = date.and_hms_opt(hour, minute, 0)
+                .ok_or_else(|| format!("Invalid schedule time: {}", schedule.time))?;
+            let fire = match tz.from_local_datetime(&naive) {
+                chrono::LocalResult::Single(t) => Some(t),
+                // Fall back: the wall time happens twice; take the first so
+                // the job fires once, not twice
+                chrono::LocalResult::Ambiguous(first, _) => Some(first),
+                // Spring forward: the wall time doesn't exist; walk forward
+                // to the first instant after the gap
+                chrono::LocalResult::None => {
+                    let mut probe = naive;
+                    let mut resolved = None;
+                    for _ in 0..240 {
+                        probe += chrono::Duration::minutes(1);
+                        match tz.from_local_datetime(&probe) {
+                            chrono::LocalResult::Single(t) => { resolved = Some(t); break; }
+                            chrono::LocalResult::Ambiguous(first, _) => { resolved = Some(first); break; }
+                            chrono::LocalResult::None => {}
+                        }
+                    }
+                    resolved
+                }
+            };
+            if let Some(fire) = fire {
+                let fire_utc = fire.with_timezone(&chrono::Utc);
+                if fire_utc > after_utc {
+                    return Ok(fire_utc);
+                }
+            }
+        }
+        date = date.succ_opt().ok_or_else(|| "Schedule date out of range".to_string())?;
+    }
+    Err(format!("Could not compute a fire time for {} {}", schedule.weekday, schedule.time))
+}
+
+// The next UTC instant the schedule fires after now; the UI sleeps until
+// this and then triggers the run
+#[tauri::command]
+fn next_scheduled_run(schedule: ReportSchedule) -> Result<String, String> {
+    next_fire_time(&schedule, chrono::Utc::now())
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+// Debug captures live under app_dir/debug_captures/<run timestamp>/, one
+// JSON file per raw API response. The API key travels in headers, so the
+// captured bodies never contain it.
+fn debug_capture_dir(app_dir: &Path, timestamp: &str) -> std::path::PathBuf {
+    app_dir.join("debug_captures").join(timestamp)
+}
+
+// Best-effort write of one raw response; a failed capture logs and never
+// fails the run it's shadowing
+fn write_debug_capture(dir: &Path, name: &str, payload: &serde_json::Value) {
+    let result = fs::create_dir_all(dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| serde_json::to_string_pretty(payload).map_err(|e| e.to_string()))
+        .and_then(|json| fs::write(dir.join(format!("{}.json", name)), json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        println!("Failed to write debug capture {}: {}", name, e);
+    }
+}
+
+// The capture folders on disk, oldest first
+fn list_debug_captures_in_dir(app_dir: &Path) -> Vec<String> {
+    let root = app_dir.join("debug_captures");
+    let entries = match fs::read_dir(&root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut captures: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    captures.sort();
+    captures
+}
+
+// Lists the debug capture folders recorded by runs with debug_capture on
+#[tauri::command]
+fn get_debug_captures(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    Ok(list_debug_captures_in_dir(&app_dir))
+}
+
+// Mailchimp exposes link tracking per body as tracking.html_clicks and
+// tracking.clicks. Missing fields count as enabled, matching the API
+// default; only an explicit opt-out of both counts as disabled.
+fn click_tracking_disabled(campaign: &serde_json::Value) -> bool {
+    let tracking = match campaign.get("tracking") {
+        Some(t) => t,
+        None => return false,
+    };
+    let html_clicks = tracking.get("html_clicks").and_then(|v| v.as_bool()).unwrap_or(true);
+    let text_clicks = tracking.get("clicks").and_then(|v| v.as_bool()).unwrap_or(true);
+    !html_clicks && !text_clicks
+}
+
+// Builds one report row from a campaign and its click details, the same
+// shape generate_report produces. Returns None when the campaign is missing
+// required fields or none of the tracking URLs were clicked.
+fn campaign_report_row(
+    campaign: &serde_json::Value,
+    click_data: &serde_json::Value,
+    tracking_urls: &[String],
+    path_match: &str,
+    custom_metrics: &[CustomMetric],
+    dc: &str,
+) -> Option<serde_json::Value> {
+    let campaign_id = campaign.get("id").and_then(|id| id.as_str())?;
+    let send_time = campaign.get("send_time").and_then(|st| st.as_str())?;
+    let formatted_date = chrono::DateTime::parse_from_rfc3339(send_time)
+        .ok()?
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let report_summary = campaign.get("report_summary").unwrap_or(&serde_json::Value::Null);
+    let unique_opens = report_summary.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0);
+    let total_opens = report_summary.get("opens").and_then(|v| v.as_u64()).unwrap_or(0);
+    let total_recipients = campaign.get("emails_sent").and_then(|v| v.as_u64()).unwrap_or(0);
+    // Targeted segment size; a full-list send reports the same number here
+    // as emails_sent
+    let targeted_count = campaign.get("recipients")
+        .and_then(|r| r.get("recipient_count"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(total_recipients);
+    let (hard_bounces, soft_bounces) = bounce_split(campaign);
+
+    let ad_clicks = count_matched_clicks(click_data, tracking_urls, path_match);
+    let tracking_disabled = click_tracking_disabled(campaign);
+    if ad_clicks == 0 && !tracking_disabled {
+        return None;
+    }
+    let campaign_total_clicks = count_total_clicks(click_data);
+
+    let ctr = if unique_opens > 0 {
+        (ad_clicks as f64 / unique_opens as f64) * 100.0
+    } else {
+        0.0
+    };
+    let clicks_per_thousand = if total_recipients > 0 {
+        (ad_clicks as f64 / total_recipients as f64) * 1000.0
+    } else {
+        0.0
+    };
+
+    let tags = campaign_tag_names(campaign);
+
+    let web_id = campaign.get("web_id").and_then(|v| v.as_u64());
+    let (mc_open_rate, mc_click_rate) = mailchimp_reported_rates(campaign);
+    let mut row = serde_json::json!({
+        "campaign_id": campaign_id,
+        "send_date": formatted_date,
+        "unique_opens": unique_opens,
+        "total_opens": total_opens,
+        "total_recipients": total_recipients,
+        "targeted_count": targeted_count,
+        "hard_bounces": hard_bounces,
+        "soft_bounces": soft_bounces,
+        "delivery_rate": delivery_rate(total_recipients, hard_bounces + soft_bounces),
+        "total_clicks": ad_clicks,
+        "ctr": ctr,
+        "clicks_per_thousand": clicks_per_thousand,
+        "tags": tags,
+        "campaign_total_clicks": campaign_total_clicks,
+        "share_of_clicks": share_of_clicks(ad_clicks, campaign_total_clicks),
+        "tracking_disabled": tracking_disabled,
+        "web_id": web_id,
+        "admin_url": web_id.map(|id| campaign_admin_url(dc, id)),
+        "mailchimp_open_rate": mc_open_rate,
+        "mailchimp_click_rate": mc_click_rate
+    });
+
+    if !custom_metrics.is_empty() {
+        let mut custom = serde_json::Map::new();
+        for metric in custom_metrics {
+            if let Some(value) = eval_custom_metric(&metric.expression, &row) {
+                custom.insert(metric.name.clone(), serde_json::json!(value));
+            }
+        }
+        row["custom"] = serde_json::Value::Object(custom);
+    }
+
+    if let Some(variate) = variate_winner(campaign) {
+        row["variate"] = variate;
+    }
+
+    Some(row)
+}
+
+// Month-end helper: one report per configured advertiser in a single pass.
+// The campaign list and click details are fetched once and reused, so a
+// ten-advertiser batch costs the same API traffic as one report. Each
+// advertiser uses the default tracking URLs persisted in settings.
+#[tauri::command]
+async fn batch_generate(
+    app: tauri::AppHandle,
+    date_range: DateRange,
+    newsletter_type: String,
+    format: Option<String>,
+) -> Result<BatchResult, String> {
+    if let Some(f) = format.as_deref() {
+        if !matches!(f, "csv" | "json") {
+            return Err(ReportError::InvalidFormat(f.to_string()).into());
+        }
+    }
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+    if settings.advertisers.is_empty() {
+        return Err("No advertisers configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    // dc still feeds the admin-URL links even when the API base is overridden
+    let dc = settings.mailchimp_api_key.split('-').next_back().unwrap_or("us1");
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&date_range)?;
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, None);
+
+    let campaigns_response = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?;
+
+    if !campaigns_response.status().is_success() {
+        let error_text = campaigns_response.text().await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Mailchimp API error: {}", error_text));
+    }
+
+    let campaigns_data = campaigns_response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let filtered_campaigns = filter_campaigns_by_type(campaigns, &newsletter_type);
+
+    // Fetch click details once per campaign; every advertiser reuses them
+    let mut campaign_details: Vec<(&serde_json::Value, serde_json::Value)> = Vec::new();
+    for (index, campaign) in filtered_campaigns.iter().enumerate() {
+        emit_bulk_progress(&app, "batch_generate", index, filtered_campaigns.len());
+
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        let click_response = client
+            .get(&click_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await;
+
+        if let Ok(response) = click_response {
+            if response.status().is_success() {
+                if let Ok(click_data) = response.json::<serde_json::Value>().await {
+                    campaign_details.push((campaign, click_data));
+                }
+            }
+        }
+    }
+
+    let path_match = default_path_match();
+    let created = format_timestamp_now(&settings.timestamp_timezone, "%Y-%m-%d");
+    let batch_stamp = chrono::Utc::now().timestamp_millis();
+    let mut items: Vec<BatchItem> = Vec::new();
+
+    for (index, advertiser) in settings.advertisers.iter().enumerate() {
+        emit_bulk_progress(&app, "batch_generate", index, settings.advertisers.len());
+
+        let failure = |message: String| BatchItem {
+            advertiser: advertiser.clone(),
+            success: false,
+            message,
+            report_id: None,
+            export_path: None,
+        };
+
+        let urls = match settings.advertiser_urls.get(advertiser) {
+            Some(urls) if !urls.is_empty() => urls.clone(),
+            _ => {
+                items.push(failure("No default tracking URLs configured".to_string()));
+                continue;
+            }
+        };
+        let urls = match validate_tracking_urls(&urls) {
+            Ok(urls) => urls,
+            Err(e) => {
+                items.push(failure(e));
+                continue;
+            }
+        };
+
+        let mut report_data: Vec<serde_json::Value> = campaign_details.iter()
+            .filter_map(|(campaign, click_data)| {
+                campaign_report_row(campaign, click_data, &urls, &path_match, &settings.custom_metrics, dc)
+            })
+            .collect();
+
+        if report_data.is_empty() {
+            items.push(failure("No clicks found for the configured tracking URLs".to_string()));
+            continue;
+        }
+
+        report_data.sort_by(|a, b| {
+            let date_a = a.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+            let date_b = b.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+            date_a.cmp(date_b)
+        });
+
+        // The shared campaign list is not embedded per report, to keep a
+        // many-advertiser batch from multiplying the stored payload
+        let final_report = serde_json::json!({
+            "report_data": report_data,
+            "metrics": settings.default_metrics,
+        });
+
+        let report = SavedReport {
+            id: format!("report-{}-{}", batch_stamp, index),
+            name: format!("{}-{}-{}", advertiser, newsletter_type, created),
+            advertiser: advertiser.clone(),
+            report_type: newsletter_type.clone(),
+            date_range: date_range.clone(),
+            created: created.clone(),
+            data: final_report,
+            metrics: settings.default_metrics.clone(),
+            tags: Vec::new(),
+            read_only: false,
+            elapsed_secs: None,
+            campaign_count: None,
+            notes: String::new(),
+        };
+
+        if let Err(e) = save_report(app.clone(), report.clone()) {
+            items.push(failure(format!("Failed to save report: {}", e)));
+            continue;
+        }
+
+        let export_path = match format.as_deref() {
+            Some("csv") => {
+                let report_value = serde_json::to_value(&report)
+                    .map_err(|e| format!("Failed to serialize report: {}", e))?;
+                match download_csv(app.clone(), report_value, None, None, None, None) {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        items.push(failure(format!("Report saved but export failed: {}", e)));
+                        continue;
+                    }
+                }
+            }
+            Some("json") => {
+                let report_value = serde_json::to_value(&report)
+                    .map_err(|e| format!("Failed to serialize report: {}", e))?;
+                match download_report(app.clone(), report_value) {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        items.push(failure(format!("Report saved but export failed: {}", e)));
+                        continue;
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        items.push(BatchItem {
+            advertiser: advertiser.clone(),
+            success: true,
+            message: format!("Report generated with {} campaigns", report.data["report_data"].as_array().map(|a| a.len()).unwrap_or(0)),
+            report_id: Some(report.id),
+            export_path,
+        });
+    }
+
+    let succeeded = items.iter().filter(|item| item.success).count();
+    let failed = items.len() - succeeded;
+
+    println!("Batch generation finished: {} succeeded, {} failed", succeeded, failed);
+
+    Ok(BatchResult { items, succeeded, failed })
+}
+
+// Structured error for the export core. Commands convert to String at the
+// boundary since the frontend only displays the message.
+#[derive(Debug)]
+enum ReportError {
+    InvalidFormat(String),
+    Auth(String),
+    Network(String),
+    Validation(String),
+}
+
+impl std::fmt::Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::InvalidFormat(msg) => write!(f, "Invalid report format: {}", msg),
+            ReportError::Auth(msg) => write!(f, "S3 authentication failed: {}", msg),
+            ReportError::Network(msg) => write!(f, "S3 upload failed: {}", msg),
+            ReportError::Validation(msg) => write!(f, "Report data validation failed: {}", msg),
+        }
+    }
+}
+
+impl From<ReportError> for String {
+    fn from(e: ReportError) -> String {
+        e.to_string()
+    }
+}
+
+// Knobs for CSV building, shared by the preview and the file exporters
+#[derive(Debug, Clone)]
+struct CsvOptions {
+    // When set, only the top N campaigns by clicks are written (plus a totals
+    // row computed from every campaign, not just the visible ones)
+    top_n: Option<usize>,
+    // Render integer counts with a thousands separator
+    thousands_separator: bool,
+    // Extra columns computed from the configured custom metric expressions
+    custom_metrics: Vec<CustomMetric>,
+    // Drop metric columns that are zero in every row
+    compact: bool,
+    // "month" or "week": emit subtotal rows between groups and a grand
+    // total at the end
+    group_by: Option<String>,
+    // When set, rows with a CTR under this percentage get a Below Threshold
+    // column flagging them, and totals rows carry the flagged-row count
+    ctr_alert_threshold: Option<f64>,
+    // CSV field delimiter, normally a comma
+    csv_delimiter: char,
+    // Separator for fractional values, normally a dot
+    decimal_separator: char,
+    // The advertiser's rate-card entry; when set, every row gets Cost and
+    // CPC columns and the totals row carries the total spend
+    cost_per_click: Option<f64>,
+    // "half_up" or "half_even": which way rate values round at .5
+    rounding_mode: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            top_n: None,
+            thousands_separator: false,
+            custom_metrics: Vec::new(),
+            compact: false,
+            group_by: None,
+            ctr_alert_threshold: None,
+            csv_delimiter: ',',
+            decimal_separator: '.',
+            cost_per_click: None,
+            rounding_mode: default_rounding_mode(),
+        }
+    }
+}
+
+// Removes metric columns whose values are all zero across every data row,
+// so client-facing sheets aren't padded with dead columns. The Date column
+// always stays, and the totals row loses the same columns as the data rows.
+fn compact_csv(csv: &str, delimiter: char) -> String {
+    let rows: Vec<Vec<String>> = csv.lines().map(|line| parse_csv_line_with(line, delimiter)).collect();
+    if rows.len() < 2 {
+        return csv.to_string();
+    }
+
+    let is_zero = |cell: &str| {
+        let trimmed = cell.trim().replace(',', "");
+        !trimmed.is_empty() && trimmed.parse::<f64>().map(|v| v == 0.0).unwrap_or(false)
+    };
+
+    let width = rows[0].len();
+    let keep: Vec<bool> = (0..width)
+        .map(|i| {
+            i == 0 || rows[1..].iter().any(|row| {
+                !is_zero(row.get(i).map(|c| c.as_str()).unwrap_or(""))
+            })
+        })
+        .collect();
+
+    let mut compacted = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row.iter()
+            .enumerate()
+            .filter(|(i, _)| *keep.get(*i).unwrap_or(&true))
+            .map(|(_, cell)| csv_escape(cell, delimiter))
+            .collect();
+        compacted.push_str(&cells.join(&delimiter.to_string()));
+        compacted.push('\n');
+    }
+    compacted
+}
+
+// Picks where an advertiser's exports go: their mapped client folder when
+// one is configured, otherwise the global download directory. Creates the
+// directory like the exporters always did for the default.
+fn resolve_export_dir(settings: &Settings, advertiser: &str) -> Result<std::path::PathBuf, String> {
+    let dir = settings.per_advertiser_dir.get(advertiser)
+        .filter(|d| !d.is_empty())
+        .cloned()
+        .unwrap_or_else(|| settings.download_directory.clone());
+
+    let path = std::path::PathBuf::from(dir);
+    if !path.exists() {
+        fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create download directory: {}", e))?;
+    }
+
+    Ok(path)
+}
+
+// Shared presentation for integer metric cells (opens, recipients, clicks)
+// so every exporter formats them the same way
+fn format_count(value: u64, thousands_separator: bool) -> String {
+    if !thousands_separator {
+        return value.to_string();
+    }
+
+    let digits = value.to_string();
+    let mut formatted = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
+// Sums the per-campaign entries into one totals object. CTR and the derived
+// rates are recomputed from the summed counts rather than averaged.
+fn compute_totals(entries: &[serde_json::Value]) -> serde_json::Value {
+    let unique_opens: u64 = entries.iter().map(|e| e.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let total_opens: u64 = entries.iter().map(|e| e.get("total_opens").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let total_recipients: u64 = entries.iter().map(|e| e.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let targeted_count: u64 = entries.iter().map(|e| e.get("targeted_count").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let hard_bounces: u64 = entries.iter().map(|e| e.get("hard_bounces").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let soft_bounces: u64 = entries.iter().map(|e| e.get("soft_bounces").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let total_clicks: u64 = entries.iter().map(|e| e.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+    let campaign_total_clicks: u64 = entries.iter().map(|e| e.get("campaign_total_clicks").and_then(|v| v.as_u64()).unwrap_or(0)).sum();
+
+    // Campaigns with click tracking disabled can't contribute clicks, so
+    // they stay out of the CTR denominator instead of dragging it down
+    let (ctr_clicks, ctr_opens) = entries.iter()
+        .filter(|e| !e.get("tracking_disabled").and_then(|v| v.as_bool()).unwrap_or(false))
+        .fold((0u64, 0u64), |(clicks, opens), e| (
+            clicks + e.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0),
+            opens + e.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0),
+        ));
+    let ctr = if ctr_opens > 0 {
+        (ctr_clicks as f64 / ctr_opens as f64) * 100.0
+    } else {
+        0.0
+    };
+    let clicks_per_thousand = if total_recipients > 0 {
+        (total_clicks as f64 / total_recipients as f64) * 1000.0
+    } else {
+        0.0
+    };
+
+    serde_json::json!({
+        "unique_opens": unique_opens,
+        "total_opens": total_opens,
+        "total_recipients": total_recipients,
+        "targeted_count": targeted_count,
+        "hard_bounces": hard_bounces,
+        "soft_bounces": soft_bounces,
+        "delivery_rate": delivery_rate(total_recipients, hard_bounces + soft_bounces),
+        "total_clicks": total_clicks,
+        "ctr": ctr,
+        "clicks_per_thousand": clicks_per_thousand,
+        "campaign_total_clicks": campaign_total_clicks,
+        "share_of_clicks": share_of_clicks(total_clicks, campaign_total_clicks)
+    })
+}
+
+// Quotes a CSV cell when it contains characters that would break the row
+// under the given field delimiter
+fn csv_escape(value: &str, delimiter: char) -> String {
+    if value.contains(delimiter) || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Rounds a rate to `precision` decimals under the configured mode. At an
+// exact .5 boundary, "half_up" rounds away from zero (2.345 -> 2.35) while
+// "half_even" keeps the even digit (2.345 -> 2.34, 2.355 -> 2.36).
+fn round_rate(value: f64, precision: usize, mode: &str) -> f64 {
+    let negative = value < 0.0;
+    // Go through a decimal rendering with guard digits so a value stored in
+    // binary as 2.34499... still reads as an exact .5 boundary
+    let rendered = format!("{:.*}", precision + 9, value.abs());
+    let (head, guard) = rendered.split_at(rendered.len() - 9);
+    let guard: u64 = guard.parse().unwrap_or(0);
+    let base: f64 = head.trim_end_matches('.').parse().unwrap_or(0.0);
+
+    let round_up = match guard.cmp(&500_000_000) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => match mode {
+            "half_up" => true,
+            // half_even: round up only when the kept digit is odd
+            _ => {
+                let last = head.chars().rev().find(|c| c.is_ascii_digit()).unwrap_or('0');
+                (last as u8 - b'0') % 2 == 1
+            }
+        },
+    };
+
+    let rounded = if round_up {
+        base + 10f64.powi(-(precision as i32))
+    } else {
+        base
+    };
+    if negative { -rounded } else { rounded }
+}
+
+// Formats a fractional value for CSV output, honoring the configured
+// rounding mode and decimal separator. A comma decimal under a comma
+// delimiter comes out quoted so the file stays parseable.
+fn format_decimal(value: f64, precision: usize, opts: &CsvOptions) -> String {
+    let value = round_rate(value, precision, &opts.rounding_mode);
+    let formatted = format!("{:.*}", precision, value);
+    let formatted = if opts.decimal_separator == '.' {
+        formatted
+    } else {
+        formatted.replace('.', &opts.decimal_separator.to_string())
+    };
+    csv_escape(&formatted, opts.csv_delimiter)
+}
+
+// The subtotal bucket a row belongs to: calendar month ("2025-01") or ISO
+// week. Rows are already date-sorted, so equal keys are adjacent.
+fn group_key(send_date: &str, group_by: &str) -> String {
+    if group_by == "week" {
+        if let Ok(date) = chrono::NaiveDate::parse_from_str(send_date, "%Y-%m-%d") {
+            return iso_week_label(date);
+        }
+    }
+    send_date.chars().take(7).collect()
+}
+
+// Nested view of the rows for grouped output. The flat report_data array is
+// kept untouched alongside this for backward compatibility.
+fn group_report_rows(entries: &[serde_json::Value], group_by: &str) -> Vec<serde_json::Value> {
+    let mut groups: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+
+    for entry in entries {
+        let key = group_key(entry.get("send_date").and_then(|d| d.as_str()).unwrap_or(""), group_by);
+        match groups.last_mut() {
+            Some((last_key, rows)) if *last_key == key => rows.push(entry.clone()),
+            _ => groups.push((key, vec![entry.clone()])),
+        }
+    }
+
+    groups.into_iter()
+        .map(|(key, rows)| {
+            let subtotal = compute_totals(&rows);
+            serde_json::json!({
+                "key": key,
+                "rows": rows,
+                "subtotal": subtotal
+            })
+        })
+        .collect()
+}
+
+// Totals for one stretch of rows, with the count of CTR-flagged rows folded
+// in when an alert threshold is active
+fn totals_with_flags(rows: &[serde_json::Value], opts: &CsvOptions) -> serde_json::Value {
+    let mut totals = compute_totals(rows);
+    if let Some(threshold) = opts.ctr_alert_threshold {
+        let flagged = rows.iter()
+            .filter(|r| r.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0) < threshold)
+            .count();
+        totals["below_threshold_count"] = serde_json::json!(flagged);
+    }
+    totals
+}
+
+// Looks up the advertiser's agreed cost per click. A populated rate card
+// with no entry for this advertiser logs a note, so missing pricing is
+// visible instead of the cost columns just silently disappearing.
+fn rate_for_advertiser(rate_card: &HashMap<String, f64>, advertiser: &str) -> Option<f64> {
+    match rate_card.get(advertiser) {
+        Some(rate) => Some(*rate),
+        None => {
+            if !rate_card.is_empty() {
+                println!("Note: no rate card entry for '{}', cost columns omitted", advertiser);
+            }
+            None
+        }
+    }
+}
+
+// Spend figures for one row of clicks. The rate is the agreed cost per
+// click; a zero-click row costs nothing and reports a zero CPC rather than
+// dividing by zero.
+fn campaign_spend(clicks: u64, rate: f64) -> (f64, f64) {
+    let cost = clicks as f64 * rate;
+    let cpc = if clicks > 0 { cost / clicks as f64 } else { 0.0 };
+    (cost, cpc)
+}
+
+// One totals-style CSV row (grand total or group subtotal), with the label
+// in the Date column and the same column order as the data rows
+fn totals_row_fields(label: &str, totals: &serde_json::Value, metrics: &serde_json::Value, opts: &CsvOptions) -> Vec<String> {
+    let mut fields = vec![label.to_string()];
+    if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(csv_escape(&format_count(totals.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+    }
+    if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(csv_escape(&format_count(totals.get("total_opens").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+    }
+    if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(csv_escape(&format_count(totals.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+    }
+    if metrics.get("targeted_count").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(csv_escape(&format_count(totals.get("targeted_count").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+    }
+    if metrics.get("bounce_detail").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(csv_escape(&format_count(totals.get("hard_bounces").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+        fields.push(csv_escape(&format_count(totals.get("soft_bounces").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+        fields.push(format_decimal(totals.get("delivery_rate").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+    }
+    if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(csv_escape(&format_count(totals.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+    }
+    if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(format_decimal(totals.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0), 6, opts));
+    }
+    // Mailchimp's rates don't aggregate meaningfully across campaigns
+    if metrics.get("mailchimp_rates").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(String::new());
+        fields.push(String::new());
+    }
+    if metrics.get("clicks_per_thousand").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(format_decimal(totals.get("clicks_per_thousand").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+    }
+    if metrics.get("share_of_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(format_decimal(totals.get("share_of_clicks").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+    }
+    if metrics.get("tags").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(String::new());
+    }
+    if metrics.get("admin_url").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(String::new());
+    }
+    if metrics.get("variate_winner").and_then(|v| v.as_bool()).unwrap_or(false) {
+        fields.push(String::new());
+    }
+    for metric in &opts.custom_metrics {
+        let value = eval_custom_metric(&metric.expression, totals).unwrap_or(0.0);
+        fields.push(format_decimal(value, 2, opts));
+    }
+    if opts.ctr_alert_threshold.is_some() {
+        fields.push(totals.get("below_threshold_count").and_then(|v| v.as_u64()).unwrap_or(0).to_string());
+    }
+    if let Some(rate) = opts.cost_per_click {
+        let clicks = totals.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+        let (cost, cpc) = campaign_spend(clicks, rate);
+        fields.push(format_decimal(cost, 2, opts));
+        fields.push(format_decimal(cpc, 2, opts));
+    }
+    fields
+}
+
+// Builds the CSV text for a report's data object, honoring the selected
+// metrics. Shared by preview_csv, open_report_in_excel, and download_csv.
+// Checks that every enabled metric's key exists in every row before an
+// exporter reads it. Rows from mixed pipeline versions can lack newer keys,
+// and the exporters would otherwise silently print 0 for them.
+fn validate_report_data(report_data: &serde_json::Value, metrics: &serde_json::Value) -> Result<(), ReportError> {
+    // Metric flags and the row keys they read; display-only flags like
+    // bounce_detail fan out to several keys
+    let required: &[(&str, &[&str])] = &[
+        ("unique_opens", &["unique_opens"]),
+        ("total_opens", &["total_opens"]),
+        ("total_recipients", &["total_recipients"]),
+        ("targeted_count", &["targeted_count"]),
+        ("bounce_detail", &["hard_bounces", "soft_bounces", "delivery_rate"]),
+        ("total_clicks", &["total_clicks"]),
+        ("ctr", &["ctr"]),
+        ("mailchimp_rates", &["mailchimp_open_rate", "mailchimp_click_rate"]),
+        ("clicks_per_thousand", &["clicks_per_thousand"]),
+        ("share_of_clicks", &["share_of_clicks"]),
+    ];
+
+    let rows = match report_data.get("report_data").and_then(|d| d.as_array()) {
+        Some(rows) => rows,
+        None => return Ok(()),
+    };
+
+    for (index, row) in rows.iter().enumerate() {
+        for (flag, keys) in required {
+            if !metrics.get(*flag).and_then(|v| v.as_bool()).unwrap_or(false) {
+                continue;
+            }
+            for key in *keys {
+                if row.get(*key).is_none() {
+                    return Err(ReportError::Validation(format!(
+                        "row {} ({}) is missing enabled metric field '{}'",
+                        index,
+                        row.get("send_date").and_then(|v| v.as_str()).unwrap_or("unknown date"),
+                        key
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn build_csv(report_data: &serde_json::Value, metrics: &serde_json::Value, opts: &CsvOptions) -> Result<String, ReportError> {
+    validate_report_data(report_data, metrics)?;
+
+    // Create CSV header based on selected metrics
+    let mut header_fields = vec!["Date"];
+    if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Unique Opens");
+    }
+    if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Total Opens");
+    }
+    if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Total Recipients");
+    }
+    if metrics.get("targeted_count").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Targeted Count");
+    }
+    if metrics.get("bounce_detail").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Hard Bounces");
+        header_fields.push("Soft Bounces");
+        header_fields.push("Delivery Rate");
+    }
+    if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Total Clicks");
+    }
+    if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("CTR");
+    }
+    if metrics.get("mailchimp_rates").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Mailchimp Open Rate");
+        header_fields.push("Mailchimp Click Rate");
+    }
+    if metrics.get("clicks_per_thousand").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Clicks Per 1000 Recipients");
+    }
+    if metrics.get("share_of_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Share of Clicks");
+    }
+    if metrics.get("tags").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Tags");
+    }
+    if metrics.get("admin_url").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Admin URL");
+    }
+    if metrics.get("variate_winner").and_then(|v| v.as_bool()).unwrap_or(false) {
+        header_fields.push("Winning Subject");
+    }
+    for metric in &opts.custom_metrics {
+        header_fields.push(metric.name.as_str());
+    }
+    if opts.ctr_alert_threshold.is_some() {
+        header_fields.push("Below Threshold");
+    }
+    if opts.cost_per_click.is_some() {
+        header_fields.push("Cost");
+        header_fields.push("CPC");
+    }
+
+    let mut csv = String::new();
+    csv.push_str(&header_fields.join(&opts.csv_delimiter.to_string()));
+    csv.push('\n');
+
+    if let Some(report_entries) = report_data.get("report_data").and_then(|d| d.as_array()) {
+        // Report entries are already sorted by date in the backend. With top_n
+        // set, re-rank by clicks and keep only the best performers.
+        let mut rows_to_write: Vec<serde_json::Value> = report_entries.clone();
+        if let Some(n) = opts.top_n {
+            rows_to_write.sort_by(|a, b| {
+                let clicks_a = a.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+                let clicks_b = b.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+                clicks_b.cmp(&clicks_a)
+            });
+            rows_to_write.truncate(n);
+        }
+
+        let mut current_group: Option<String> = None;
+        let mut group_start = 0;
+
+        for (index, entry) in rows_to_write.iter().enumerate() {
+            // Close out the previous group with a subtotal row before a new
+            // one starts
+            if let Some(group_by) = opts.group_by.as_deref() {
+                let key = group_key(entry.get("send_date").and_then(|d| d.as_str()).unwrap_or(""), group_by);
+                if current_group.as_deref() != Some(key.as_str()) {
+                    if let Some(previous) = &current_group {
+                        let subtotal = totals_with_flags(&rows_to_write[group_start..index], opts);
+                        csv.push_str(&totals_row_fields(&format!("Subtotal {}", previous), &subtotal, metrics, opts).join(&opts.csv_delimiter.to_string()));
+                        csv.push('\n');
+                    }
+                    current_group = Some(key);
+                    group_start = index;
+                }
+            }
+
+            let mut row_fields = vec![entry.get("send_date").and_then(|d| d.as_str()).unwrap_or("N/A").to_string()];
+
+            if metrics.get("unique_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(csv_escape(&format_count(entry.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+            }
+            if metrics.get("total_opens").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(csv_escape(&format_count(entry.get("total_opens").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+            }
+            if metrics.get("total_recipients").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(csv_escape(&format_count(entry.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+            }
+            if metrics.get("targeted_count").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(csv_escape(&format_count(entry.get("targeted_count").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+            }
+            if metrics.get("bounce_detail").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(csv_escape(&format_count(entry.get("hard_bounces").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+                row_fields.push(csv_escape(&format_count(entry.get("soft_bounces").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+                row_fields.push(format_decimal(entry.get("delivery_rate").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+            }
+            if metrics.get("total_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(csv_escape(&format_count(entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0), opts.thousands_separator), opts.csv_delimiter));
+            }
+            if metrics.get("ctr").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(format_decimal(entry.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0), 6, opts));
+            }
+            if metrics.get("mailchimp_rates").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(format_decimal(entry.get("mailchimp_open_rate").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+                row_fields.push(format_decimal(entry.get("mailchimp_click_rate").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+            }
+            if metrics.get("clicks_per_thousand").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(format_decimal(entry.get("clicks_per_thousand").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+            }
+            if metrics.get("share_of_clicks").and_then(|v| v.as_bool()).unwrap_or(false) {
+                row_fields.push(format_decimal(entry.get("share_of_clicks").and_then(|v| v.as_f64()).unwrap_or(0.0), 2, opts));
+            }
+            if metrics.get("tags").and_then(|v| v.as_bool()).unwrap_or(false) {
+                let joined = entry.get("tags")
+                    .and_then(|t| t.as_array())
+                    .map(|arr| arr.iter()
+                        .filter_map(|tag| tag.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; "))
+                    .unwrap_or_default();
+                row_fields.push(csv_escape(&joined, opts.csv_delimiter));
+            }
+            if metrics.get("admin_url").and_then(|v| v.as_bool()).unwrap_or(false) {
+                // Blank for campaigns Mailchimp returned without a web_id
+                let admin_url = entry.get("admin_url").and_then(|v| v.as_str()).unwrap_or("");
+                row_fields.push(csv_escape(admin_url, opts.csv_delimiter));
+            }
+            if metrics.get("variate_winner").and_then(|v| v.as_bool()).unwrap_or(false) {
+                // Blank for campaigns that weren't subject-tested
+                let subject = entry.get("variate")
+                    .and_then(|v| v.get("winning_subject"))
+                    .and_then(|s| s.as_str())
+                    .unwrap_or("");
+                row_fields.push(csv_escape(subject, opts.csv_delimiter));
+            }
+
+            for metric in &opts.custom_metrics {
+                // Stored values win; recompute for reports saved before the
+                // metric was configured
+                let value = entry.get("custom")
+                    .and_then(|c| c.get(&metric.name))
+                    .and_then(|v| v.as_f64())
+                    .or_else(|| eval_custom_metric(&metric.expression, entry))
+                    .unwrap_or(0.0);
+                row_fields.push(format_decimal(value, 2, opts));
+            }
+
+            if let Some(threshold) = opts.ctr_alert_threshold {
+                let ctr = entry.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                row_fields.push((ctr < threshold).to_string());
+            }
+            if let Some(rate) = opts.cost_per_click {
+                let clicks = entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+                let (cost, cpc) = campaign_spend(clicks, rate);
+                row_fields.push(format_decimal(cost, 2, opts));
+                row_fields.push(format_decimal(cpc, 2, opts));
+            }
+
+            csv.push_str(&row_fields.join(&opts.csv_delimiter.to_string()));
+            csv.push('\n');
+        }
+
+        // Close out the final group
+        if let Some(key) = &current_group {
+            let subtotal = totals_with_flags(&rows_to_write[group_start..], opts);
+            csv.push_str(&totals_row_fields(&format!("Subtotal {}", key), &subtotal, metrics, opts).join(&opts.csv_delimiter.to_string()));
+            csv.push('\n');
+        }
+
+        // The totals row always reflects every campaign, even when only the
+        // top N rows are shown. Grouped output always ends on the grand
+        // total so the subtotals have something to reconcile against.
+        if opts.top_n.is_some() || opts.group_by.is_some() {
+            let totals = totals_with_flags(report_entries, opts);
+            let totals_fields = totals_row_fields("Totals", &totals, metrics, opts);
+            csv.push_str(&totals_fields.join(&opts.csv_delimiter.to_string()));
+            csv.push('\n');
+        }
+    } else {
+        // If no report data found, create an empty report with headers only
+        csv.push_str("No campaign data found\n");
+    }
+
+    if opts.compact {
+        return Ok(compact_csv(&csv, opts.csv_delimiter));
+    }
+
+    Ok(csv)
+}
+
+// Returns the CSV as a string so the UI can show a preview table before the
+// user commits to writing a file
+#[tauri::command]
+fn preview_csv(app: tauri::AppHandle, reportData: serde_json::Value, top_n: Option<usize>, compact: Option<bool>, group_by: Option<String>, ctr_alert_threshold: Option<f64>) -> Result<String, String> {
+    let report_data = reportData.get("data")
+        .ok_or_else(|| "Invalid report format: missing data field".to_string())?;
+
+    let metrics = report_data.get("metrics")
+        .ok_or_else(|| "Invalid report format: missing metrics".to_string())?;
+
+    // The preview must match what the exported file will look like,
+    // including the cost columns for rate-carded advertisers
+    let settings = load_settings(app)?;
+    let advertiser = reportData.get("advertiser")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let opts = CsvOptions {
+        top_n,
+        thousands_separator: settings.thousands_separator,
+        custom_metrics: settings.custom_metrics.clone(),
+        compact: compact.unwrap_or(false),
+        group_by,
+        ctr_alert_threshold,
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        cost_per_click: rate_for_advertiser(&settings.rate_card, advertiser),
+        rounding_mode: settings.rounding_mode.clone(),
+    };
+    build_csv(report_data, metrics, &opts).map_err(String::from)
+}
+
+// Re-pulls each campaign stored in a report and returns the metric fields
+// whose live values no longer match what we saved, for settling disputes
+#[tauri::command]
+async fn reconcile_report(app: tauri::AppHandle, report_id: String) -> Result<Vec<Discrepancy>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let stored_campaigns = report.data.get("campaigns")
+        .and_then(|c| c.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    if stored_campaigns.is_empty() {
+        return Err("Report has no stored campaigns to reconcile".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+
+    let total = stored_campaigns.len();
+    let mut discrepancies = Vec::new();
+
+    for (index, stored) in stored_campaigns.iter().enumerate() {
+        emit_bulk_progress(&app, "Reconciling report", index, total);
+
+        let campaign_id = match stored.get("id").and_then(|v| v.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let campaign_url = format!("{}/campaigns/{}", base_url, campaign_id);
+        let current = client
+            .get(&campaign_url)
+            .header("Authorization", auth.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch campaign {}: {}", campaign_id, e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Failed to parse campaign {}: {}", campaign_id, e))?;
+
+        discrepancies.extend(campaign_discrepancies(stored, &current));
+    }
+
+    emit_bulk_progress(&app, "Reconciling report", total, total);
+
+    Ok(discrepancies)
+}
+
+// Two-proportion z-test on clicks-per-unique-open between two periods.
+// The statistics are deliberately simple: every unique open is treated as
+// an independent trial and every matched click as a success, which
+// overstates certainty when the same subscribers open in both periods.
+// Returns 0.0 when either denominator is zero or the pooled variance
+// collapses (all successes or all failures).
+fn two_proportion_z(clicks_a: u64, opens_a: u64, clicks_b: u64, opens_b: u64) -> f64 {
+    if opens_a == 0 || opens_b == 0 {
+        return 0.0;
+    }
+
+    let na = opens_a as f64;
+    let nb = opens_b as f64;
+    let pa = clicks_a as f64 / na;
+    let pb = clicks_b as f64 / nb;
+    let pooled = (clicks_a + clicks_b) as f64 / (na + nb);
+
+    let variance = pooled * (1.0 - pooled) * (1.0 / na + 1.0 / nb);
+    if variance <= 0.0 {
+        return 0.0;
+    }
+
+    (pa - pb) / variance.sqrt()
+}
+
+// Answers "is this CTR change real or noise?" by running the matching
+// pipeline over two periods and z-testing the difference. |z| >= 1.96
+// (roughly 95% two-tailed) is flagged significant.
+#[tauri::command]
+async fn compare_periods(
+    app: tauri::AppHandle,
+    advertiser: String,
+    tracking_urls: Vec<String>,
+    newsletter_type: String,
+    range_a: DateRange,
+    range_b: DateRange,
+) -> Result<PeriodComparison, String> {
+    let tracking_urls = validate_tracking_urls(&tracking_urls)?;
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+    let path_match = default_path_match();
+
+    let mut stats: Vec<PeriodStats> = Vec::new();
+    for range in [&range_a, &range_b] {
+        let (start_iso, end_iso) = date_range_bounds(range)?;
+        let campaigns_url = build_campaigns_url(&base_url, &start_iso, &end_iso, None);
+
+        let campaigns_data = client
+            .get(&campaigns_url)
+            .header("Authorization", auth.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+        let campaigns = match campaigns_data.get("campaigns") {
+            Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+            _ => return Err("No campaigns found in response".to_string()),
+        };
+
+        let filtered_campaigns = filter_campaigns_by_type(campaigns, &newsletter_type);
+
+        let mut total_clicks: u64 = 0;
+        let mut unique_opens: u64 = 0;
+        for campaign in &filtered_campaigns {
+            let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            unique_opens += campaign.get("report_summary")
+                .and_then(|rs| rs.get("unique_opens"))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+
+            let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+            let click_response = client
+                .get(&click_url)
+                .header("Authorization", auth.clone())
+                .send()
+                .await;
+
+            if let Ok(response) = click_response {
+                if response.status().is_success() {
+                    if let Ok(click_data) = response.json::<serde_json::Value>().await {
+                        total_clicks += count_matched_clicks(&click_data, &tracking_urls, &path_match);
+                    }
+                }
+            }
+        }
+
+        let ctr = if unique_opens > 0 {
+            (total_clicks as f64 / unique_opens as f64) * 100.0
+        } else {
+            0.0
+        };
+        stats.push(PeriodStats { total_clicks, unique_opens, ctr });
+    }
+
+    let period_b = stats.pop().unwrap();
+    let period_a = stats.pop().unwrap();
+    let z_score = two_proportion_z(
+        period_a.total_clicks, period_a.unique_opens,
+        period_b.total_clicks, period_b.unique_opens,
+    );
+
+    println!(
+        "Period comparison for {}: CTR {:.4} vs {:.4}, z = {:.3}",
+        advertiser, period_a.ctr, period_b.ctr, z_score
+    );
+
+    Ok(PeriodComparison {
+        period_a,
+        period_b,
+        z_score,
+        significant: z_score.abs() >= 1.96,
+    })
+}
+
+// Week-over-week engagement read for an advertiser: runs the matching
+// pipeline over the last N weeks and buckets the results by ISO week. The
+// campaign list is fetched once for the whole window.
+#[tauri::command]
+async fn ctr_trend(app: tauri::AppHandle, advertiser: String, tracking_urls: Vec<String>, newsletter_type: String, weeks: u32) -> Result<Vec<WeeklyCtr>, String> {
+    let tracking_urls = validate_tracking_urls(&tracking_urls)?;
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::weeks(weeks.max(1) as i64);
+
+    // One campaigns fetch covers the whole window
+    let campaigns_url = build_campaigns_url(&base_url, &start.to_rfc3339(), &end.to_rfc3339(), None);
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", auth.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = campaigns_data.get("campaigns")
+        .and_then(|c| c.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let filtered = filter_campaigns_by_type(&campaigns, &newsletter_type);
+
+    println!("CTR trend for {}: {} matching campaigns over {} weeks", advertiser, filtered.len(), weeks);
+
+    let mut points = Vec::new();
+    for campaign in &filtered {
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+        let send_date = match campaign.get("send_time")
+            .and_then(|st| st.as_str())
+            .and_then(|st| chrono::DateTime::parse_from_rfc3339(st).ok())
+        {
+            Some(dt) => dt.date_naive(),
+            None => continue,
+        };
+        let unique_opens = campaign.get("report_summary")
+            .and_then(|r| r.get("unique_opens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        let mut clicks = 0;
+        if let Ok(response) = client.get(&click_url).header("Authorization", auth.clone()).send().await {
+            if response.status().is_success() {
+                if let Ok(click_data) = response.json::<serde_json::Value>().await {
+                    clicks = count_matched_clicks(&click_data, &tracking_urls, default_path_match().as_str());
+                }
+            }
+        }
+
+        points.push((send_date, clicks, unique_opens));
+    }
+
+    Ok(bucket_weekly_ctr(&points, start.date_naive(), end.date_naive()))
+}
+
+// Resolves a URL to its Mailchimp link id, then pages through the members
+// who clicked it. Returns email addresses, so see the PII note on
+// MemberClick before surfacing this anywhere.
+#[tauri::command]
+async fn url_click_members(app: tauri::AppHandle, campaign_id: String, url: String) -> Result<Vec<MemberClick>, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+
+    // Find the link id whose URL matches the one we were asked about
+    let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+    let click_data = client
+        .get(&click_url)
+        .header("Authorization", auth.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch click details: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse click details: {}", e))?;
+
+    let normalized = normalize_link(&url);
+    let link_id = click_data.get("urls_clicked")
+        .and_then(|u| u.as_array())
+        .and_then(|urls| urls.iter().find(|item| {
+            item.get("url")
+                .and_then(|u| u.as_str())
+                .map(|u| normalize_link(u) == normalized)
+                .unwrap_or(false)
+        }))
+        .and_then(|item| item.get("id").and_then(|id| id.as_str()))
+        .map(|id| id.to_string())
+        .ok_or_else(|| format!("Not found: URL {} was not clicked in campaign {}", url, campaign_id))?;
+
+    // Page through the members list; Mailchimp caps count at 1000 per page
+    let mut members = Vec::new();
+    let mut offset = 0;
+    loop {
+        let members_url = format!(
+            "{}/reports/{}/click-details/{}/members?count=1000&offset={}",
+            base_url, campaign_id, link_id, offset
+        );
+        let page = client
+            .get(&members_url)
+            .header("Authorization", auth.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch click members: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Failed to parse click members: {}", e))?;
+
+        let page_members = page.get("members").and_then(|m| m.as_array()).cloned().unwrap_or_default();
+        let page_len = page_members.len();
+
+        for member in page_members {
+            members.push(MemberClick {
+                email_address: member.get("email_address").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                clicks: member.get("clicks").and_then(|v| v.as_u64()).unwrap_or(0),
+            });
+        }
+
+        let total_items = page.get("total_items").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        offset += page_len;
+        if page_len == 0 || offset >= total_items {
+            break;
+        }
+
+        // Be polite to the API between pages
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    }
+
+    Ok(members)
+}
+
+// One CSV row per clicked URL across the requested campaigns, the audit
+// shape export_click_details writes: campaign id, url, total and unique
+// click counts, straight from Mailchimp with no matching applied
+fn click_details_csv(details: &[(String, serde_json::Value)]) -> String {
+    let mut csv = String::from("Campaign ID,URL,Total Clicks,Unique Clicks\n");
+    for (campaign_id, url_item) in details {
+        let url = url_item.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        let total_clicks = url_item.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+        let unique_clicks = url_item.get("unique_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_escape(campaign_id, ','),
+            csv_escape(url, ','),
+            total_clicks,
+            unique_clicks
+        ));
+    }
+    csv
+}
+
+// Projects the API traffic of a report run from a matched campaign count:
+// one campaigns listing, one click-details call per campaign (a single page
+// covers up to 1000 distinct URLs, which campaigns never approach), and one
+// list-activity call when the report asks for it
+fn estimate_for_campaigns(matched_campaigns: usize, include_list_activity: bool) -> ApiCostEstimate {
+    let campaign_list_calls = 1;
+    let click_detail_calls = matched_campaigns;
+    let estimated_pages = matched_campaigns;
+    let activity_calls = if include_list_activity { 1 } else { 0 };
+
+    ApiCostEstimate {
+        matched_campaigns,
+        campaign_list_calls,
+        click_detail_calls,
+        estimated_pages,
+        projected_total: campaign_list_calls + click_detail_calls + activity_calls,
+    }
+}
+
+// Planning tool: answers "how many API calls will this report cost?" before
+// anything heavy runs. Only the cheap campaigns listing is fetched; the
+// click-details traffic is projected from the matched campaign count.
+#[tauri::command]
+async fn estimate_api_calls(app: tauri::AppHandle, request: ReportRequest) -> Result<ApiCostEstimate, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&request.date_range)?;
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, request.folder_id.as_deref());
+
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let filtered_campaigns = filter_campaigns_by_type(campaigns, &request.newsletter_type);
+    let estimate = estimate_for_campaigns(filtered_campaigns.len(), request.include_list_activity);
+
+    println!(
+        "Estimated {} API calls for {} matched campaigns",
+        estimate.projected_total, estimate.matched_campaigns
+    );
+    Ok(estimate)
+}
+
+// Warms the campaign cache for a window so a later generate_report over the
+// same dates skips the slow campaign list fetch. Meant for scheduled
+// off-peak runs ahead of interactive reporting.
+#[tauri::command]
+async fn prefetch_campaigns(app: tauri::AppHandle, date_range: DateRange) -> Result<usize, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_iso, end_iso) = date_range_bounds(&date_range)?;
+    let campaigns_url = build_campaigns_url(&base_url, &start_iso, &end_iso, None);
+
+    emit_bulk_progress(&app, "Prefetching campaigns", 0, 1);
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap().clone(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let path = campaign_cache_path(&app_dir, &settings.mailchimp_audience_id, &start_iso, &end_iso, None);
+    write_campaign_cache(&path, &campaigns)?;
+    emit_bulk_progress(&app, "Prefetching campaigns", 1, 1);
+
+    println!("Prefetched {} campaigns into the cache", campaigns.len());
+    Ok(campaigns.len())
+}
+
+// Two-phase preview-then-confirm: a preview pins its matched campaigns
+// behind a short-lived token, and the confirmed run redeems it instead of
+// re-fetching and re-filtering
+const PREVIEW_TOKEN_TTL_SECS: i64 = 15 * 60;
+
+#[derive(Debug, Clone)]
+struct PreviewToken {
+    token: String,
+    campaigns: Vec<serde_json::Value>,
+    issued_at: chrono::DateTime<chrono::Utc>,
+}
+
+static PREVIEW_TOKENS: Mutex<Vec<PreviewToken>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Serialize)]
+struct PreviewResponse {
+    token: String,
+    expires_in_secs: i64,
+    // id / title / send_time per matched campaign, for the confirm dialog
+    campaigns: Vec<serde_json::Value>,
+}
+
+fn issue_preview_token(
+    tokens: &mut Vec<PreviewToken>,
+    advertiser: &str,
+    campaigns: Vec<serde_json::Value>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    // Sweep expired entries while we're here so the list stays small
+    tokens.retain(|t| now.signed_duration_since(t.issued_at).num_seconds() <= PREVIEW_TOKEN_TTL_SECS);
+
+    use sha2::Digest;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(advertiser.as_bytes());
+    hasher.update(now.timestamp_millis().to_string().as_bytes());
+    for campaign in &campaigns {
+        if let Some(id) = campaign.get("id").and_then(|v| v.as_str()) {
+            hasher.update(id.as_bytes());
+        }
+    }
+    let token = format!("pv_{}", &to_hex(&hasher.finalize())[..16]);
+
+    tokens.push(PreviewToken {
+        token: token.clone(),
+        campaigns,
+        issued_at: now,
+    });
+    token
+}
+
+// Validates and consumes a token. Single-use by design: a confirmed run
+// removes it, so a stale confirm can't silently reuse old matches.
+fn redeem_preview_token(
+    tokens: &mut Vec<PreviewToken>,
+    token: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let index = tokens.iter().position(|t| t.token == token)
+        .ok_or_else(|| format!("Unknown or already used preview token: {}", token))?;
+    let entry = tokens.remove(index);
+    if now.signed_duration_since(entry.issued_at).num_seconds() > PREVIEW_TOKEN_TTL_SECS {
+        return Err("Preview token has expired; run the preview again".to_string());
+    }
+    Ok(entry.campaigns)
+}
+
+// Phase one of preview-then-confirm: fetch and filter exactly as a run
+// would, then hand back a token generate_report can redeem to analyze
+// these exact campaigns without another fetch
+#[tauri::command]
+async fn preview_campaigns(app: tauri::AppHandle, request: ReportRequest) -> Result<PreviewResponse, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let (start_iso, end_iso) = date_range_bounds(&request.date_range)?;
+
+    // Same windowed, cached fetch the real run uses
+    let sub_windows = split_fetch_window(&start_iso, &end_iso, settings.campaign_fetch_span_days.max(1) as i64);
+    let mut pages = Vec::new();
+    for (index, (sub_start, sub_end)) in sub_windows.iter().enumerate() {
+        emit_bulk_progress(&app, "Previewing campaigns", index, sub_windows.len());
+        pages.push(fetch_campaign_window(&client, &app, &settings, sub_start, sub_end, request.folder_id.as_deref()).await?);
+    }
+    let campaigns = merge_campaign_pages(pages);
+    let matched = select_campaigns(&campaigns, &request)?;
+    emit_bulk_progress(&app, "Previewing campaigns", sub_windows.len(), sub_windows.len());
+
+    let summaries: Vec<serde_json::Value> = matched.iter().map(|c| {
+        serde_json::json!({
+            "id": c.get("id").and_then(|v| v.as_str()).unwrap_or(""),
+            "title": c.get("settings").and_then(|s| s.get("title")).and_then(|v| v.as_str()).unwrap_or(""),
+            "send_time": c.get("send_time").and_then(|v| v.as_str()).unwrap_or(""),
+        })
+    }).collect();
+
+    let token = {
+        let mut tokens = PREVIEW_TOKENS.lock()
+            .map_err(|e| format!("Preview tokens lock poisoned: {}", e))?;
+        issue_preview_token(&mut tokens, &request.advertiser, matched, chrono::Utc::now())
+    };
+
+    println!("Previewed {} matching campaigns", summaries.len());
+    Ok(PreviewResponse {
+        token,
+        expires_in_secs: PREVIEW_TOKEN_TTL_SECS,
+        campaigns: summaries,
+    })
+}
+
+// Fetches the window's campaigns and explains, per campaign, why the type
+// filter would include or exclude it - the fastest way to chase down an
+// unexpected match
+#[tauri::command]
+async fn explain_matches(app: tauri::AppHandle, request: ReportRequest) -> Result<Vec<MatchExplanation>, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&request.date_range)?;
+    // Deliberately unscoped so campaigns outside a requested folder still
+    // show up, with the folder named as the reason they're excluded
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, None);
+
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let explanations = explain_campaign_matches(campaigns, &request.newsletter_type, request.folder_id.as_deref());
+    let included = explanations.iter().filter(|e| e.included).count();
+    println!("Explained {} campaigns ({} included)", explanations.len(), included);
+
+    Ok(explanations)
+}
+
+// Lists the distinct Mailchimp tags on campaigns in the window, so users
+// picking tag filter mode can see what's available to select on
+#[tauri::command]
+async fn list_campaign_tags(app: tauri::AppHandle, date_range: DateRange) -> Result<Vec<String>, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&date_range)?;
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, None);
+
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let mut tags: Vec<String> = Vec::new();
+    for campaign in campaigns {
+        for tag in campaign_tag_names(campaign) {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                tags.push(tag);
+            }
+        }
+    }
+    tags.sort_by_key(|t| t.to_lowercase());
+
+    println!("Found {} distinct campaign tags in window", tags.len());
+    Ok(tags)
+}
+
+// Audit export for ad-ops teams: dumps each campaign's unfiltered
+// urls_clicked data to a CSV so our matching can be checked against the
+// raw numbers. Unlike the report pipeline, nothing is filtered or matched.
+#[tauri::command]
+async fn export_click_details(app: tauri::AppHandle, campaign_ids: Vec<String>, out_path: String) -> Result<String, String> {
+    if campaign_ids.is_empty() {
+        return Err("No campaign ids provided".to_string());
+    }
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+
+    let mut details: Vec<(String, serde_json::Value)> = Vec::new();
+
+    for (index, campaign_id) in campaign_ids.iter().enumerate() {
+        emit_bulk_progress(&app, "export_click_details", index, campaign_ids.len());
+
+        // Page through the click details; Mailchimp caps count at 1000 per page
+        let mut offset = 0;
+        loop {
+            let click_url = format!(
+                "{}/reports/{}/click-details?count=1000&offset={}",
+                base_url, campaign_id, offset
+            );
+            let page = client
+                .get(&click_url)
+                .header("Authorization", auth.clone())
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch click details: {}", e))?
+                .json::<serde_json::Value>()
+                .await
+                .map_err(|e| format!("Failed to parse click details: {}", e))?;
+
+            let urls_clicked = page.get("urls_clicked").and_then(|u| u.as_array()).cloned().unwrap_or_default();
+            let page_len = urls_clicked.len();
+
+            for url_item in urls_clicked {
+                details.push((campaign_id.clone(), url_item));
+            }
+
+            let total_items = page.get("total_items").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            offset += page_len;
+            if page_len == 0 || offset >= total_items {
+                break;
+            }
+
+            // Be polite to the API between pages
+            tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        }
+    }
+
+    let csv = click_details_csv(&details);
+
+    std::fs::write(&out_path, csv.as_bytes())
+        .map_err(|e| format!("Failed to write click details CSV: {}", e))?;
+
+    println!("Wrote {} click-detail rows to {}", details.len(), out_path);
+    Ok(out_path)
+}
+
+// One row of the URL-pivoted export: a tracking URL with its matched
+// clicks summed across campaigns and the campaigns it appeared in
+#[derive(Debug, Serialize)]
+struct UrlRow {
+    url: String,
+    total_clicks: u64,
+    campaigns: Vec<String>,
+}
+
+// Pivots per-campaign click details the other way around: one row per
+// tracking URL instead of one row per campaign. Each (label, click_data)
+// pair is a campaign; matching follows the same path_match rules as the
+// report pipeline, so the pivoted totals reconcile with the campaign rows.
+// Zero-click URLs stay as zero rows unless include_zero is off.
+fn pivot_clicks_by_url(
+    details: &[(String, serde_json::Value)],
+    tracking_urls: &[String],
+    path_match: &str,
+    include_zero: bool,
+) -> Vec<UrlRow> {
+    let mut rows: Vec<UrlRow> = tracking_urls.iter()
+        .map(|u| UrlRow { url: u.clone(), total_clicks: 0, campaigns: Vec::new() })
+        .collect();
+
+    for (label, click_data) in details {
+        if let Some(urls_clicked) = click_data.get("urls_clicked").and_then(|u| u.as_array()) {
+            for url_item in urls_clicked {
+                let url = url_item.get("url").and_then(|u| u.as_str()).unwrap_or("");
+                let clicks = url_item.get("total_clicks").and_then(|c| c.as_u64()).unwrap_or(0);
+
+                for row in rows.iter_mut() {
+                    if url_matches_tracking(url, &row.url, path_match) {
+                        row.total_clicks += clicks;
+                        if clicks > 0 && !row.campaigns.contains(label) {
+                            row.campaigns.push(label.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if !include_zero {
+        rows.retain(|row| row.total_clicks > 0);
+    }
+
+    rows
+}
+
+// Renders the URL pivot as CSV with a totals row, honoring the configured
+// delimiter and thousands separator like every other export
+fn url_pivot_csv(rows: &[UrlRow], opts: &CsvOptions) -> String {
+    let delimiter = opts.csv_delimiter.to_string();
+    let mut csv = String::new();
+    csv.push_str(&["Tracking URL", "Total Clicks", "Campaigns"].join(&delimiter));
+    csv.push('\n');
+
+    let mut total: u64 = 0;
+    for row in rows {
+        total += row.total_clicks;
+        let fields = [
+            csv_escape(&row.url, opts.csv_delimiter),
+            csv_escape(&format_count(row.total_clicks, opts.thousands_separator), opts.csv_delimiter),
+            csv_escape(&row.campaigns.join("; "), opts.csv_delimiter),
+        ];
+        csv.push_str(&fields.join(&delimiter));
+        csv.push('\n');
+    }
+
+    let totals = [
+        "Totals".to_string(),
+        csv_escape(&format_count(total, opts.thousands_separator), opts.csv_delimiter),
+        String::new(),
+    ];
+    csv.push_str(&totals.join(&delimiter));
+    csv.push('\n');
+
+    csv
+}
+
+// Exports the per-URL pivot for a report window: fetches the same campaigns
+// and click details as generate_report, then writes one row per tracking
+// URL instead of per campaign. Returns the written path.
+#[tauri::command]
+async fn download_url_pivot_csv(app: tauri::AppHandle, mut request: ReportRequest, include_zero: Option<bool>) -> Result<String, String> {
+    request.tracking_urls = validate_tracking_urls(&request.tracking_urls)?;
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&request.date_range)?;
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, request.folder_id.as_deref());
+
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let filtered_campaigns = select_campaigns(campaigns, &request)?;
+
+    let mut details: Vec<(String, serde_json::Value)> = Vec::new();
+    for (index, campaign) in filtered_campaigns.iter().enumerate() {
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let send_date = campaign.get("send_time")
+            .and_then(|st| st.as_str())
+            .and_then(|st| chrono::DateTime::parse_from_rfc3339(st).ok())
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        emit_bulk_progress(&app, "Building URL pivot", index + 1, filtered_campaigns.len());
+
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        let click_data = client
+            .get(&click_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch click details: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Failed to parse click details: {}", e))?;
+
+        details.push((send_date, click_data));
+    }
+
+    let rows = pivot_clicks_by_url(&details, &request.tracking_urls, &request.path_match, include_zero.unwrap_or(true));
+
+    let opts = CsvOptions {
+        thousands_separator: settings.thousands_separator,
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        ..Default::default()
+    };
+    let csv = url_pivot_csv(&rows, &opts);
+
+    let download_dir = resolve_export_dir(&settings, &request.advertiser)?;
+    let clean_advertiser = request.advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+    let file_name = format!("{}_{}_url_pivot_{}.csv",
+        clean_advertiser,
+        request.newsletter_type,
+        format_timestamp_now(&settings.timestamp_timezone, "%Y%m%d_%H%M%S")
+    );
+    let file_path = download_dir.join(&file_name);
+
+    std::fs::write(&file_path, csv.as_bytes())
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    if settings.emit_checksum {
+        let hash = write_checksum_sidecar(&file_path)?;
+        println!("Export checksum: {}", hash);
+    }
+
+    println!("Wrote URL pivot with {} rows to {:?}", rows.len(), file_path);
+    file_path.to_str()
+        .ok_or_else(|| "Failed to get file path".to_string())
+        .map(|s| s.to_string())
+}
+
+// Lighter sibling of generate_report: returns a per-campaign click table for
+// quick questions without saving a report or writing any files
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct UrlAuditEntry {
+    url: String,
+    recent_clicks: u64,
+    campaigns_matched: usize,
+    possibly_stale: bool,
+}
+
+// Checks each stored default URL against recent click data. A URL that no
+// recent campaign clicks matched has probably gone stale - the landing page
+// moved and reports built from it will quietly come back empty.
+fn audit_urls_against_clicks(urls: &[String], details: &[(String, serde_json::Value)], path_match: &str) -> Vec<UrlAuditEntry> {
+    pivot_clicks_by_url(details, urls, path_match, true)
+        .into_iter()
+        .map(|row| UrlAuditEntry {
+            url: row.url,
+            recent_clicks: row.total_clicks,
+            campaigns_matched: row.campaigns.len(),
+            possibly_stale: row.total_clicks == 0,
+        })
+        .collect()
+}
+
+// Maintenance tool for the per-advertiser URL map: reconciles the stored
+// default tracking URLs against clicks from the last since_days of campaigns
+#[tauri::command]
+async fn audit_advertiser_urls(app: tauri::AppHandle, advertiser: String, since_days: u32) -> Result<Vec<UrlAuditEntry>, String> {
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let urls = match settings.advertiser_urls.get(&advertiser) {
+        Some(urls) if !urls.is_empty() => validate_tracking_urls(urls)?,
+        _ => return Err(format!("No default tracking URLs configured for {}", advertiser)),
+    };
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+    let auth = format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key)));
+
+    let end = chrono::Utc::now();
+    let start = end - chrono::Duration::days(since_days.max(1) as i64);
+
+    // All campaigns in the window, regardless of newsletter type; a stale
+    // URL is stale no matter which send the clicks came from
+    let campaigns_url = build_campaigns_url(&base_url, &start.to_rfc3339(), &end.to_rfc3339(), None);
+    let campaigns_data = client
+        .get(&campaigns_url)
+        .header("Authorization", auth.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = campaigns_data.get("campaigns")
+        .and_then(|c| c.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut details: Vec<(String, serde_json::Value)> = Vec::new();
+    for (index, campaign) in campaigns.iter().enumerate() {
+        emit_bulk_progress(&app, "Auditing advertiser URLs", index, campaigns.len());
+
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+        let title = campaign.get("settings")
+            .and_then(|s| s.get("title"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("Untitled")
+            .to_string();
+
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        if let Ok(response) = client.get(&click_url).header("Authorization", auth.clone()).send().await {
+            if response.status().is_success() {
+                if let Ok(click_data) = response.json::<serde_json::Value>().await {
+                    details.push((title, click_data));
+                }
+            }
+        }
+    }
+    emit_bulk_progress(&app, "Auditing advertiser URLs", campaigns.len(), campaigns.len());
+
+    let entries = audit_urls_against_clicks(&urls, &details, &default_path_match());
+    let stale = entries.iter().filter(|e| e.possibly_stale).count();
+    println!("URL audit for {}: {} of {} stored URLs look stale", advertiser, stale, entries.len());
+    Ok(entries)
+}
+
+#[tauri::command]
+async fn campaign_click_breakdown(app: tauri::AppHandle, mut request: ReportRequest) -> Result<Vec<CampaignClicks>, String> {
+    request.tracking_urls = validate_tracking_urls(&request.tracking_urls)?;
+
+    let settings = load_settings(app.clone())?;
+
+    if settings.mailchimp_api_key.is_empty() || settings.mailchimp_audience_id.is_empty() {
+        return Err("Mailchimp API settings not configured".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let base_url = mailchimp_base_url(&settings);
+
+    let (start_date_iso, end_date_iso) = date_range_bounds(&request.date_range)?;
+
+    let campaigns_url = build_campaigns_url(&base_url, &start_date_iso, &end_date_iso, request.folder_id.as_deref());
+
+    let fetching_update = ProgressUpdate {
+        stage: "FetchingCampaigns".to_string(),
+        progress: 10,
+        message: "Fetching campaign data from Mailchimp...".to_string(),
+        time_remaining: None,
+        elapsed_secs: None,
+    };
+    if let Err(e) = app.emit("report-progress", fetching_update) {
+        println!("Failed to emit progress update: {}", e);
+    }
+
+    let campaigns_response = client
+        .get(&campaigns_url)
+        .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch campaigns: {}", e))?;
+
+    if !campaigns_response.status().is_success() {
+        let error_text = campaigns_response.text().await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Mailchimp API error: {}", error_text));
+    }
+
+    let campaigns_data = campaigns_response.json::<serde_json::Value>().await
+        .map_err(|e| format!("Failed to parse campaigns response: {}", e))?;
+
+    let campaigns = match campaigns_data.get("campaigns") {
+        Some(campaigns_array) if campaigns_array.is_array() => campaigns_array.as_array().unwrap(),
+        _ => return Err("No campaigns found in response".to_string()),
+    };
+
+    let filtered_campaigns = filter_campaigns_by_type(campaigns, &request.newsletter_type);
+
+    let mut breakdown = Vec::new();
+
+    for (index, campaign) in filtered_campaigns.iter().enumerate() {
+        let campaign_id = match campaign.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let title = campaign.get("settings")
+            .and_then(|s| s.get("title"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("Untitled")
+            .to_string();
+
+        let send_date = campaign.get("send_time")
+            .and_then(|st| st.as_str())
+            .and_then(|st| chrono::DateTime::parse_from_rfc3339(st).ok())
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        // Progress across the click-detail fetches (10-100%)
+        let progress = 10 + ((index as f64 / filtered_campaigns.len().max(1) as f64) * 90.0) as u8;
+        let campaign_update = ProgressUpdate {
+            stage: "ProcessingCampaigns".to_string(),
+            progress,
+            message: format!("Checking clicks for campaign {} of {}: {}", index + 1, filtered_campaigns.len(), title),
+            time_remaining: None,
+            elapsed_secs: None,
+        };
+        if let Err(e) = app.emit("report-progress", campaign_update) {
+            println!("Failed to emit progress update: {}", e);
+        }
+
+        let mut total_clicks: u64 = 0;
+        let click_url = format!("{}/reports/{}/click-details?count=1000", base_url, campaign_id);
+        let click_response = client
+            .get(&click_url)
+            .header("Authorization", format!("Basic {}", STANDARD.encode(format!("anystring:{}", settings.mailchimp_api_key))))
+            .send()
+            .await;
+
+        if let Ok(response) = click_response {
+            if response.status().is_success() {
+                if let Ok(click_data) = response.json::<serde_json::Value>().await {
+                    total_clicks = count_matched_clicks(&click_data, &request.tracking_urls, &request.path_match);
+                }
+            }
+        }
+
+        breakdown.push(CampaignClicks {
+            id: campaign_id.to_string(),
+            title,
+            send_date,
+            total_clicks,
+        });
+    }
+
+    // Sort by send date so the table reads chronologically
+    breakdown.sort_by(|a, b| a.send_date.cmp(&b.send_date));
+
+    Ok(breakdown)
+}
+
+#[tauri::command]
+fn open_report_in_excel(app: tauri::AppHandle, _window: tauri::Window, reportData: serde_json::Value) -> Result<String, String> {
+    // Load settings for the configured timestamp timezone
+    let settings = load_settings(app.clone())?;
+
+    // Extract report data for CSV content
+    let report_data = reportData.get("data")
+        .ok_or_else(|| "Invalid report format: missing data field".to_string())?;
+    
+    // Get selected metrics from the report data
+    let metrics = report_data.get("metrics")
+        .ok_or_else(|| "Invalid report format: missing metrics".to_string())?;
+
+    // Extract report metadata for filename
+    let advertiser = reportData.get("advertiser")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown-advertiser");
+    
+    let newsletter_type = reportData.get("report_type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown-type");
+    
+    // Extract date range for filename
+    let date_range = if let Some(range) = reportData.get("date_range") {
+        let start = range.get("start_date")
+            .and_then(|d| d.as_str())
+            .unwrap_or("");
+        
+        let end = range.get("end_date")
+            .and_then(|d| d.as_str())
+            .unwrap_or("");
+            
+        if !start.is_empty() && !end.is_empty() {
+            format!("{}_{}", start, end)
+        } else {
+            "unknown-dates".to_string()
+        }
+    } else {
+        "unknown-dates".to_string()
+    };
+    
+    // Create a timestamp for uniqueness if needed
+    let timestamp = format_timestamp_now(&settings.timestamp_timezone, "%Y%m%d_%H%M%S");
+    
+    // Create a clean advertiser name (remove special chars)
+    let clean_advertiser = advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+    
+    // Get the system temp directory
+    let temp_dir = std::env::temp_dir();
+    
+    // Format the filename: Advertiser_NewsletterType_DateRange.csv
+    let file_name = format!("{}_{}_{}_{}.csv", 
+        clean_advertiser,
+        newsletter_type,
+        date_range,
+        timestamp
+    );
+    
+    let file_path = temp_dir.join(&file_name);
+    
+    // Build the CSV content through the shared builder
+    let opts = CsvOptions {
+        thousands_separator: settings.thousands_separator,
+        custom_metrics: settings.custom_metrics.clone(),
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        ..Default::default()
+    };
+    let csv = build_csv(report_data, metrics, &opts)?;
+    
+    // Write the CSV content to the file
+    std::fs::write(&file_path, csv.as_bytes())
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    // Compliance sidecar so delivered files can be verified later
+    if settings.emit_checksum {
+        let hash = write_checksum_sidecar(&file_path)?;
+        println!("Export checksum: {}", hash);
+    }
+    
+    // Return the file path as a string
+    file_path.to_str()
+        .ok_or_else(|| "Failed to get file path".to_string())
+        .map(|s| s.to_string())
+}
+
+#[tauri::command]
+fn write_report_file(path: String, report: serde_json::Value) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+    let mut file = File::create(&path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    file.write_all(json.as_bytes())
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(())
+}
+
+// Core of delete_report, split out so the shared-directory refusal can be
+// exercised against plain directories
+fn delete_report_from_dirs(app_dir: &Path, shared_dir: Option<&Path>, report_id: &str, trash_size: usize) -> Result<(), String> {
+    let _guard = REPORTS_LOCK.lock()
+        .map_err(|e| format!("Reports lock poisoned: {}", e))?;
+    let _file_lock = lock_reports_file(app_dir)?;
+
+    let mut reports = load_reports_from_dir(app_dir)?;
+
+    // Remove the report with matching ID, keeping it restorable from trash
+    if let Some(index) = reports.iter().position(|r| r.id == report_id) {
+        let removed = reports.remove(index);
+        push_to_trash(app_dir, removed, trash_size)?;
+        return write_reports_to_dir(app_dir, &reports);
+    }
+
+    // Not stored locally. If the id lives in the shared read-only folder,
+    // say so instead of pretending the report doesn't exist.
+    if let Some(dir) = shared_dir {
+        let shared = load_reports_from_dir(dir)?;
+        if shared.iter().any(|r| r.id == report_id) {
+            return Err(format!(
+                "Report {} comes from the shared reports directory and is read-only on this machine",
+                report_id
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_report(app: tauri::AppHandle, report_id: String) -> Result<(), String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let settings = load_settings(app.clone())?;
+    let shared_dir = settings.shared_reports_dir
+        .as_deref()
+        .filter(|d| !d.is_empty())
+        .map(Path::new);
+
+    delete_report_from_dirs(&app_dir, shared_dir, &report_id, settings.trash_size)
+}
+
+// Hex SHA-256 of a file's contents
+fn file_sha256(path: &Path) -> Result<String, String> {
+    use sha2::Digest;
+
+    let contents = fs::read(path)
+        .map_err(|e| format!("Failed to read file for hashing: {}", e))?;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(&contents);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Writes the "<hash>  <filename>" sidecar next to an exported file (the
+// format sha256sum expects) and returns the hash
+fn write_checksum_sidecar(path: &Path) -> Result<String, String> {
+    let hash = file_sha256(path)?;
+    let file_name = path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let sidecar = path.with_extension(
+        format!("{}.sha256", path.extension().map(|e| e.to_string_lossy()).unwrap_or_default())
+    );
+    fs::write(&sidecar, format!("{}  {}\n", hash, file_name))
+        .map_err(|e| format!("Failed to write checksum file: {}", e))?;
+
+    Ok(hash)
+}
+
+// Returns the SHA-256 of an exported file so the UI can display it
+#[tauri::command]
+fn export_checksum(path: String) -> Result<String, String> {
+    file_sha256(Path::new(&path))
+}
+
+// HMAC-SHA256 building block for SigV4 request signing
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    use hmac::Mac;
+
+    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key)
+        .expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// Hex SHA-256 of an in-memory payload (file_sha256 covers the on-disk case)
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest;
+
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Computes the AWS Signature Version 4 Authorization header for a PUT of a
+// payload with hash `payload_hash` to `host``path`. Only the three headers
+// we actually send are signed. Works against AWS S3 and anything speaking
+// the same protocol (MinIO, Wasabi, ...).
+fn sigv4_authorization(
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+    host: &str,
+    path: &str,
+    amz_date: &str,
+    payload_hash: &str,
+) -> String {
+    let short_date = &amz_date[..8];
+
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+        path, host, payload_hash, amz_date, payload_hash
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", short_date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date, scope, sha256_hex(canonical_request.as_bytes())
+    );
+
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), short_date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, b"s3");
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = to_hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+        access_key, scope, signature
+    )
+}
+
+// Renders a saved report and PUTs it straight into an S3-compatible bucket
+// (path-style addressing, so MinIO works too) using the endpoint and
+// credentials from settings. Returns the object URL on success.
+#[tauri::command]
+async fn upload_report_to_s3(app: tauri::AppHandle, report_id: String, format: String, bucket: String, key_prefix: String) -> Result<String, String> {
+    if format != "csv" && format != "json" {
+        return Err(ReportError::InvalidFormat(format).into());
+    }
+
+    let settings = load_settings(app.clone())?;
+    if settings.s3_endpoint.is_empty() {
+        return Err("S3 endpoint is not configured. Please set it in Settings.".to_string());
+    }
+    if settings.s3_access_key.is_empty() || settings.s3_secret_key.is_empty() {
+        return Err(ReportError::Auth("S3 credentials are not configured".to_string()).into());
+    }
+
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    // Render the deliverable in memory; nothing touches the local disk
+    let body = if format == "csv" {
+        let opts = CsvOptions {
+            thousands_separator: settings.thousands_separator,
+            custom_metrics: settings.custom_metrics.clone(),
+            csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+            decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+            ..Default::default()
+        };
+        let metrics = serde_json::to_value(&report.metrics)
+            .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+        build_csv(&report.data, &metrics, &opts)?
+    } else {
+        serde_json::to_string_pretty(report)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?
+    };
+
+    // Create a clean advertiser name (remove special chars)
+    let clean_advertiser = report.advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+
+    let file_name = format!("{}_{}_{}_{}.{}",
+        clean_advertiser,
+        report.report_type,
+        report.date_range.start_date,
+        report.date_range.end_date,
+        format
+    );
+    let key = if key_prefix.is_empty() {
+        file_name
+    } else {
+        format!("{}/{}", key_prefix.trim_matches('/'), file_name)
+    };
+
+    let object_url = format!("{}/{}/{}", settings.s3_endpoint.trim_end_matches('/'), bucket, key);
+    let parsed = url::Url::parse(&object_url)
+        .map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+    // The Host header (and its signed copy) must include a non-default port,
+    // which is the norm for local MinIO deployments
+    let host = match (parsed.host_str(), parsed.port()) {
+        (Some(h), Some(p)) => format!("{}:{}", h, p),
+        (Some(h), None) => h.to_string(),
+        (None, _) => return Err(format!("Invalid S3 endpoint: {}", settings.s3_endpoint)),
+    };
+
+    let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let payload_hash = sha256_hex(body.as_bytes());
+    let authorization = sigv4_authorization(
+        &settings.s3_access_key,
+        &settings.s3_secret_key,
+        &settings.s3_region,
+        &host,
+        parsed.path(),
+        &amz_date,
+        &payload_hash,
+    );
+
+    let client = reqwest::Client::new();
+    let response = client.put(&object_url)
+        .header("x-amz-date", &amz_date)
+        .header("x-amz-content-sha256", &payload_hash)
+        .header("Authorization", authorization)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| String::from(ReportError::Network(e.to_string())))?;
+
+    let status = response.status();
+    if status.as_u16() == 401 || status.as_u16() == 403 {
+        let detail = response.text().await.unwrap_or_default();
+        return Err(ReportError::Auth(format!("{} {}", status, detail.trim())).into());
+    }
+    if !status.is_success() {
+        let detail = response.text().await.unwrap_or_default();
+        return Err(ReportError::Network(format!("{} {}", status, detail.trim())).into());
+    }
+
+    println!("Uploaded report {} to {}", report_id, object_url);
+    Ok(object_url)
+}
+
+// Renders a JSON value canonically: object keys sorted, arrays kept in
+// stored order, integral floats collapsed to integers, and no whitespace.
+// Two logically-equal values always produce identical bytes.
+fn write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String((*key).clone()).to_string());
+                out.push(':');
+                write_canonical_json(&map[key.as_str()], out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        serde_json::Value::Number(n) => {
+            // 5.0 and 5 are the same quantity; render both as 5 so a
+            // regeneration that switches types doesn't dirty the diff
+            let integral = n.as_f64()
+                .filter(|_| n.as_i64().is_none() && n.as_u64().is_none())
+                .filter(|f| f.is_finite() && f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0);
+            match integral {
+                Some(f) => out.push_str(&format!("{}", f as i64)),
+                None => out.push_str(&n.to_string()),
+            }
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+fn to_canonical_json(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    write_canonical_json(value, &mut out);
+    out
+}
+
+// Writes a report as canonical JSON so clients who keep reports in git get
+// minimal, meaningful diffs between regenerations. Deliberately distinct
+// from download_report's pretty-print, which is for human eyes.
+#[tauri::command]
+fn export_canonical_json(app: tauri::AppHandle, report_id: String, out_path: String) -> Result<String, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let value = serde_json::to_value(report)
+        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+
+    // Trailing newline so git doesn't flag the file ending
+    fs::write(&out_path, format!("{}\n", to_canonical_json(&value)))
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    println!("Wrote canonical JSON for {} to {}", report_id, out_path);
+    Ok(out_path)
+}
+
+// Shell for export_standalone_html: one self-contained page with the report
+// embedded as inline JSON and a small vanilla-JS sortable/filterable table.
+// No external assets or network, so the file can be emailed around and
+// opened anywhere.
+const STANDALONE_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+__META__
+<title>__TITLE__</title>
+<style>
+body { font-family: __FONT__, Arial, sans-serif; margin: 2rem; color: #222; }
+h1 { color: __HEADER_COLOR__; margin-bottom: 0.25rem; }
+.meta { color: #666; margin-bottom: 1rem; }
+input { margin-bottom: 0.75rem; padding: 0.4rem; width: 16rem; }
+table { border-collapse: collapse; width: 100%; }
+th { background: __HEADER_COLOR__; color: #fff; cursor: pointer; }
+th, td { border: 1px solid #ddd; padding: 0.4rem 0.6rem; text-align: left; }
+tbody tr:nth-child(even) { background: #f7f7f7; }
+tfoot td { font-weight: bold; border-top: 2px solid __ACCENT_COLOR__; }
+</style>
+</head>
+<body>
+<h1>__TITLE__</h1>
+<div class="meta">__SUBTITLE__</div>
+__NOTES__
+<input id="filter" type="text" placeholder="Filter rows...">
+<table id="report"><thead></thead><tbody></tbody><tfoot></tfoot></table>
+<script id="report-data" type="application/json">__PAYLOAD__</script>
+<script>
+(function () {
+  var payload = JSON.parse(document.getElementById('report-data').textContent);
+  var candidates = [
+    { key: 'send_date', label: 'Date', always: true },
+    { key: 'unique_opens', label: 'Unique Opens' },
+    { key: 'total_opens', label: 'Total Opens' },
+    { key: 'total_recipients', label: 'Total Recipients' },
+    { key: 'targeted_count', label: 'Targeted Count' },
+    { key: 'hard_bounces', label: 'Hard Bounces', flag: 'bounce_detail' },
+    { key: 'soft_bounces', label: 'Soft Bounces', flag: 'bounce_detail' },
+    { key: 'delivery_rate', label: 'Delivery Rate', flag: 'bounce_detail' },
+    { key: 'total_clicks', label: 'Total Clicks' },
+    { key: 'ctr', label: 'CTR' },
+    { key: 'mailchimp_open_rate', label: 'Mailchimp Open Rate', flag: 'mailchimp_rates' },
+    { key: 'mailchimp_click_rate', label: 'Mailchimp Click Rate', flag: 'mailchimp_rates' },
+    { key: 'clicks_per_thousand', label: 'Clicks/1000' },
+    { key: 'share_of_clicks', label: 'Share of Clicks' }
+  ];
+  var columns = candidates.filter(function (c) {
+    return c.always || payload.metrics[c.flag || c.key];
+  });
+  var sortKey = 'send_date';
+  var sortDir = 1;
+  function esc(s) {
+    return s.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/"/g, '&quot;');
+  }
+  function cell(value) {
+    return value === null || value === undefined ? '' : esc(String(value));
+  }
+  function render() {
+    var filter = document.getElementById('filter').value.toLowerCase();
+    document.querySelector('thead').innerHTML = '<tr>' + columns.map(function (c) {
+      return '<th data-key=\'' + c.key + '\'>' + esc(c.label) + '</th>';
+    }).join('') + '</tr>';
+    var visible = payload.rows.filter(function (r) {
+      return columns.some(function (c) {
+        return String(r[c.key]).toLowerCase().indexOf(filter) !== -1;
+      });
+    });
+    visible.sort(function (a, b) {
+      var x = a[sortKey], y = b[sortKey];
+      if (x === y) return 0;
+      return (x > y ? 1 : -1) * sortDir;
+    });
+    document.querySelector('tbody').innerHTML = visible.map(function (r) {
+      return '<tr>' + columns.map(function (c) { return '<td>' + cell(r[c.key]) + '</td>'; }).join('') + '</tr>';
+    }).join('');
+    var tfoot = '';
+    if (payload.totals) {
+      tfoot = '<tr>' + columns.map(function (c, i) {
+        if (i === 0) return '<td>Totals</td>';
+        return '<td>' + cell(payload.totals[c.key]) + '</td>';
+      }).join('') + '</tr>';
+    }
+    document.querySelector('tfoot').innerHTML = tfoot;
+  }
+  document.getElementById('filter').addEventListener('input', render);
+  document.querySelector('thead').addEventListener('click', function (event) {
+    var key = event.target.getAttribute('data-key');
+    if (!key) return;
+    sortDir = key === sortKey ? -sortDir : 1;
+    sortKey = key;
+    render();
+  });
+  render();
+})();
+</script>
+</body>
+</html>
+"#;
+
+// Minimal HTML entity escaping for text interpolated into the page shell
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Document properties for DMS systems that index exported files: subject
+// and keywords from the report itself, author from settings when set
+fn document_meta_tags(report: &SavedReport, author: &str) -> String {
+    let mut tags = Vec::new();
+    if !author.trim().is_empty() {
+        tags.push(format!("<meta name=\"author\" content=\"{}\">", html_escape(author.trim())));
+    }
+    tags.push(format!(
+        "<meta name=\"description\" content=\"{}\">",
+        html_escape(&format!("{} {} report, {} to {}", report.advertiser, report.report_type, report.date_range.start_date, report.date_range.end_date))
+    ));
+    tags.push(format!(
+        "<meta name=\"keywords\" content=\"{}\">",
+        html_escape(&format!("{}, {}, {}, {}", report.advertiser, report.report_type, report.date_range.start_date, report.date_range.end_date))
+    ));
+    tags.join("\n")
+}
+
+// Renders a saved report into the standalone page shell, respecting the
+// report's metric selection and the configured export theme
+fn standalone_html(report: &SavedReport, theme: &ExportTheme, author: &str) -> Result<String, String> {
+    let metrics = serde_json::to_value(&report.metrics)
+        .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+    let rows = report.data.get("report_data")
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let payload = serde_json::json!({
+        "metrics": metrics,
+        "totals": compute_totals(&rows),
+        "rows": rows,
+    });
+    let payload_json = serde_json::to_string(&payload)
+        .map_err(|e| format!("Failed to serialize report data: {}", e))?
+        // A literal "</script>" inside the data would end the tag early
+        .replace("</", "<\\/");
+
+    let title = format!("{} - {}", report.advertiser, report.name);
+    let subtitle = format!(
+        "{} | {} to {} | generated {}",
+        report.report_type, report.date_range.start_date, report.date_range.end_date, report.created
+    );
+
+    let notes_html = if report.notes.trim().is_empty() {
+        String::new()
+    } else {
+        format!("<div class=\"meta\">Notes: {}</div>", html_escape(report.notes.trim()))
+    };
+
+    Ok(STANDALONE_HTML_TEMPLATE
+        .replace("__META__", &document_meta_tags(report, author))
+        .replace("__TITLE__", &html_escape(&title))
+        .replace("__SUBTITLE__", &html_escape(&subtitle))
+        .replace("__NOTES__", &notes_html)
+        .replace("__FONT__", &html_escape(&theme.font))
+        .replace("__HEADER_COLOR__", &html_escape(&theme.header_color))
+        .replace("__ACCENT_COLOR__", &html_escape(&theme.accent_color))
+        .replace("__PAYLOAD__", &payload_json))
+}
+
+// Writes a shareable read-only report page for clients without the app
+#[tauri::command]
+fn export_standalone_html(app: tauri::AppHandle, report_id: String, out_path: String) -> Result<String, String> {
+    let settings = load_settings(app.clone())?;
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let html = standalone_html(report, &resolve_export_theme(&settings), &settings.export_author)?;
+    fs::write(&out_path, html)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    println!("Wrote standalone HTML for {} to {}", report_id, out_path);
+    Ok(out_path)
+}
+
+// Variables exposed to user templates: report metadata under `report`, the
+// per-campaign entries under `rows`, and the summed totals under `totals`.
+// Stable names on purpose; user template files outlive internal refactors.
+fn template_context(report: &SavedReport) -> serde_json::Value {
+    let rows: Vec<serde_json::Value> = report.data.get("report_data")
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let totals = compute_totals(&rows);
+    serde_json::json!({
+        "report": {
+            "id": report.id,
+            "name": report.name,
+            "advertiser": report.advertiser,
+            "report_type": report.report_type,
+            "start_date": report.date_range.start_date,
+            "end_date": report.date_range.end_date,
+            "created": report.created,
+            "tags": report.tags
+        },
+        "rows": rows,
+        "totals": totals
+    })
+}
+
+// Compiles and renders a user-supplied Handlebars template. Both error paths
+// pass along the engine's message, which carries the offending line and
+// column, so users can fix their template file without guessing.
+fn render_report_template(template: &str, report: &SavedReport) -> Result<String, String> {
+    let mut registry = handlebars::Handlebars::new();
+    registry.register_template_string("report", template)
+        .map_err(|e| format!("Template failed to compile: {}", e))?;
+    registry.render("report", &template_context(report))
+        .map_err(|e| format!("Template failed to render: {}", e))
+}
+
+// Renders a saved report through a user-supplied template file so clients
+// can get bespoke text/HTML/markdown layouts without code changes
+#[tauri::command]
+fn export_with_template(app: tauri::AppHandle, report_id: String, template_path: String, out_path: String) -> Result<String, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let template = fs::read_to_string(&template_path)
+        .map_err(|e| format!("Failed to read template: {}", e))?;
+    let rendered = render_report_template(&template, report)?;
+
+    fs::write(&out_path, rendered)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    println!("Rendered report {} through {} to {}", report_id, template_path, out_path);
+    Ok(out_path)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct HighlightRow {
+    campaign_id: String,
+    send_date: String,
+    total_clicks: u64,
+    ctr: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Highlights {
+    top_by_ctr: Vec<HighlightRow>,
+    bottom_by_ctr: Vec<HighlightRow>,
+    top_by_clicks: Vec<HighlightRow>,
+    bottom_by_clicks: Vec<HighlightRow>,
+}
+
+// Top/bottom N campaigns from a report's stored rows, ranked by CTR and
+// separately by clicks. With fewer than 2N rows the two ends overlap;
+// that's fine, callers get whatever is available.
+fn compute_highlights(data: &serde_json::Value, n: usize) -> Result<Highlights, String> {
+    let entries = data.get("report_data")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| "Report has no report_data".to_string())?;
+
+    let rows: Vec<HighlightRow> = entries.iter()
+        .map(|entry| HighlightRow {
+            campaign_id: entry.get("campaign_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            send_date: entry.get("send_date").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            total_clicks: entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0),
+            ctr: entry.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0),
+        })
+        .collect();
+
+    let mut by_ctr = rows.clone();
+    by_ctr.sort_by(|a, b| b.ctr.total_cmp(&a.ctr));
+    let mut by_clicks = rows;
+    by_clicks.sort_by_key(|r| std::cmp::Reverse(r.total_clicks));
+
+    // Bottom lists run worst-first so "bottom 3" reads naturally
+    Ok(Highlights {
+        top_by_ctr: by_ctr.iter().take(n).cloned().collect(),
+        bottom_by_ctr: by_ctr.iter().rev().take(n).cloned().collect(),
+        top_by_clicks: by_clicks.iter().take(n).cloned().collect(),
+        bottom_by_clicks: by_clicks.iter().rev().take(n).cloned().collect(),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct RunTiming {
+    report_id: String,
+    advertiser: String,
+    created: String,
+    elapsed_secs: Option<u64>,
+    campaign_count: Option<usize>,
+}
+
+// Timing history across saved reports, oldest first. Reports from before
+// timings were recorded show up with empty fields rather than being hidden.
+fn run_timing_history_from(reports: &[SavedReport]) -> Vec<RunTiming> {
+    let mut timings: Vec<RunTiming> = reports.iter()
+        .map(|report| RunTiming {
+            report_id: report.id.clone(),
+            advertiser: report.advertiser.clone(),
+            created: report.created.clone(),
+            elapsed_secs: report.elapsed_secs,
+            campaign_count: report.campaign_count,
+        })
+        .collect();
+    timings.sort_by(|a, b| a.created.cmp(&b.created));
+    timings
+}
+
+// Per-report run durations for diagnosing "reports got slow" complaints
+#[tauri::command]
+fn run_timing_history(app: tauri::AppHandle) -> Result<Vec<RunTiming>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    Ok(run_timing_history_from(&reports))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TimePoint {
+    send_date: String,
+    value: f64,
+    average: f64,
+}
+
+// Trailing moving average of one metric over a report's date-sorted rows.
+// The leading edge averages however many points exist, so the series starts
+// at the first send instead of `window` sends in.
+fn rolling_average_series(data: &serde_json::Value, metric: &str, window: usize) -> Result<Vec<TimePoint>, String> {
+    if window == 0 {
+        return Err("Window size must be at least 1".to_string());
+    }
+    let allowed = ["ctr", "total_clicks", "unique_opens", "total_opens", "clicks_per_thousand"];
+    if !allowed.contains(&metric) {
+        return Err(format!("Unsupported metric: {}", metric));
+    }
+
+    let entries = data.get("report_data")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| "Report has no report_data".to_string())?;
+
+    let mut rows: Vec<(String, f64)> = entries.iter()
+        .map(|entry| (
+            entry.get("send_date").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            entry.get(metric).and_then(|v| v.as_f64()).unwrap_or(0.0),
+        ))
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let values: Vec<f64> = rows.iter().map(|(_, value)| *value).collect();
+    Ok(rows.into_iter()
+        .enumerate()
+        .map(|(i, (send_date, value))| {
+            let from = (i + 1).saturating_sub(window);
+            let slice = &values[from..=i];
+            TimePoint {
+                send_date,
+                value,
+                average: slice.iter().sum::<f64>() / slice.len() as f64,
+            }
+        })
+        .collect())
+}
+
+// Trailing moving average of a metric across a saved report's campaigns,
+// for trend smoothing. Pure computation over stored rows.
+#[tauri::command]
+fn rolling_average(app: tauri::AppHandle, report_id: String, metric: String, window: usize) -> Result<Vec<TimePoint>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    rolling_average_series(&report.data, &metric, window)
+}
+
+// "Top 3 / bottom 3" style highlights from a saved report, by CTR and by
+// clicks. Pure computation over stored rows; no Mailchimp calls.
+#[tauri::command]
+fn report_highlights(app: tauri::AppHandle, report_id: String, n: usize) -> Result<Highlights, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    compute_highlights(&report.data, n)
+}
+
+// Re-verifies a delivered file against its .sha256 sidecar
+#[tauri::command]
+fn verify_export(path: String, checksum_path: String) -> Result<bool, String> {
+    let sidecar = fs::read_to_string(&checksum_path)
+        .map_err(|e| format!("Failed to read checksum file: {}", e))?;
+    let expected = sidecar.split_whitespace().next()
+        .ok_or_else(|| "Checksum file is empty".to_string())?;
+
+    let actual = file_sha256(Path::new(&path))?;
+    Ok(actual == expected)
+}
+
+// Everything the app ever writes under its config dir. Kept as an explicit
+// list so a stray user file in the same directory survives a reset.
+const APP_DATA_FILES: &[&str] = &[
+    "settings.json",
+    "settings.json.tmp",
+    "reports.json",
+    "reports.json.tmp",
+    "reports.json.lock",
+    "trash.json",
+    "merge_fields.json",
+    "fetch_times.json",
+    "diagnostics.json",
+    "active_profile",
+];
+const APP_DATA_DIRS: &[&str] = &["backups", "campaign_cache",
+    "debug_captures", "profiles"];
+
+fn factory_reset_in_dir(app_dir: &Path, confirm: &str) -> Result<(), String> {
+    if confirm != "DELETE" {
+        return Err("Factory reset requires the confirmation token DELETE".to_string());
+    }
+    for name in APP_DATA_FILES {
+        let path = app_dir.join(name);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", name, e))?;
+        }
+    }
+    for name in APP_DATA_DIRS {
+        let path = app_dir.join(name);
+        if path.exists() {
+            fs::remove_dir_all(&path).map_err(|e| format!("Failed to remove {}: {}", name, e))?;
+        }
+    }
+    Ok(())
+}
+
+// Clean wipe for QA machines and hand-offs. Destructive, so it only runs
+// when the caller passes the literal confirmation token "DELETE".
+#[tauri::command]
+fn factory_reset(app: tauri::AppHandle, confirm: String) -> Result<(), String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    factory_reset_in_dir(&app_dir, &confirm)?;
+    println!("Factory reset: cleared application data in {}", app_dir.display());
+    Ok(())
+}
+
+#[tauri::command]
+fn opener_open(_app: tauri::AppHandle, path: String) -> Result<(), String> {
+    // Use a standard method to open the file
+    let path_obj = std::path::Path::new(&path);
+    opener::open(path_obj)
+        .map_err(|e| format!("Failed to open file: {}", e))
+}
+
+// Filters a report's stored rows to a date window (inclusive, dates as
+// YYYY-MM-DD like the stored send_date) and recomputes the totals for just
+// that subset. The campaign list and metrics selection carry over as-is.
+fn slice_report_data(data: &serde_json::Value, start_date: &str, end_date: &str) -> Result<serde_json::Value, String> {
+    let entries = data.get("report_data")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| "Invalid report format: missing report_data".to_string())?;
+
+    let subset: Vec<serde_json::Value> = entries.iter()
+        .filter(|e| {
+            let date = e.get("send_date").and_then(|d| d.as_str()).unwrap_or("");
+            date >= start_date && date <= end_date
+        })
+        .cloned()
+        .collect();
+
+    let mut sliced = data.clone();
+    sliced["report_totals"] = compute_totals(&subset);
+    sliced["report_data"] = serde_json::json!(subset);
+
+    Ok(sliced)
+}
+
+// Exports just a date window of an already-saved report, without
+// re-fetching anything from Mailchimp
+#[tauri::command]
+fn export_report_slice(app: tauri::AppHandle, report_id: String, start_date: String, end_date: String, format: String) -> Result<String, String> {
+    if format != "csv" {
+        return Err(format!("Unsupported export format: {}", format));
+    }
+
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let mut report_value = serde_json::to_value(report)
+        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+    report_value["data"] = slice_report_data(&report.data, &start_date, &end_date)?;
+    // So the exported filename reflects the slice, not the original window
+    report_value["date_range"] = serde_json::json!({
+        "start_date": start_date,
+        "end_date": end_date
+    });
+
+    download_csv(app, report_value, None, None, None, None)
+}
+
+// Partitions a report's stored rows by calendar month, reusing the slice
+// logic so each partition carries its own recomputed totals. Months with no
+// rows simply don't appear.
+fn month_slices(data: &serde_json::Value) -> Result<Vec<(String, serde_json::Value)>, String> {
+    let entries = data.get("report_data")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| "Invalid report format: missing report_data".to_string())?;
+
+    let mut months: Vec<String> = Vec::new();
+    for entry in entries {
+        let month = group_key(entry.get("send_date").and_then(|d| d.as_str()).unwrap_or(""), "month");
+        if !months.contains(&month) {
+            months.push(month);
+        }
+    }
+
+    let mut slices = Vec::new();
+    for month in months {
+        // Stored dates are zero-padded, so the day bounds compare correctly
+        // as strings
+        let slice = slice_report_data(data, &format!("{}-01", month), &format!("{}-31", month))?;
+        let has_rows = slice.get("report_data")
+            .and_then(|d| d.as_array())
+            .map(|a| !a.is_empty())
+            .unwrap_or(false);
+        if has_rows {
+            slices.push((month, slice));
+        }
+    }
+
+    Ok(slices)
+}
+
+// Splits a saved report into one file per calendar month, for clients who
+// file monthly. Returns the written paths; months with no campaign
+// activity produce no file.
+#[tauri::command]
+fn export_report_by_month(app: tauri::AppHandle, report_id: String, format: String, out_dir: String) -> Result<Vec<String>, String> {
+    if format != "csv" && format != "json" {
+        return Err(ReportError::InvalidFormat(format).into());
+    }
+
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let settings = load_settings(app.clone())?;
+
+    let out_path = std::path::Path::new(&out_dir);
+    if !out_path.exists() {
+        std::fs::create_dir_all(out_path)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+
+    let opts = CsvOptions {
+        thousands_separator: settings.thousands_separator,
+        custom_metrics: settings.custom_metrics.clone(),
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        ..Default::default()
+    };
+    let metrics = serde_json::to_value(&report.metrics)
+        .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+
+    // Create a clean advertiser name (remove special chars)
+    let clean_advertiser = report.advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+
+    let mut written = Vec::new();
+    for (month, slice) in month_slices(&report.data)? {
+        let file_path = out_path.join(format!("{}_{}_{}.{}", clean_advertiser, report.report_type, month, format));
+
+        if format == "csv" {
+            let csv = build_csv(&slice, &metrics, &opts)?;
+            std::fs::write(&file_path, csv.as_bytes())
+                .map_err(|e| format!("Failed to write CSV: {}", e))?;
+        } else {
+            let json = serde_json::to_string_pretty(&slice)
+                .map_err(|e| format!("Failed to serialize report slice: {}", e))?;
+            std::fs::write(&file_path, json.as_bytes())
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+        }
+
+        // Compliance sidecar so delivered files can be verified later
+        if settings.emit_checksum {
+            let hash = write_checksum_sidecar(&file_path)?;
+            println!("Export checksum: {}", hash);
+        }
+
+        written.push(file_path.to_string_lossy().to_string());
+    }
+
+    println!("Wrote {} monthly files to {}", written.len(), out_dir);
+    Ok(written)
+}
+
+// Rebuilds the request that would regenerate a saved report, using the
+// advertiser's stored default tracking URLs. Tracking URLs aren't persisted
+// with reports, so this only works for advertisers with URLs in settings.
+fn rebuild_report_request(report: &SavedReport, tracking_urls: Vec<String>) -> ReportRequest {
+    ReportRequest {
+        newsletter_type: report.report_type.clone(),
+        advertiser: report.advertiser.clone(),
+        tracking_urls,
+        date_range: report.date_range.clone(),
+        metrics: report.metrics.clone(),
+        path_match: default_path_match(),
+        folder_id: None,
+        // A quiet window shouldn't fail the re-run; the original report
+        // already established this request shape is valid
+        allow_empty: true,
+        include_list_activity: false,
+        group_by: None,
+        incremental: false,
+        filter_mode: default_filter_mode(),
+        tag: None,
+        exclude_wrong_audience: false,
+        audience_scope: None,
+        preview_token: None,
+        debug_capture: false,
+    }
+}
+
+// Convenience for the "open the usual report" flow: reconstructs the most
+// recently created saved report's request from the advertiser's stored
+// tracking URLs, re-runs the pipeline, then exports and opens the fresh
+// result. Advertisers without stored URLs fall back to exporting the
+// saved rows as-is, since their original request can't be rebuilt.
+#[tauri::command]
+async fn run_last_report(app: tauri::AppHandle, format: String) -> Result<String, String> {
+    if format != "csv" {
+        return Err(format!("Unsupported export format: {}", format));
+    }
+
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let settings = load_settings(app.clone())?;
+    let reports = load_reports_from_dir(&app_dir)?;
+    let latest = reports.iter()
+        .max_by(|a, b| a.created.cmp(&b.created))
+        .ok_or_else(|| "No saved reports to run".to_string())?;
+
+    let stored_urls = settings.advertiser_urls.get(&latest.advertiser)
+        .filter(|urls| !urls.is_empty())
+        .cloned();
+
+    let report_value = match stored_urls {
+        Some(tracking_urls) => {
+            let request = rebuild_report_request(latest, tracking_urls);
+            let sink = ProgressSink::Window(app.clone());
+            let response = run_report(app.clone(), request, sink).await?;
+            if !response.success {
+                return Err(response.message);
+            }
+
+            // The run just saved a fresh report; export that one
+            let rerun = load_reports_from_dir(&app_dir)?
+                .into_iter()
+                .max_by(|a, b| a.created.cmp(&b.created))
+                .ok_or_else(|| "Re-run saved no report".to_string())?;
+            serde_json::to_value(&rerun)
+                .map_err(|e| format!("Failed to serialize report: {}", e))?
+        }
+        None => {
+            println!("No stored tracking URLs for {}; exporting saved rows as-is", latest.advertiser);
+            serde_json::to_value(latest)
+                .map_err(|e| format!("Failed to serialize report: {}", e))?
+        }
+    };
+
+    let path = download_csv(app.clone(), report_value, None, None, None, None)?;
+
+    opener::open(std::path::Path::new(&path))
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+
+    Ok(path)
+}
+
+#[tauri::command]
+fn download_report(app: tauri::AppHandle, report: serde_json::Value) -> Result<String, String> {
+    // Load settings to get the custom download directory
+    let settings = load_settings(app.clone())?;
+
+    // Create a timestamp for the file name
+    let timestamp = format_timestamp_now(&settings.timestamp_timezone, "%Y%m%d_%H%M%S");
+    
+    // Write into the advertiser's mapped folder when one is configured
+    let advertiser = report.get("advertiser")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown-advertiser");
+    let download_dir = resolve_export_dir(&settings, advertiser)?;
+
+    // Debug log the download directory
+    println!("Using download directory: '{}'", download_dir.display());
+    
+    // Create a file name with the report name if available
+    let report_name = report.get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("report");
+    
+    let file_name = format!("{}_{}.json", report_name, timestamp);
+    let file_path = download_dir.join(file_name);
+    
+    // Debug log the file path
+    println!("Writing JSON report to: '{}'", file_path.display());
+    
+    // Serialize report to JSON
+    let report_json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+
+    // Write to file
+    match std::fs::write(&file_path, report_json.as_bytes()) {
+        Ok(_) => {
+            println!("Successfully wrote JSON file to {}", file_path.display());
+            // Compliance sidecar so delivered files can be verified later
+            if settings.emit_checksum {
+                let hash = write_checksum_sidecar(&file_path)?;
+                println!("Export checksum: {}", hash);
+            }
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to write file: {}", e);
+            println!("{}", error_msg);
+            return Err(error_msg);
+        }
+    };
+
+    // Return the file path for displaying to the user
+    let path_str = file_path.to_string_lossy().to_string();
+    println!("Returning file path: '{}'", path_str);
+    Ok(path_str)
+}
+
+// Lets the UI surface data-shape problems before an export is attempted
+#[tauri::command]
+fn validate_report(app: tauri::AppHandle, report_id: String) -> Result<(), String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let metrics = serde_json::to_value(&report.metrics)
+        .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+    validate_report_data(&report.data, &metrics).map_err(String::from)
+}
+
+#[tauri::command]
+fn download_csv(app: tauri::AppHandle, reportData: serde_json::Value, top_n: Option<usize>, compact: Option<bool>, group_by: Option<String>, ctr_alert_threshold: Option<f64>) -> Result<String, String> {
+    // Extract report data for CSV content
+    let report_data = reportData.get("data")
+        .ok_or_else(|| "Invalid report format: missing data field".to_string())?;
+    
+    // Get selected metrics from the report data
+    let metrics = report_data.get("metrics")
+        .ok_or_else(|| "Invalid report format: missing metrics".to_string())?;
+    
+    // Load settings to get the custom download directory
+    let settings = load_settings(app.clone())?;
+    
+    // Extract report metadata for filename
+    let advertiser = reportData.get("advertiser")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown-advertiser");
+
+    // Write into the advertiser's mapped folder when one is configured
+    let download_dir = resolve_export_dir(&settings, advertiser)?;
+    
+    let newsletter_type = reportData.get("report_type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown-type");
+    
+    // Extract date range for filename
+    let date_range = if let Some(range) = reportData.get("date_range") {
+        let start = range.get("start_date")
+            .and_then(|d| d.as_str())
+            .unwrap_or("");
+        
+        let end = range.get("end_date")
+            .and_then(|d| d.as_str())
+            .unwrap_or("");
+            
+        if !start.is_empty() && !end.is_empty() {
+            format!("{}_{}", start, end)
+        } else {
+            "unknown-dates".to_string()
+        }
+    } else {
+        "unknown-dates".to_string()
+    };
+    
+    // Create a timestamp for uniqueness if needed
+    let timestamp = format_timestamp_now(&settings.timestamp_timezone, "%Y%m%d_%H%M%S");
+    
+    // Create a clean advertiser name (remove special chars)
+    let clean_advertiser = advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+    
+    // Format the filename: Advertiser_NewsletterType_DateRange.csv
+    let file_name = format!("{}_{}_{}_{}.csv", 
+        clean_advertiser,
+        newsletter_type,
+        date_range,
+        timestamp
+    );
+    
+    let file_path = download_dir.join(&file_name);
+    
+    // Build the CSV content through the shared builder
+    let opts = CsvOptions {
+        top_n,
+        thousands_separator: settings.thousands_separator,
+        custom_metrics: settings.custom_metrics.clone(),
+        compact: compact.unwrap_or(false),
+        group_by,
+        ctr_alert_threshold,
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        cost_per_click: rate_for_advertiser(&settings.rate_card, advertiser),
+        rounding_mode: settings.rounding_mode.clone(),
+    };
+    let csv = build_csv(report_data, metrics, &opts)?;
+
+    // Write the CSV content to the file
+    std::fs::write(&file_path, csv.as_bytes())
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    // Compliance sidecar so delivered files can be verified later
+    if settings.emit_checksum {
+        let hash = write_checksum_sidecar(&file_path)?;
+        println!("Export checksum: {}", hash);
+    }
+    
+    // Return the file path as a string
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+// Prepends the analyst notes as a comment line so exported sheets carry the
+// context without disturbing the tabular rows
+fn csv_with_notes(csv: &str, notes: &str) -> String {
+    let notes = notes.trim();
+    if notes.is_empty() {
+        return csv.to_string();
+    }
+    format!("# Notes: {}\n{}", notes.replace('\n', " "), csv)
+}
+
+// Writes one report in several formats with a shared timestamp, rendering
+// everything up front and rolling back on a failed write so the bundle is
+// all-or-nothing. Only the formats with real exporters are accepted.
+fn export_bundle_to_dir(
+    report: &SavedReport,
+    formats: &[String],
+    dir: &Path,
+    opts: &CsvOptions,
+    timestamp: &str,
+    emit_checksum: bool,
+) -> Result<Vec<String>, String> {
+    if formats.is_empty() {
+        return Err("No formats requested".to_string());
+    }
+
+    let metrics = serde_json::to_value(&report.metrics)
+        .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+
+    // Render everything before writing anything
+    let mut rendered: Vec<(String, String)> = Vec::new();
+    for format in formats {
+        let content = match format.as_str() {
+            "json" => serde_json::to_string_pretty(report)
+                .map_err(|e| format!("Failed to serialize report: {}", e))?,
+            "csv" => csv_with_notes(&build_csv(&report.data, &metrics, opts)?, &report.notes),
+            other => return Err(format!("Unsupported bundle format: {} (supported: json, csv)", other)),
+        };
+        rendered.push((format.clone(), content));
+    }
+
+    let clean_advertiser = report.advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+    let mut written: Vec<std::path::PathBuf> = Vec::new();
+    for (ext, content) in &rendered {
+        let file_name = format!(
+            "{}_{}_{}_{}_{}.{}",
+            clean_advertiser, report.report_type,
+            report.date_range.start_date, report.date_range.end_date,
+            timestamp, ext
+        );
+        let file_path = dir.join(file_name);
+        let result = fs::write(&file_path, content.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", file_path.display(), e))
+            .and_then(|_| {
+                if emit_checksum {
+                    write_checksum_sidecar(&file_path).map(|_| ())
+                } else {
+                    Ok(())
+                }
+            });
+        if let Err(e) = result {
+            // Roll back already-written files so the bundle stays atomic
+            for path in &written {
+                let _ = fs::remove_file(path);
+                let _ = fs::remove_file(path.with_extension(format!("{}.sha256", path.extension().and_then(|x| x.to_str()).unwrap_or(""))));
+            }
+            let _ = fs::remove_file(&file_path);
+            return Err(e);
+        }
+        written.push(file_path);
+    }
+
+    Ok(written.into_iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
+
+// One stage-by-stage engagement line: recipients -> opens -> ad clicks,
+// with each stage as a percentage of the one before it
+#[derive(Debug, Serialize)]
+struct FunnelRow {
+    label: String,
+    recipients: u64,
+    unique_opens: u64,
+    // Unique opens as a percentage of recipients
+    open_rate: f64,
+    ad_clicks: u64,
+    // Ad clicks as a percentage of unique opens
+    click_to_open_rate: f64,
+}
+
+// Builds the funnel from stored report rows: one row per campaign plus an
+// aggregate computed from the summed counts. Empty stages yield 0.0 rather
+// than dividing by zero.
+fn compute_funnel(data: &[serde_json::Value]) -> Vec<FunnelRow> {
+    let pct = |part: u64, whole: u64| {
+        if whole > 0 {
+            (part as f64 / whole as f64) * 100.0
+        } else {
+            0.0
+        }
+    };
+    let row = |label: String, recipients: u64, opens: u64, clicks: u64| FunnelRow {
+        label,
+        recipients,
+        unique_opens: opens,
+        open_rate: pct(opens, recipients),
+        ad_clicks: clicks,
+        click_to_open_rate: pct(clicks, opens),
+    };
+
+    let mut rows: Vec<FunnelRow> = data.iter().map(|entry| {
+        row(
+            entry.get("send_date").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+            entry.get("total_recipients").and_then(|v| v.as_u64()).unwrap_or(0),
+            entry.get("unique_opens").and_then(|v| v.as_u64()).unwrap_or(0),
+            entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0),
+        )
+    }).collect();
+
+    let recipients: u64 = rows.iter().map(|r| r.recipients).sum();
+    let opens: u64 = rows.iter().map(|r| r.unique_opens).sum();
+    let clicks: u64 = rows.iter().map(|r| r.ad_clicks).sum();
+    rows.push(row("All campaigns".to_string(), recipients, opens, clicks));
+    rows
+}
+
+// Engagement funnel for a saved report, per campaign and in aggregate
+#[tauri::command]
+fn report_funnel(app: tauri::AppHandle, report_id: String) -> Result<Vec<FunnelRow>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let data = report.data.get("report_data")
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    Ok(compute_funnel(&data))
+}
+
+// One campaign's slice of the report's clicks, for pie-chart breakdowns
+#[derive(Debug, Serialize)]
+struct ContributionRow {
+    send_date: String,
+    total_clicks: u64,
+    // This campaign's clicks as a percentage of the report total; 0.0 for
+    // every row when the report has no clicks at all
+    contribution: f64,
+}
+
+// Computes each campaign's share of the total clicks, biggest slice first
+fn compute_click_contribution(data: &[serde_json::Value]) -> Vec<ContributionRow> {
+    let total: u64 = data.iter()
+        .map(|e| e.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0))
+        .sum();
+
+    let mut rows: Vec<ContributionRow> = data.iter().map(|entry| {
+        let clicks = entry.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0);
+        ContributionRow {
+            send_date: entry.get("send_date").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+            total_clicks: clicks,
+            contribution: if total > 0 {
+                (clicks as f64 / total as f64) * 100.0
+            } else {
+                0.0
+            },
+        }
+    }).collect();
+    rows.sort_by_key(|r| std::cmp::Reverse(r.total_clicks));
+    rows
+}
+
+#[tauri::command]
+fn click_contribution(app: tauri::AppHandle, report_id: String) -> Result<Vec<ContributionRow>, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let data = report.data.get("report_data")
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+    Ok(compute_click_contribution(&data))
+}
+
+// One advertiser measured against the rest of the portfolio, with the
+// others folded into an anonymous average
+#[derive(Debug, Serialize)]
+struct Benchmark {
+    advertiser: String,
+    start_date: String,
+    end_date: String,
+    advertiser_ctr: f64,
+    advertiser_clicks: u64,
+    // Mean of the per-advertiser aggregates across every advertiser with
+    // reports in the period, the target included
+    portfolio_avg_ctr: f64,
+    portfolio_avg_clicks: f64,
+    // 0-100: share of the other advertisers whose CTR the target beats.
+    // None when there are too few others to rank against.
+    percentile: Option<f64>,
+    note: Option<String>,
+}
+
+// Aggregates stored reports into per-advertiser CTR/clicks for the period
+// and ranks the target against the rest. Reports count when their date
+// range overlaps the requested one.
+fn compute_benchmark(reports: &[SavedReport], advertiser: &str, start_date: &str, end_date: &str) -> Result<Benchmark, String> {
+    let mut rows_by_advertiser: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
+    for report in reports {
+        if report.date_range.start_date.as_str() > end_date || report.date_range.end_date.as_str() < start_date {
+            continue;
+        }
+        let rows = report.data.get("report_data")
+            .and_then(|d| d.as_array())
+            .cloned()
+            .unwrap_or_default();
+        rows_by_advertiser.entry(report.advertiser.clone()).or_default().extend(rows);
+    }
+
+    let per_advertiser: HashMap<String, (f64, u64)> = rows_by_advertiser.iter()
+        .map(|(name, rows)| {
+            let totals = compute_totals(rows);
+            (
+                name.clone(),
+                (
+                    totals.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0),
+                    totals.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0),
+                ),
+            )
+        })
+        .collect();
+
+    let (advertiser_ctr, advertiser_clicks) = *per_advertiser.get(advertiser)
+        .ok_or_else(|| format!("No reports found for advertiser {} between {} and {}", advertiser, start_date, end_date))?;
+
+    let portfolio_avg_ctr = per_advertiser.values().map(|(ctr, _)| ctr).sum::<f64>() / per_advertiser.len() as f64;
+    let portfolio_avg_clicks = per_advertiser.values().map(|(_, clicks)| *clicks as f64).sum::<f64>() / per_advertiser.len() as f64;
+
+    let others: Vec<f64> = per_advertiser.iter()
+        .filter(|(name, _)| name.as_str() != advertiser)
+        .map(|(_, (ctr, _))| *ctr)
+        .collect();
+    let (percentile, note) = if others.len() < 2 {
+        (None, Some(format!(
+            "Only {} other advertiser(s) have reports in this period; too few to benchmark against",
+            others.len()
+        )))
+    } else {
+        let beaten = others.iter().filter(|ctr| **ctr < advertiser_ctr).count();
+        (Some((beaten as f64 / others.len() as f64) * 100.0), None)
+    };
+
+    Ok(Benchmark {
+        advertiser: advertiser.to_string(),
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        advertiser_ctr,
+        advertiser_clicks,
+        portfolio_avg_ctr,
+        portfolio_avg_clicks,
+        percentile,
+        note,
+    })
+}
+
+// How an advertiser stacks against the anonymized portfolio for a period
+#[tauri::command]
+fn advertiser_benchmark(app: tauri::AppHandle, advertiser: String, date_range: DateRange) -> Result<Benchmark, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    compute_benchmark(&reports, &advertiser, &date_range.start_date, &date_range.end_date)
+}
+
+// One manifest line per saved report, summarizing it for the handoff index
+#[derive(Debug, Serialize)]
+struct ManifestRow {
+    name: String,
+    start_date: String,
+    end_date: String,
+    created: String,
+    campaign_count: usize,
+    total_clicks: u64,
+    ctr: f64,
+}
+
+// Builds the portfolio index for one advertiser from stored metadata and
+// recomputed totals, oldest report first
+fn advertiser_manifest(reports: &[SavedReport], advertiser: &str) -> Vec<ManifestRow> {
+    let mut rows: Vec<ManifestRow> = reports.iter()
+        .filter(|r| r.advertiser == advertiser)
+        .map(|r| {
+            let entries = r.data.get("report_data")
+                .and_then(|d| d.as_array())
+                .cloned()
+                .unwrap_or_default();
+            let totals = compute_totals(&entries);
+            ManifestRow {
+                name: r.name.clone(),
+                start_date: r.date_range.start_date.clone(),
+                end_date: r.date_range.end_date.clone(),
+                created: r.created.clone(),
+                campaign_count: r.campaign_count.unwrap_or(entries.len()),
+                total_clicks: totals.get("total_clicks").and_then(|v| v.as_u64()).unwrap_or(0),
+                ctr: totals.get("ctr").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| a.created.cmp(&b.created));
+    rows
+}
+
+fn manifest_csv(rows: &[ManifestRow]) -> String {
+    let mut csv = String::from("Report Name,Start Date,End Date,Created,Campaign Count,Total Clicks,Overall CTR\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{:.2}\n",
+            csv_escape(&row.name, ','), row.start_date, row.end_date, row.created,
+            row.campaign_count, row.total_clicks, row.ctr
+        ));
+    }
+    csv
+}
+
+// Writes the handoff index of every report produced for an advertiser; the
+// out_path extension picks the format
+#[tauri::command]
+fn export_advertiser_manifest(app: tauri::AppHandle, advertiser: String, out_path: String) -> Result<String, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let rows = advertiser_manifest(&reports, &advertiser);
+    if rows.is_empty() {
+        return Err(format!("No reports found for advertiser: {}", advertiser));
+    }
+
+    let extension = std::path::Path::new(&out_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let content = match extension.as_str() {
+        "csv" => manifest_csv(&rows),
+        "json" => serde_json::to_string_pretty(&rows)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?,
+        other => return Err(format!("Unsupported manifest format: {} (supported: csv, json)", other)),
+    };
+
+    fs::write(&out_path, content)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    println!("Wrote manifest of {} reports for {} to {}", rows.len(), advertiser, out_path);
+    Ok(out_path)
+}
+
+// Re-renders stored reports with the current column and locale config.
+// One bad report doesn't stop the rest; its error is collected instead.
+fn reexport_to_dir(
+    reports: &[SavedReport],
+    report_ids: &[String],
+    format: &str,
+    dir: &Path,
+    opts_base: &CsvOptions,
+    rate_card: &HashMap<String, f64>,
+) -> (Vec<String>, Vec<String>) {
+    let mut written = Vec::new();
+    let mut errors = Vec::new();
+
+    for report_id in report_ids {
+        let report = match reports.iter().find(|r| r.id == *report_id) {
+            Some(report) => report,
+            None => {
+                errors.push(format!("{}: report not found", report_id));
+                continue;
+            }
+        };
+
+        let result = (|| -> Result<String, String> {
+            let clean_advertiser = report.advertiser.replace(&[' ', ',', '.', '/', '\\', ':', ';', '\"', '\'', '!', '?', '*', '(', ')', '[', ']', '{', '}', '<', '>'][..], "_");
+            let file_path = dir.join(format!(
+                "{}_{}_{}_{}.{}",
+                clean_advertiser, report.report_type,
+                report.date_range.start_date, report.date_range.end_date, format
+            ));
+
+            let content = if format == "csv" {
+                let metrics = serde_json::to_value(&report.metrics)
+                    .map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+                let mut opts = opts_base.clone();
+                opts.cost_per_click = rate_for_advertiser(rate_card, &report.advertiser);
+                csv_with_notes(&build_csv(&report.data, &metrics, &opts)?, &report.notes)
+            } else {
+                serde_json::to_string_pretty(report)
+                    .map_err(|e| format!("Failed to serialize report: {}", e))?
+            };
+
+            fs::write(&file_path, content)
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+            Ok(file_path.to_string_lossy().to_string())
+        })();
+
+        match result {
+            Ok(path) => written.push(path),
+            Err(e) => errors.push(format!("{}: {}", report_id, e)),
+        }
+    }
+
+    (written, errors)
+}
+
+// Refreshes a batch of stored exports after a column or locale config
+// change, so every delivered file matches the current settings
+#[tauri::command]
+fn reexport_reports(app: tauri::AppHandle, report_ids: Vec<String>, format: String, out_dir: String) -> Result<Vec<String>, String> {
+    if format != "csv" && format != "json" {
+        return Err(ReportError::InvalidFormat(format).into());
+    }
+
+    let settings = load_settings(app.clone())?;
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let reports = load_reports_from_dir(&app_dir)?;
+
+    let out_path = std::path::Path::new(&out_dir);
+    if !out_path.exists() {
+        std::fs::create_dir_all(out_path)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+
+    let opts_base = CsvOptions {
+        thousands_separator: settings.thousands_separator,
+        custom_metrics: settings.custom_metrics.clone(),
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        rounding_mode: settings.rounding_mode.clone(),
+        ..Default::default()
+    };
+
+    let mut written = Vec::new();
+    let mut errors = Vec::new();
+    for (index, report_id) in report_ids.iter().enumerate() {
+        emit_bulk_progress(&app, "Re-exporting reports", index, report_ids.len());
+        let (mut batch_written, mut batch_errors) =
+            reexport_to_dir(&reports, std::slice::from_ref(report_id), &format, out_path, &opts_base, &settings.rate_card);
+        written.append(&mut batch_written);
+        errors.append(&mut batch_errors);
+    }
+    emit_bulk_progress(&app, "Re-exporting reports", report_ids.len(), report_ids.len());
+
+    for error in &errors {
+        println!("Re-export failed: {}", error);
+    }
+    if written.is_empty() && !errors.is_empty() {
+        return Err(format!("All re-exports failed: {}", errors.join("; ")));
+    }
+
+    println!("Re-exported {} of {} reports to {}", written.len(), report_ids.len(), out_dir);
+    Ok(written)
+}
+
+// One call for the whole delivery bundle: the saved report rendered in each
+// requested format, written with a shared timestamp
+#[tauri::command]
+fn export_bundle(app: tauri::AppHandle, report_id: String, formats: Vec<String>) -> Result<Vec<String>, String> {
+    let settings = load_settings(app.clone())?;
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+
+    let reports = load_reports_from_dir(&app_dir)?;
+    let report = reports.iter()
+        .find(|r| r.id == report_id)
+        .ok_or_else(|| format!("Report not found: {}", report_id))?;
+
+    let opts = CsvOptions {
+        top_n: None,
+        thousands_separator: settings.thousands_separator,
+        custom_metrics: settings.custom_metrics.clone(),
+        compact: false,
+        group_by: None,
+        ctr_alert_threshold: None,
+        csv_delimiter: settings.csv_delimiter.chars().next().unwrap_or(','),
+        decimal_separator: settings.decimal_separator.chars().next().unwrap_or('.'),
+        cost_per_click: rate_for_advertiser(&settings.rate_card, &report.advertiser),
+        rounding_mode: settings.rounding_mode.clone(),
+    };
+
+    let download_dir = resolve_export_dir(&settings, &report.advertiser)?;
+    let timestamp = format_timestamp_now(&settings.timestamp_timezone, "%Y%m%d_%H%M%S");
+
+    let paths = export_bundle_to_dir(report, &formats, &download_dir, &opts, &timestamp, settings.emit_checksum)?;
+    println!("Exported bundle for {}: {} files", report_id, paths.len());
+    Ok(paths)
+}
+
+#[tauri::command]
+fn get_settings_path(app: tauri::AppHandle) -> Result<String, String> {
+    let app_dir = app.path().app_config_dir()
+        .map_err(|e| format!("Could not get app directory: {}", e))?;
+    let settings_path = app_dir.join("settings.json");
+    
+    Ok(settings_path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn emit_event(app: tauri::AppHandle, event: String, payload: Option<serde_json::Value>) -> Result<(), String> {
+    app.emit(&event, payload)
+        .map_err(|e| format!("Failed to emit event: {}", e))
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .setup(|app| {
+            #[cfg(debug_assertions)]
+            if let Some(window) = app.get_webview_window("main") {
+                window.open_devtools();
+            }
+            Ok(())
+        })
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            load_settings,
+            save_settings,
+            save_profile,
+            load_profile,
+            list_profiles,
+            delete_profile,
+            dedupe_advertisers,
+            generate_report,
+            generate_report_from_csv,
+            load_reports,
+            save_report,
+            generate_sample_report,
+            report_qr_codes,
+            find_overlapping_reports,
+            rename_advertiser_in_reports,
+            audit_reports,
+            report_text_summary,
+            report_highlights,
+            report_funnel,
+            click_contribution,
+            advertiser_benchmark,
+            rolling_average,
+            run_timing_history,
+            update_report_metrics,
+            add_report_tag,
+            remove_report_tag,
+            set_report_notes,
+            list_reports_by_tag,
+            get_campaign_links,
+            campaign_click_breakdown,
+            download_url_pivot_csv,
+            audit_advertiser_urls,
+            url_click_members,
+            export_click_details,
+            estimate_api_calls,
+            prefetch_campaigns,
+            preview_campaigns,
+            explain_matches,
+            list_campaign_tags,
+            test_url_match,
+            ctr_trend,
+            compare_periods,
+            reconcile_report,
+            reports_storage_stats,
+            cross_advertiser_report,
+            batch_generate,
+            list_folders,
+            get_merge_fields,
+            migrate_reports,
+            open_report_in_excel,
+            preview_csv,
+            write_report_file,
+            delete_report,
+            opener_open,
+            export_report_slice,
+            export_report_by_month,
+            upload_report_to_s3,
+            run_last_report,
+            download_report,
+            download_csv,
+            validate_report,
+            export_bundle,
+            export_advertiser_manifest,
+            reexport_reports,
+            get_settings_path,
+            get_debug_captures,
+            next_scheduled_run,
+            get_diagnostics,
+            factory_reset,
+            export_checksum,
+            export_canonical_json,
+            export_with_template,
+            export_standalone_html,
+            verify_export,
+            emit_event
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report(id: &str) -> SavedReport {
+        SavedReport {
+            id: id.to_string(),
+            name: format!("Test Report {}", id),
+            advertiser: "Test Advertiser".to_string(),
+            report_type: "AM".to_string(),
+            date_range: DateRange {
+                start_date: "2025-01-01".to_string(),
+                end_date: "2025-01-31".to_string(),
+            },
+            created: "2025-02-01".to_string(),
+            data: serde_json::json!({}),
+            metrics: Metrics {
+                unique_opens: true,
+                total_opens: true,
+                total_recipients: true,
+                total_clicks: true,
+                ctr: true,
+                clicks_per_thousand: false,
+                tags: false,
+                share_of_clicks: false,
+                admin_url: false,
+                variate_winner: false,
+                mailchimp_rates: false,
+                targeted_count: false,
+                bounce_detail: false,
+            },
+            tags: Vec::new(),
+            read_only: false,
+            elapsed_secs: None,
+            campaign_count: None,
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn timestamp_timezone_controls_offset() {
+        // The %z offset makes the zone choice observable regardless of when the test runs
+        let utc = format_timestamp_now("utc", "%z");
+        assert_eq!(utc, "+0000");
+
+        let local = format_timestamp_now("local", "%z");
+        let expected_local = chrono::Local::now().format("%z").to_string();
+        assert_eq!(local, expected_local);
+    }
+
+    #[test]
+    fn prefix_match_keeps_substring_behavior() {
+        assert!(url_matches_tracking("https://example.com/offer/thanks", "/offer", "prefix"));
+        assert!(url_matches_tracking("https://example.com/offers-expired", "/offer", "prefix"));
+    }
+
+    #[test]
+    fn segment_match_respects_path_boundaries() {
+        assert!(url_matches_tracking("https://example.com/offer/thanks", "/offer", "segment"));
+        assert!(!url_matches_tracking("https://example.com/offers", "/offer", "segment"));
+        assert!(!url_matches_tracking("https://example.com/offers-expired", "/offer", "segment"));
+    }
+
+    #[test]
+    fn exact_match_compares_normalized_links() {
+        assert!(url_matches_tracking("https://example.com/offer/", "https://example.com/offer", "exact"));
+        assert!(!url_matches_tracking("https://example.com/offer/thanks", "https://example.com/offer", "exact"));
+    }
+
+    #[test]
+    fn date_only_range_covers_the_full_days() {
+        let range = DateRange {
+            start_date: "2025-03-01".to_string(),
+            end_date: "2025-03-31".to_string(),
+        };
+        let (start, end) = date_range_bounds(&range).unwrap();
+        assert_eq!(start, "2025-03-01T00:00:00Z");
+        assert_eq!(end, "2025-03-31T23:59:59Z");
+    }
+
+    #[test]
+    fn timestamp_range_keeps_the_precise_instants() {
+        let range = DateRange {
+            start_date: "2025-03-01T12:00:00Z".to_string(),
+            end_date: "2025-03-01T17:30:00-04:00".to_string(),
+        };
+        let (start, end) = date_range_bounds(&range).unwrap();
+        assert_eq!(start, "2025-03-01T12:00:00Z");
+        assert_eq!(end, "2025-03-01T21:30:00Z");
+    }
+
+    #[test]
+    fn misspelled_url_suggests_the_real_link() {
+        let candidates = vec![
+            "https://example.com/spring-offer".to_string(),
+            "https://other.org/totally/different".to_string(),
+        ];
+        let suggestions = suggest_similar_urls("https://example.com/spring-offre", &candidates, 3);
+        assert_eq!(suggestions.first().map(|s| s.as_str()), Some("https://example.com/spring-offer"));
+    }
+
+    fn entry(date: &str, clicks: u64, opens: u64, recipients: u64) -> serde_json::Value {
+        serde_json::json!({
+            "send_date": date,
+            "unique_opens": opens,
+            "total_opens": opens + 10,
+            "total_recipients": recipients,
+            "total_clicks": clicks,
+            "ctr": if opens > 0 { clicks as f64 / opens as f64 * 100.0 } else { 0.0 },
+            "clicks_per_thousand": if recipients > 0 { clicks as f64 / recipients as f64 * 1000.0 } else { 0.0 }
+        })
+    }
+
+    #[test]
+    fn url_pivot_totals_reconcile_with_campaign_totals() {
+        let tracking = vec![
+            "https://example.com/offer".to_string(),
+            "https://example.com/landing".to_string(),
+            "https://example.com/unclicked".to_string(),
+        ];
+        let details = vec![
+            ("2025-01-06".to_string(), serde_json::json!({
+                "urls_clicked": [
+                    { "url": "https://example.com/offer", "total_clicks": 40 },
+                    { "url": "https://example.com/landing", "total_clicks": 10 },
+                    { "url": "https://other.com/ignored", "total_clicks": 99 }
+                ]
+            })),
+            ("2025-01-13".to_string(), serde_json::json!({
+                "urls_clicked": [
+                    { "url": "https://example.com/offer", "total_clicks": 25 }
+                ]
+            })),
+        ];
+
+        let rows = pivot_clicks_by_url(&details, &tracking, "exact", true);
+        assert_eq!(rows.len(), 3);
+
+        // Per-URL totals add up to the same clicks the campaign rows report
+        let campaign_total: u64 = details.iter()
+            .map(|(_, data)| count_matched_clicks(data, &tracking, "exact"))
+            .sum();
+        let pivot_total: u64 = rows.iter().map(|r| r.total_clicks).sum();
+        assert_eq!(pivot_total, campaign_total);
+
+        let offer = rows.iter().find(|r| r.url.ends_with("/offer")).unwrap();
+        assert_eq!(offer.total_clicks, 65);
+        assert_eq!(offer.campaigns, vec!["2025-01-06", "2025-01-13"]);
+
+        // The zero-click URL stays as a zero row until the toggle drops it
+        assert!(rows.iter().any(|r| r.url.ends_with("/unclicked") && r.total_clicks == 0));
+        let trimmed = pivot_clicks_by_url(&details, &tracking, "exact", false);
+        assert_eq!(trimmed.len(), 2);
+
+        let csv = url_pivot_csv(&rows, &CsvOptions::default());
+        let lines: Vec<&str> = csv.trim_end().lines().collect();
+        assert_eq!(lines[0], "Tracking URL,Total Clicks,Campaigns");
+        assert_eq!(*lines.last().unwrap(), "Totals,75,");
+    }
+
+    #[test]
+    fn rate_card_prices_exports_with_totals() {
+        // 250 clicks at $0.40/click is $100.00 total spend
+        let (cost, cpc) = campaign_spend(250, 0.40);
+        assert!((cost - 100.0).abs() < 1e-9);
+        assert!((cpc - 0.40).abs() < 1e-9);
+        // A zero-click row must not divide by zero
+        assert_eq!(campaign_spend(0, 0.40), (0.0, 0.0));
+
+        let mut rate_card = HashMap::new();
+        rate_card.insert("NJUA".to_string(), 0.40);
+        assert_eq!(rate_for_advertiser(&rate_card, "NJUA"), Some(0.40));
+        assert_eq!(rate_for_advertiser(&rate_card, "Unlisted"), None);
+
+        let report_data = serde_json::json!({
+            "report_data": [entry("2025-01-06", 100, 200, 1000), entry("2025-01-13", 150, 300, 1000)]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true });
+        let opts = CsvOptions {
+            top_n: Some(10),
+            cost_per_click: Some(0.40),
+            ..Default::default()
+        };
+
+        let csv = build_csv(&report_data, &metrics, &opts).expect("failed to build csv");
+        let lines: Vec<&str> = csv.trim_end().lines().collect();
+        assert_eq!(lines[0], "Date,Total Clicks,Cost,CPC");
+        // top_n re-ranks by clicks, so the bigger campaign comes first
+        assert_eq!(lines[1], "2025-01-13,150,60.00,0.40");
+        assert_eq!(lines[2], "2025-01-06,100,40.00,0.40");
+        assert_eq!(lines[3], "Totals,250,100.00,0.40");
+    }
+
+    #[test]
+    fn mapped_advertiser_exports_to_its_own_folder() {
+        let base = tempfile::tempdir().expect("failed to create temp dir");
+        let global = base.path().join("downloads");
+        let client = base.path().join("clients/acme");
+
+        let mut settings = serde_json::from_value::<Settings>(serde_json::json!({
+            "mailchimp_api_key": "",
+            "mailchimp_audience_id": "",
+            "advertisers": [],
+            "download_directory": global.to_string_lossy()
+        })).unwrap();
+        settings.per_advertiser_dir.insert(
+            "Acme".to_string(),
+            client.to_string_lossy().to_string(),
+        );
+
+        let mapped = resolve_export_dir(&settings, "Acme").expect("resolve failed");
+        assert_eq!(mapped, client);
+        assert!(client.exists());
+
+        let fallback = resolve_export_dir(&settings, "Other").expect("resolve failed");
+        assert_eq!(fallback, global);
+    }
+
+    #[test]
+    fn thousands_separator_only_when_enabled() {
+        let report_data = serde_json::json!({
+            "report_data": [ entry("2025-01-10", 12345, 0, 0) ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true });
+
+        let plain = build_csv(&report_data, &metrics, &CsvOptions::default()).expect("csv failed");
+        assert!(plain.contains("2025-01-10,12345"));
+
+        let opts = CsvOptions { thousands_separator: true, ..Default::default() };
+        let separated = build_csv(&report_data, &metrics, &opts).expect("csv failed");
+        assert!(separated.contains("2025-01-10,\"12,345\""));
+    }
+
+    #[test]
+    fn concurrency_halves_on_rate_limits_and_recovers_additively() {
+        let mut limiter = AimdConcurrency::new(8, 1);
+        assert_eq!(limiter.current(), 8);
+
+        limiter.on_rate_limit();
+        assert_eq!(limiter.current(), 4);
+        limiter.on_rate_limit();
+        assert_eq!(limiter.current(), 2);
+
+        // Three clean batches buy back one step toward the cap
+        limiter.on_success_batch();
+        limiter.on_success_batch();
+        assert_eq!(limiter.current(), 2);
+        limiter.on_success_batch();
+        assert_eq!(limiter.current(), 3);
+
+        // The floor holds no matter how hard the API pushes back
+        let mut floored = AimdConcurrency::new(2, 2);
+        floored.on_rate_limit();
+        floored.on_rate_limit();
+        assert_eq!(floored.current(), 2);
+    }
+
+    #[test]
+    fn text_summary_carries_the_headline_figures() {
+        let mut report = sample_report("r1");
+        report.data = serde_json::json!({
+            "report_data": [
+                entry("2025-01-06", 10, 100, 1000),
+                entry("2025-01-13", 25, 100, 1000),
+                entry("2025-01-20", 5, 100, 1000),
+            ]
+        });
+
+        let summary = text_summary_for(&report);
+
+        assert!(summary.contains("Advertiser: Test Advertiser"));
+        assert!(summary.contains("Period: 2025-01-01 to 2025-01-31"));
+        assert!(summary.contains("Campaigns with clicks: 3"));
+        assert!(summary.contains("Total clicks: 40"));
+        // 40 clicks over 300 unique opens
+        assert!(summary.contains("Overall CTR: 13.33%"));
+        assert!(summary.contains("Top campaign: 2025-01-13 (25 clicks)"));
+    }
+
+    #[test]
+    fn only_transient_failures_qualify_for_auto_retry() {
+        assert!(is_transient_error("Failed to fetch campaigns: connection reset by peer"));
+        assert!(is_transient_error("Mailchimp API error: 429 Too Many Requests"));
+        assert!(is_transient_error("Failed to fetch campaigns: operation timed out"));
+
+        // Validation and auth failures would fail the same way again
+        assert!(!is_transient_error("Mailchimp API settings not configured"));
+        assert!(!is_transient_error("Mailchimp API error: 401 Unauthorized"));
+        assert!(!is_transient_error("Please provide at least one non-empty tracking URL"));
+    }
+
+    #[test]
+    fn z_test_flags_large_ctr_shifts_and_guards_zero_opens() {
+        // 50/1000 vs 100/1000 is a big, well-sampled shift
+        let z = two_proportion_z(50, 1000, 100, 1000);
+        assert!(z < -3.0, "expected strongly negative z, got {}", z);
+
+        // Identical proportions produce z = 0
+        assert!(two_proportion_z(10, 100, 100, 1000).abs() < 1e-9);
+
+        // Zero denominators and degenerate pools collapse to 0 rather than NaN
+        assert_eq!(two_proportion_z(0, 0, 5, 100), 0.0);
+        assert_eq!(two_proportion_z(0, 100, 0, 200), 0.0);
+    }
+
+    #[test]
+    fn variate_winner_resolves_subject_through_combinations() {
+        let campaign = serde_json::json!({
+            "id": "c1",
+            "variate_settings": {
+                "winner_criteria": "opens",
+                "subject_lines": ["Subject A", "Subject B"],
+                "winning_combination_id": "combo-2",
+                "combinations": [
+                    { "id": "combo-1", "subject_line": 0 },
+                    { "id": "combo-2", "subject_line": 1 }
+                ]
+            }
+        });
+
+        let variate = variate_winner(&campaign).expect("variate campaign");
+        assert_eq!(variate["winner_criteria"], "opens");
+        assert_eq!(variate["winning_subject"], "Subject B");
+
+        // Non-variate campaigns carry no field at all
+        assert!(variate_winner(&serde_json::json!({ "id": "c2" })).is_none());
+
+        // A malformed payload still yields the criteria without a winner
+        let partial = serde_json::json!({
+            "variate_settings": { "winner_criteria": "clicks", "subject_lines": ["A", "B"] }
+        });
+        let variate = variate_winner(&partial).expect("variate campaign");
+        assert_eq!(variate["winning_subject"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn audit_flags_duplicate_report_ids() {
+        let rows = serde_json::json!({ "report_data": [entry("2025-01-06", 10, 100, 1000)] });
+        let mut healthy = sample_report("r1");
+        healthy.data = rows.clone();
+        let mut duplicate = sample_report("r1");
+        duplicate.data = rows.clone();
+        let mut other = sample_report("r2");
+        other.data = rows;
+        let reports = vec![healthy, duplicate, other];
+
+        let issues = audit_report_list(&reports);
+
+        // Only the second occurrence of the id is flagged
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].report_id, "r1");
+        assert!(issues[0].issue.contains("Duplicate"));
+    }
+
+    #[test]
+    fn admin_url_column_links_rows_and_tolerates_missing_web_id() {
+        let mut linked = entry("2025-01-06", 10, 100, 1000);
+        linked["admin_url"] = serde_json::json!("https://us1.admin.mailchimp.com/reports/summary?id=123456");
+        let unlinked = entry("2025-01-13", 5, 100, 1000);
+
+        let report_data = serde_json::json!({ "report_data": [linked, unlinked] });
+        let metrics = serde_json::json!({ "total_clicks": true, "admin_url": true });
+
+        let csv = build_csv(&report_data, &metrics, &CsvOptions::default()).expect("csv failed");
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "Date,Total Clicks,Admin URL");
+        assert_eq!(lines[1], "2025-01-06,10,https://us1.admin.mailchimp.com/reports/summary?id=123456");
+        // A campaign without web_id just gets an empty cell
+        assert_eq!(lines[2], "2025-01-13,5,");
+    }
+
+    #[test]
+    fn monthly_split_produces_one_slice_per_active_month() {
+        let data = serde_json::json!({
+            "report_data": [
+                entry("2025-01-06", 10, 100, 1000),
+                entry("2025-01-27", 15, 100, 1000),
+                // February has no sends at all
+                entry("2025-03-03", 20, 100, 1000),
+            ],
+            "metrics": { "total_clicks": true }
+        });
+
+        let slices = month_slices(&data).expect("split failed");
+
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].0, "2025-01");
+        assert_eq!(slices[1].0, "2025-03");
+        assert_eq!(slices[0].1["report_data"].as_array().unwrap().len(), 2);
+        // Each slice carries totals recomputed for just its month
+        assert_eq!(slices[0].1["report_totals"]["total_clicks"], 25);
+        assert_eq!(slices[1].1["report_totals"]["total_clicks"], 20);
+    }
+
+    #[test]
+    fn rename_updates_every_report_with_the_old_name() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let mut legacy = sample_report("r1");
+        legacy.advertiser = "N.J.U.A.".to_string();
+        save_report_to_dir(dir.path(), legacy).expect("save failed");
+
+        let mut second = sample_report("r2");
+        second.advertiser = "N.J.U.A.".to_string();
+        save_report_to_dir(dir.path(), second).expect("save failed");
+
+        let mut other = sample_report("r3");
+        other.advertiser = "Acme".to_string();
+        save_report_to_dir(dir.path(), other).expect("save failed");
+
+        let changed = rename_advertiser_in_dir(dir.path(), "N.J.U.A.", "NJUA", 5)
+            .expect("rename failed");
+        assert_eq!(changed, 2);
+
+        let reports = load_reports_from_dir(dir.path()).expect("load failed");
+        assert!(reports.iter().all(|r| r.advertiser != "N.J.U.A."));
+        assert_eq!(reports.iter().filter(|r| r.advertiser == "NJUA").count(), 2);
+        assert_eq!(reports.iter().filter(|r| r.advertiser == "Acme").count(), 1);
+
+        // The pre-rename state was backed up first
+        let backups: Vec<_> = std::fs::read_dir(dir.path().join("backups"))
+            .expect("backups dir missing")
+            .collect();
+        assert_eq!(backups.len(), 1);
+    }
+
+    #[test]
+    fn api_estimate_counts_one_call_per_campaign_plus_listing() {
+        let estimate = estimate_for_campaigns(12, false);
+        assert_eq!(estimate.matched_campaigns, 12);
+        assert_eq!(estimate.campaign_list_calls, 1);
+        assert_eq!(estimate.click_detail_calls, 12);
+        assert_eq!(estimate.projected_total, 13);
+
+        // Asking for list activity costs one more call
+        let with_activity = estimate_for_campaigns(12, true);
+        assert_eq!(with_activity.projected_total, 14);
+    }
+
+    #[test]
+    fn ctr_threshold_flags_only_underperforming_rows() {
+        let report_data = serde_json::json!({
+            "report_data": [
+                entry("2025-01-06", 2, 200, 1000),   // CTR 1.0
+                entry("2025-01-13", 20, 200, 1000),  // CTR 10.0
+                entry("2025-01-20", 5, 200, 1000),   // CTR 2.5
+            ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true, "ctr": true });
+        let opts = CsvOptions { top_n: Some(10), ctr_alert_threshold: Some(3.0), ..Default::default() };
+
+        let csv = build_csv(&report_data, &metrics, &opts).expect("csv failed");
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "Date,Total Clicks,CTR,Below Threshold");
+        // top_n re-ranks by clicks
+        assert!(lines[1].starts_with("2025-01-13") && lines[1].ends_with("false"));
+        assert!(lines[2].starts_with("2025-01-20") && lines[2].ends_with("true"));
+        assert!(lines[3].starts_with("2025-01-06") && lines[3].ends_with("true"));
+        // The totals row carries the flagged-campaign count
+        assert!(lines[4].starts_with("Totals") && lines[4].ends_with("2"));
+    }
+
+    #[test]
+    fn click_details_csv_writes_raw_rows() {
+        let details = vec![
+            ("c1".to_string(), serde_json::json!({
+                "url": "https://ads.example.com/promo?a=1,b=2",
+                "total_clicks": 40,
+                "unique_clicks": 25
+            })),
+            ("c2".to_string(), serde_json::json!({
+                "url": "https://other.example.com/x",
+                "total_clicks": 7,
+                "unique_clicks": 7
+            })),
+        ];
+
+        let csv = click_details_csv(&details);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "Campaign ID,URL,Total Clicks,Unique Clicks");
+        // The comma in the query string gets the cell quoted
+        assert_eq!(lines[1], "c1,\"https://ads.example.com/promo?a=1,b=2\",40,25");
+        assert_eq!(lines[2], "c2,https://other.example.com/x,7,7");
+    }
+
+    #[test]
+    fn incremental_merge_adds_new_and_replaces_settled_campaigns() {
+        let existing = vec![
+            serde_json::json!({ "campaign_id": "c1", "send_date": "2025-01-06", "total_clicks": 10 }),
+            serde_json::json!({ "campaign_id": "c2", "send_date": "2025-01-13", "total_clicks": 20 }),
+        ];
+        let fresh = vec![
+            serde_json::json!({ "campaign_id": "c2", "send_date": "2025-01-13", "total_clicks": 25 }),
+            serde_json::json!({ "campaign_id": "c3", "send_date": "2025-01-20", "total_clicks": 5 }),
+        ];
+
+        let merged = merge_report_data(&existing, &fresh);
+
+        // Only the genuinely new campaign was added; c2 was re-pulled inside
+        // the settling window, so its newer numbers replace the stored ones
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0]["campaign_id"], "c1");
+        assert_eq!(merged[0]["total_clicks"], 10);
+        assert_eq!(merged[1]["total_clicks"], 25);
+        assert_eq!(merged[2]["campaign_id"], "c3");
+    }
+
+    #[test]
+    fn campaign_row_matches_only_the_advertisers_urls() {
+        let campaign = serde_json::json!({
+            "id": "c1",
+            "send_time": "2025-01-06T09:00:00+00:00",
+            "emails_sent": 1000,
+            "report_summary": { "unique_opens": 200, "opens": 300 }
+        });
+        let click_data = serde_json::json!({
+            "urls_clicked": [
+                { "url": "https://ads.example.com/promo", "total_clicks": 25 },
+                { "url": "https://other.example.com/x", "total_clicks": 40 }
+            ]
+        });
+
+        let row = campaign_report_row(
+            &campaign,
+            &click_data,
+            &["https://ads.example.com".to_string()],
+            "prefix",
+            &[],
+            "us1",
+        ).expect("row should be produced");
+
+        assert_eq!(row["total_clicks"], 25);
+        assert_eq!(row["send_date"], "2025-01-06");
+        assert_eq!(row["campaign_total_clicks"], 65);
+
+        // An advertiser with no matching URL gets no row at all
+        let no_match = campaign_report_row(
+            &campaign,
+            &click_data,
+            &["https://elsewhere.example.com".to_string()],
+            "prefix",
+            &[],
+            "us1",
+        );
+        assert!(no_match.is_none());
+    }
+
+    #[test]
+    fn comma_decimal_under_comma_delimiter_stays_parseable() {
+        let report_data = serde_json::json!({
+            "report_data": [ entry("2025-01-06", 25, 200, 1000) ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true, "ctr": true });
+        let opts = CsvOptions { decimal_separator: ',', ..Default::default() };
+
+        let csv = build_csv(&report_data, &metrics, &opts).expect("csv failed");
+        let row = csv.lines().nth(1).expect("missing data row");
+        assert!(row.contains("\"12,500000\""));
+
+        // The quoted decimal parses back as one cell
+        let cells = parse_csv_line(row);
+        assert_eq!(cells, vec!["2025-01-06", "25", "12,500000"]);
+    }
+
+    #[test]
+    fn reconcile_reports_only_drifted_fields() {
+        let stored = serde_json::json!({
+            "id": "abc123",
+            "emails_sent": 1000,
+            "report_summary": { "unique_opens": 300, "opens": 450 }
+        });
+        let current = serde_json::json!({
+            "id": "abc123",
+            "emails_sent": 1000,
+            "report_summary": { "unique_opens": 310, "opens": 450 }
+        });
+
+        let diffs = campaign_discrepancies(&stored, &current);
+        assert_eq!(diffs, vec![Discrepancy {
+            campaign_id: "abc123".to_string(),
+            field: "unique_opens".to_string(),
+            stored: 300,
+            current: 310,
+        }]);
+    }
+
+    #[test]
+    fn monthly_subtotals_sum_to_the_grand_total() {
+        let rows = vec![
+            entry("2025-01-06", 10, 100, 1000),
+            entry("2025-01-20", 20, 100, 1000),
+            entry("2025-02-03", 30, 100, 1000),
+        ];
+
+        let groups = group_report_rows(&rows, "month");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].get("key").and_then(|k| k.as_str()), Some("2025-01"));
+
+        let subtotal_clicks: u64 = groups.iter()
+            .map(|g| g.get("subtotal").and_then(|s| s.get("total_clicks")).and_then(|v| v.as_u64()).unwrap_or(0))
+            .sum();
+        let grand_total = compute_totals(&rows);
+        assert_eq!(Some(subtotal_clicks), grand_total.get("total_clicks").and_then(|v| v.as_u64()));
+
+        // The CSV interleaves subtotal rows and ends on the grand total
+        let report_data = serde_json::json!({ "report_data": rows });
+        let metrics = serde_json::json!({ "total_clicks": true });
+        let opts = CsvOptions { group_by: Some("month".to_string()), ..Default::default() };
+        let csv = build_csv(&report_data, &metrics, &opts).expect("csv failed");
+        assert!(csv.contains("Subtotal 2025-01,30\n"));
+        assert!(csv.contains("Subtotal 2025-02,30\n"));
+        assert!(csv.ends_with("Totals,60\n"));
+    }
+
+    #[test]
+    fn checksum_verification_catches_a_byte_flip() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let export = dir.path().join("report.csv");
+        std::fs::write(&export, "Date,Total Clicks\n2025-01-06,10\n").unwrap();
+
+        let hash = write_checksum_sidecar(&export).expect("sidecar failed");
+        let sidecar = dir.path().join("report.csv.sha256");
+        assert!(std::fs::read_to_string(&sidecar).unwrap().starts_with(&hash));
+        assert_eq!(file_sha256(&export).unwrap(), hash);
+
+        std::fs::write(&export, "Date,Total Clicks\n2025-01-06,11\n").unwrap();
+        assert_ne!(file_sha256(&export).unwrap(), hash);
+    }
+
+    #[test]
+    fn list_activity_sums_only_days_inside_the_window() {
+        let activity = serde_json::json!({
+            "activity": [
+                { "day": "2025-01-05", "subs": 10, "unsubs": 2 },
+                { "day": "2025-01-10", "subs": 5, "unsubs": 8 },
+                { "day": "2025-02-01", "subs": 100, "unsubs": 0 }
+            ]
+        });
+
+        let summary = sum_list_activity(&activity, "2025-01-01", "2025-01-31");
+        assert_eq!(summary.get("subscribes").and_then(|v| v.as_i64()), Some(15));
+        assert_eq!(summary.get("unsubscribes").and_then(|v| v.as_i64()), Some(10));
+        assert_eq!(summary.get("net").and_then(|v| v.as_i64()), Some(5));
+    }
+
+    #[test]
+    fn tagging_untagging_and_querying_by_tag() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        save_report_to_dir(dir.path(), sample_report("one")).expect("save failed");
+        save_report_to_dir(dir.path(), sample_report("two")).expect("save failed");
+
+        add_report_tag_in_dir(dir.path(), "one", "Q1").expect("tag failed");
+        // Case-insensitive duplicate is a no-op
+        let tagged = add_report_tag_in_dir(dir.path(), "one", "q1").expect("tag failed");
+        assert_eq!(tagged.tags, vec!["Q1".to_string()]);
+
+        add_report_tag_in_dir(dir.path(), "two", "final").expect("tag failed");
+
+        let reports = load_reports_from_dir(dir.path()).expect("load failed");
+        let by_tag: Vec<&SavedReport> = reports.iter()
+            .filter(|r| r.tags.iter().any(|t| t.eq_ignore_ascii_case("q1")))
+            .collect();
+        assert_eq!(by_tag.len(), 1);
+        assert_eq!(by_tag[0].id, "one");
+
+        let untagged = remove_report_tag_in_dir(dir.path(), "one", "q1").expect("untag failed");
+        assert!(untagged.tags.is_empty());
+    }
+
+    #[test]
+    fn compact_mode_drops_all_zero_columns() {
+        let report_data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "total_clicks": 10, "total_opens": 0 },
+                { "send_date": "2025-01-13", "total_clicks": 20, "total_opens": 0 }
+            ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true, "total_opens": true });
+
+        let full = build_csv(&report_data, &metrics, &CsvOptions::default()).expect("csv failed");
+        assert!(full.starts_with("Date,Total Opens,Total Clicks\n"));
+
+        let opts = CsvOptions { compact: true, ..Default::default() };
+        let compacted = build_csv(&report_data, &metrics, &opts).expect("csv failed");
+        assert!(compacted.starts_with("Date,Total Clicks\n"));
+        assert!(compacted.contains("2025-01-06,10\n"));
+    }
+
+    #[test]
+    fn campaign_csv_parses_and_merges_click_rows() {
+        let csv = "Title,Send Date,Unique Opens,Total Opens,Total Recipients,Clicked URL,URL Clicks\n\
+            AM Newsletter,2025-01-06,3100,4200,9800,https://example.com/ad,25\n\
+            AM Newsletter,2025-01-06,3100,4200,9800,https://example.com/other,75\n\
+            PM Newsletter,2025-01-06,2000,2500,9000,https://example.com/ad,10\n";
+
+        let campaigns = parse_campaign_csv(csv).expect("parse failed");
+        assert_eq!(campaigns.len(), 2);
+
+        let tracking = vec!["https://example.com/ad".to_string()];
+        assert_eq!(count_matched_clicks(&campaigns[0], &tracking, "prefix"), 25);
+        assert_eq!(count_total_clicks(&campaigns[0]), 100);
+    }
+
+    #[test]
+    fn campaign_csv_with_missing_columns_errors_clearly() {
+        let csv = "Title,Send Date\nAM Newsletter,2025-01-06\n";
+        let err = parse_campaign_csv(csv).unwrap_err();
+        assert!(err.contains("unique_opens"));
+        assert!(err.contains("Expected columns"));
+    }
+
+    #[test]
+    fn lowering_max_backups_prunes_older_backups() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        std::fs::write(dir.path().join("reports.json"), "[]").unwrap();
+
+        for _ in 0..4 {
+            back_up_reports_file(dir.path(), 3).expect("backup failed");
+        }
+        let count = std::fs::read_dir(dir.path().join("backups")).unwrap().count();
+        assert_eq!(count, 3);
+
+        // Reducing the retention prunes the excess on the next write
+        back_up_reports_file(dir.path(), 1).expect("backup failed");
+        let count = std::fs::read_dir(dir.path().join("backups")).unwrap().count();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn custom_metric_computes_a_weighted_score() {
+        let report_data = serde_json::json!({
+            "report_data": [ entry("2025-01-10", 10, 100, 1000) ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true });
+        let opts = CsvOptions {
+            custom_metrics: vec![CustomMetric {
+                name: "Engagement Score".to_string(),
+                expression: "opens*0.3 + clicks*0.7".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let csv = build_csv(&report_data, &metrics, &opts).expect("csv failed");
+        assert!(csv.starts_with("Date,Total Clicks,Engagement Score\n"));
+        assert!(csv.contains("2025-01-10,10,37.00"));
+    }
+
+    #[test]
+    fn custom_metric_with_unknown_variable_is_rejected() {
+        let metric = CustomMetric {
+            name: "Bad".to_string(),
+            expression: "opens + bounces".to_string(),
+        };
+        let err = validate_custom_metric(&metric).unwrap_err();
+        assert!(err.contains("bounces"));
+
+        let good = CustomMetric {
+            name: "Good".to_string(),
+            expression: "clicks / recipients * 1000".to_string(),
+        };
+        assert!(validate_custom_metric(&good).is_ok());
+    }
+
+    #[test]
+    fn overlap_detection_for_adjacent_overlapping_and_disjoint_ranges() {
+        let range = |start: &str, end: &str| DateRange {
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+        };
+        let january = range("2025-01-01", "2025-01-31");
+
+        // Overlapping by a single shared day
+        assert!(ranges_overlap(&january, &range("2025-01-31", "2025-02-15")).unwrap());
+        // Adjacent: starts the day after January ends
+        assert!(!ranges_overlap(&january, &range("2025-02-01", "2025-02-15")).unwrap());
+        // Disjoint
+        assert!(!ranges_overlap(&january, &range("2025-03-01", "2025-03-31")).unwrap());
+        // Fully contained
+        assert!(ranges_overlap(&january, &range("2025-01-10", "2025-01-12")).unwrap());
+    }
+
+    #[test]
+    fn campaigns_without_archive_urls_are_skipped() {
+        let data = serde_json::json!({
+            "campaigns": [
+                {
+                    "settings": { "title": "AM Newsletter" },
+                    "archive_url": "https://example.us21.list-manage.com/abc"
+                },
+                { "settings": { "title": "Draft" } }
+            ]
+        });
+
+        let sections = campaign_qr_sections(&data);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, "AM Newsletter");
+        assert!(sections[0].svg.contains("<svg"));
+    }
+
+    #[test]
+    fn weekly_buckets_are_continuous_with_zero_gaps() {
+        let points = vec![
+            (chrono::NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(), 10, 100),
+            (chrono::NaiveDate::from_ymd_opt(2025, 1, 20).unwrap(), 30, 100),
+        ];
+        let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let end = chrono::NaiveDate::from_ymd_opt(2025, 1, 26).unwrap();
+
+        let series = bucket_weekly_ctr(&points, start, end);
+
+        assert_eq!(series.len(), 3);
+        assert_eq!(series[0].week, "2025-W02");
+        assert_eq!(series[0].total_clicks, 10);
+        // The week in between had no campaigns but still gets a point
+        assert_eq!(series[1], WeeklyCtr {
+            week: "2025-W03".to_string(),
+            total_clicks: 0,
+            unique_opens: 0,
+            ctr: 0.0,
+        });
+        assert_eq!(series[2].ctr, 30.0);
+    }
+
+    #[test]
+    fn all_empty_tracking_urls_are_rejected() {
+        let urls = vec!["".to_string(), "   ".to_string()];
+        let err = validate_tracking_urls(&urls).unwrap_err();
+        assert!(err.contains("at least one non-empty tracking URL"));
+    }
+
+    #[test]
+    fn empty_entries_are_skipped_when_a_real_url_exists() {
+        let urls = vec!["".to_string(), "https://example.com/ad".to_string()];
+        let deduped = validate_tracking_urls(&urls).expect("validation failed");
+        assert_eq!(deduped, vec!["https://example.com/ad".to_string()]);
+    }
+
+    #[test]
+    fn duplicate_tracking_urls_count_once() {
+        let urls = vec![
+            "https://example.com/ad".to_string(),
+            "https://example.com/ad/".to_string(),
+        ];
+        let deduped = validate_tracking_urls(&urls).expect("validation failed");
+        assert_eq!(deduped.len(), 1);
+
+        let click_data = serde_json::json!({
+            "urls_clicked": [
+                { "url": "https://example.com/ad", "total_clicks": 10 }
+            ]
+        });
+        assert_eq!(count_matched_clicks(&click_data, &deduped, "prefix"), 10);
+    }
+
+    #[test]
+    fn slicing_a_quarter_down_to_one_month() {
+        let data = serde_json::json!({
+            "report_data": [
+                entry("2025-01-10", 10, 100, 1000),
+                entry("2025-02-05", 20, 200, 2000),
+                entry("2025-02-20", 30, 300, 3000),
+                entry("2025-03-15", 40, 400, 4000)
+            ],
+            "metrics": { "total_clicks": true }
+        });
+
+        let sliced = slice_report_data(&data, "2025-02-01", "2025-02-28").expect("slice failed");
+
+        let rows = sliced.get("report_data").and_then(|d| d.as_array()).unwrap();
+        assert_eq!(rows.len(), 2);
+        let totals = sliced.get("report_totals").unwrap();
+        assert_eq!(totals.get("total_clicks").and_then(|v| v.as_u64()), Some(50));
+        assert_eq!(totals.get("total_recipients").and_then(|v| v.as_u64()), Some(5000));
+    }
+
+    #[test]
+    fn diagnostics_are_capped_and_redacted() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        for i in 0..(DIAGNOSTICS_CAP + 5) {
+            record_diagnostic(
+                dir.path(),
+                "generate_report",
+                &format!("run {}", i),
+                "key 0123456789abcdef0123456789abcdef-us21 was rejected",
+            );
+        }
+
+        let entries: Vec<serde_json::Value> = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("diagnostics.json")).unwrap(),
+        ).unwrap();
+
+        assert_eq!(entries.len(), DIAGNOSTICS_CAP);
+        // The oldest entries were dropped
+        assert_eq!(entries[0].get("request").and_then(|v| v.as_str()), Some("run 5"));
+        let error = entries[0].get("error").and_then(|v| v.as_str()).unwrap();
+        assert_eq!(error, "key [redacted] was rejected");
+    }
+
+    #[test]
+    fn share_of_clicks_is_the_matched_fraction() {
+        let click_data = serde_json::json!({
+            "urls_clicked": [
+                { "url": "https://example.com/ad", "total_clicks": 30 },
+                { "url": "https://example.com/other", "total_clicks": 70 }
+            ]
+        });
+        let tracking = vec!["https://example.com/ad".to_string()];
+
+        let matched = count_matched_clicks(&click_data, &tracking, "prefix");
+        let total = count_total_clicks(&click_data);
+        assert_eq!(matched, 30);
+        assert_eq!(total, 100);
+        assert_eq!(share_of_clicks(matched, total), 30.0);
+        assert_eq!(share_of_clicks(5, 0), 0.0);
+    }
+
+    #[test]
+    fn tags_column_joins_and_escapes() {
+        let report_data = serde_json::json!({
+            "report_data": [
+                {
+                    "send_date": "01/05/25",
+                    "total_clicks": 3,
+                    "tags": ["sponsored, Q1", "newsletter"]
+                }
+            ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true, "tags": true });
+
+        let csv = build_csv(&report_data, &metrics, &CsvOptions::default()).expect("csv failed");
+        assert!(csv.starts_with("Date,Total Clicks,Tags\n"));
+        assert!(csv.contains("3,\"sponsored, Q1; newsletter\""));
+    }
+
+    #[test]
+    fn hex_color_validation() {
+        assert!(is_valid_hex_color("#1a1a2e"));
+        assert!(is_valid_hex_color("#fff"));
+        assert!(!is_valid_hex_color("1a1a2e"));
+        assert!(!is_valid_hex_color("#12345"));
+        assert!(!is_valid_hex_color("#gggggg"));
+    }
+
+    #[test]
+    fn custom_theme_with_bad_colors_falls_back_to_light() {
+        let mut theme = export_theme_preset("dark").unwrap();
+        theme.name = "custom".to_string();
+        theme.header_color = "not-a-color".to_string();
+
+        let mut settings = serde_json::from_value::<Settings>(serde_json::json!({
+            "mailchimp_api_key": "",
+            "mailchimp_audience_id": "",
+            "advertisers": [],
+            "download_directory": ""
+        })).unwrap();
+        settings.export_theme = theme;
+
+        assert_eq!(resolve_export_theme(&settings), default_export_theme());
+    }
+
+    #[test]
+    fn campaigns_url_includes_folder_only_when_set() {
+        let base = "https://us1.api.mailchimp.com/3.0";
+        let plain = build_campaigns_url(base, "2025-01-01T00:00:00Z", "2025-01-31T23:59:59Z", None);
+        assert!(!plain.contains("folder_id"));
+
+        let scoped = build_campaigns_url(base, "2025-01-01T00:00:00Z", "2025-01-31T23:59:59Z", Some("abc123"));
+        assert!(scoped.contains("&folder_id=abc123"));
+    }
+
+    #[test]
+    fn top_n_limits_rows_but_totals_cover_everything() {
+        let report_data = serde_json::json!({
+            "report_data": [
+                entry("2025-01-01", 5, 100, 1000),
+                entry("2025-01-02", 50, 100, 1000),
+                entry("2025-01-03", 20, 100, 1000),
+                entry("2025-01-04", 30, 100, 1000),
+                entry("2025-01-05", 10, 100, 1000),
+            ]
+        });
+        let metrics = serde_json::json!({ "total_clicks": true });
+
+        let opts = CsvOptions { top_n: Some(3), ..Default::default() };
+        let csv = build_csv(&report_data, &metrics, &opts).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        // Header, three top rows, totals row
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "2025-01-02,50");
+        assert_eq!(lines[2], "2025-01-04,30");
+        assert_eq!(lines[3], "2025-01-03,20");
+        // Totals reflect all five campaigns, not just the three shown
+        assert_eq!(lines[4], "Totals,115");
+    }
+
+    #[test]
+    fn migrating_a_legacy_file_fills_missing_fields() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        // A legacy-shaped entry with no metrics and no date_range
+        let legacy = serde_json::json!([{
+            "id": "report-old",
+            "name": "Old Report",
+            "advertiser": "NJUA",
+            "report_type": "AM",
+            "created": "2024-01-01",
+            "data": {}
+        }]);
+        fs::write(dir.path().join("reports.json"), serde_json::to_string_pretty(&legacy).unwrap()).unwrap();
+
+        let summary = migrate_reports_in_dir(dir.path(), default_max_backups()).expect("migration failed");
+        assert_eq!(summary.total, 1);
+        assert_eq!(summary.upgraded, 1);
+        assert!(std::path::Path::new(&summary.backup_path).exists());
+
+        // The rewritten file now parses strictly
+        let rewritten = fs::read_to_string(dir.path().join("reports.json")).unwrap();
+        let strict: Vec<SavedReport> = serde_json::from_str(&rewritten).expect("strict parse failed");
+        assert_eq!(strict[0].id, "report-old");
+
+        // A second migration finds nothing left to upgrade
+        let second = migrate_reports_in_dir(dir.path(), default_max_backups()).expect("second migration failed");
+        assert_eq!(second.upgraded, 0);
+    }
+
+    #[test]
+    fn concurrent_saves_keep_both_reports() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let dir_a = dir.path().to_path_buf();
+        let dir_b = dir.path().to_path_buf();
+        let t1 = std::thread::spawn(move || save_report_to_dir(&dir_a, sample_report("report-1")));
+        let t2 = std::thread::spawn(move || save_report_to_dir(&dir_b, sample_report("report-2")));
+        t1.join().expect("save thread panicked").expect("first save failed");
+        t2.join().expect("save thread panicked").expect("second save failed");
+
+        let reports = load_reports_from_dir(dir.path()).expect("failed to load reports");
+        assert_eq!(reports.len(), 2);
+        let mut ids: Vec<&str> = reports.iter().map(|r| r.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["report-1", "report-2"]);
+    }
+
+    #[test]
+    fn dedupe_advertisers_keeps_first_spelling() {
+        let advertisers = vec![
+            "NJ Bankers".to_string(),
+            "  nj   bankers ".to_string(),
+            "NJUA".to_string(),
+            "N.J.U.A.".to_string(),
+            "NJ BANKERS".to_string(),
+            "   ".to_string(),
+        ];
+        let mut merges = HashMap::new();
+        merges.insert("N.J.U.A.".to_string(), "NJUA".to_string());
+
+        let cleaned = dedupe_advertiser_list(&advertisers, &merges);
+        assert_eq!(cleaned, vec!["NJ Bankers", "NJUA"]);
+
+        // Without the merge map the dotted spelling survives as its own entry
+        let unmerged = dedupe_advertiser_list(&advertisers, &HashMap::new());
+        assert_eq!(unmerged, vec!["NJ Bankers", "NJUA", "N.J.U.A."]);
+    }
+
+    #[test]
+    fn profiles_snapshot_switch_and_persist() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let client_a = serde_json::json!({
+            "mailchimp_api_key": "key-a-us1",
+            "mailchimp_audience_id": "aud-a",
+            "advertisers": ["Client A"],
+            "download_directory": "/tmp/client-a"
+        });
+        fs::write(dir.path().join("settings.json"), client_a.to_string()).unwrap();
+        save_profile_in_dir(dir.path(), "client-a").expect("first snapshot failed");
+
+        let client_b = serde_json::json!({
+            "mailchimp_api_key": "key-b-us1",
+            "mailchimp_audience_id": "aud-b",
+            "advertisers": ["Client B"],
+            "download_directory": "/tmp/client-b"
+        });
+        fs::write(dir.path().join("settings.json"), client_b.to_string()).unwrap();
+        save_profile_in_dir(dir.path(), "client-b").expect("second snapshot failed");
+
+        assert_eq!(list_profiles_in_dir(dir.path()).unwrap(), vec!["client-a", "client-b"]);
+        assert_eq!(active_profile_in_dir(dir.path()), Some("client-b".to_string()));
+
+        // Switching rewrites the live settings and the marker survives a
+        // "restart" (nothing but the files carries the state)
+        let switched = load_profile_in_dir(dir.path(), "client-a").expect("switch failed");
+        assert_eq!(switched.mailchimp_api_key, "key-a-us1");
+        let on_disk = fs::read_to_string(dir.path().join("settings.json")).unwrap();
+        assert!(on_disk.contains("key-a-us1"));
+        assert_eq!(active_profile_in_dir(dir.path()), Some("client-a".to_string()));
+
+        // The active profile can't be deleted out from under the app
+        assert!(delete_profile_in_dir(dir.path(), "client-a").is_err());
+        delete_profile_in_dir(dir.path(), "client-b").expect("delete failed");
+        assert_eq!(list_profiles_in_dir(dir.path()).unwrap(), vec!["client-a"]);
+
+        // Names that would escape the profiles directory are rejected
+        assert!(validate_profile_name("../evil").is_err());
+        assert!(validate_profile_name("   ").is_err());
+    }
+
+    #[test]
+    fn shared_reports_are_listed_but_not_deletable() {
+        let local = tempfile::tempdir().expect("failed to create temp dir");
+        let shared = tempfile::tempdir().expect("failed to create temp dir");
+
+        save_report_to_dir(local.path(), sample_report("report-local")).expect("local save failed");
+        save_report_to_dir(shared.path(), sample_report("report-shared")).expect("shared save failed");
+
+        let merged = merge_shared_reports(
+            load_reports_from_dir(local.path()).expect("failed to load local reports"),
+            load_reports_from_dir(shared.path()).expect("failed to load shared reports"),
+        );
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().find(|r| r.id == "report-shared").unwrap().read_only);
+        assert!(!merged.iter().find(|r| r.id == "report-local").unwrap().read_only);
+
+        // Deleting the shared report locally is refused with a pointed error
+        let err = delete_report_from_dirs(local.path(), Some(shared.path()), "report-shared", default_trash_size())
+            .expect_err("deleting a shared report should fail");
+        assert!(err.contains("read-only"));
+        assert_eq!(load_reports_from_dir(shared.path()).unwrap().len(), 1);
+
+        // Local deletes still work with a shared directory configured
+        delete_report_from_dirs(local.path(), Some(shared.path()), "report-local", default_trash_size())
+            .expect("local delete failed");
+        assert!(load_reports_from_dir(local.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn next_fire_holds_local_clock_time_across_dst() {
+        let schedule = ReportSchedule {
+            weekday: "Monday".to_string(),
+            time: "08:00".to_string(),
+            timezone: "America/New_York".to_string(),
+        };
+        let after = |s: &str| chrono::DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&chrono::Utc);
+
+        // Normal week: Wednesday rolls forward to the next Monday, 8am EST
+        let fire = next_fire_time(&schedule, after("2025-01-08T12:00:00Z")).unwrap();
+        assert_eq!(fire.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), "2025-01-13T13:00:00Z");
+        // Exactly one week to the one after, and strictly later than "after"
+        let next = next_fire_time(&schedule, fire).unwrap();
+        assert_eq!(next - fire, chrono::Duration::days(7));
+
+        // Across the 2025-03-09 spring-forward: still 8am on the wall
+        // clock, so the UTC offset shifts from -5 to -4 without drifting
+        let fire = next_fire_time(&schedule, after("2025-03-07T12:00:00Z")).unwrap();
+        assert_eq!(fire.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), "2025-03-10T12:00:00Z");
+
+        // A time inside the spring-forward gap fires once the gap ends
+        let gap = ReportSchedule {
+            weekday: "Sunday".to_string(),
+            time: "02:30".to_string(),
+            timezone: "America/New_York".to_string(),
+        };
+        let fire = next_fire_time(&gap, after("2025-03-08T12:00:00Z")).unwrap();
+        assert_eq!(fire.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), "2025-03-09T07:00:00Z");
+
+        // A fall-back repeat (2025-11-02) fires at its first occurrence only
+        let repeat = ReportSchedule {
+            weekday: "Sunday".to_string(),
+            time: "01:30".to_string(),
+            timezone: "America/New_York".to_string(),
+        };
+        let fire = next_fire_time(&repeat, after("2025-11-01T12:00:00Z")).unwrap();
+        assert_eq!(fire.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), "2025-11-02T05:30:00Z");
+        let next = next_fire_time(&repeat, fire).unwrap();
+        // The second 01:30 that morning is skipped; next fire is next week
+        assert_eq!(next.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), "2025-11-09T06:30:00Z");
+
+        assert!(next_fire_time(&ReportSchedule {
+            weekday: "Someday".to_string(),
+            time: "08:00".to_string(),
+            timezone: "America/New_York".to_string(),
+        }, after("2025-01-08T12:00:00Z")).unwrap_err().contains("Unknown weekday"));
+    }
+
+    #[test]
+    fn debug_captures_write_and_list_per_run() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+
+        let run_dir = debug_capture_dir(dir.path(), "20250106_090000");
+        write_debug_capture(&run_dir, "campaigns", &serde_json::json!([{ "id": "c1" }]));
+        write_debug_capture(&run_dir, "click_details_c1", &serde_json::json!({ "urls_clicked": [] }));
+
+        let campaigns = std::fs::read_to_string(run_dir.join("campaigns.json"))
+            .expect("failed to read capture");
+        assert!(campaigns.contains("\"c1\""));
+        assert!(run_dir.join("click_details_c1.json").exists());
+
+        // Listing returns one entry per captured run, oldest first
+        let later = debug_capture_dir(dir.path(), "20250107_090000");
+        write_debug_capture(&later, "campaigns", &serde_json::json!([]));
+        assert_eq!(
+            list_debug_captures_in_dir(dir.path()),
+            vec!["20250106_090000".to_string(), "20250107_090000".to_string()]
+        );
+
+        // No captures is an empty list, not an error
+        let empty = tempfile::tempdir().expect("failed to create temp dir");
+        assert!(list_debug_captures_in_dir(empty.path()).is_empty());
+    }
+
+    #[test]
+    fn benchmark_ranks_the_advertiser_against_the_portfolio() {
+        let seeded = |id: &str, advertiser: &str, opens: u64, clicks: u64| {
+            let mut report = sample_report(id);
+            report.advertiser = advertiser.to_string();
+            report.data = serde_json::json!({
+                "report_data": [
+                    { "send_date": "2025-01-06", "unique_opens": opens, "total_clicks": clicks }
+                ]
+            });
+            report
+        };
+        let reports = vec![
+            seeded("report-b1", "Acme", 100, 30),     // 30% CTR
+            seeded("report-b2", "Globex", 100, 10),   // 10% CTR
+            seeded("report-b3", "Initech", 100, 20),  // 20% CTR
+        ];
+
+        let benchmark = compute_benchmark(&reports, "Acme", "2025-01-01", "2025-01-31")
+            .expect("failed to compute benchmark");
+        assert!((benchmark.advertiser_ctr - 30.0).abs() < 1e-9);
+        assert_eq!(benchmark.advertiser_clicks, 30);
+        assert!((benchmark.portfolio_avg_ctr - 20.0).abs() < 1e-9);
+        assert!((benchmark.portfolio_avg_clicks - 20.0).abs() < 1e-9);
+        // Acme beats both of the others
+        assert_eq!(benchmark.percentile, Some(100.0));
+        assert!(benchmark.note.is_none());
+
+        // With one other advertiser there's no meaningful rank, just a note
+        let thin = compute_benchmark(&reports[..2], "Acme", "2025-01-01", "2025-01-31")
+            .expect("failed to compute benchmark");
+        assert!(thin.percentile.is_none());
+        assert!(thin.note.as_deref().unwrap_or("").contains("too few"));
+
+        // Reports outside the period don't count at all
+        let err = compute_benchmark(&reports, "Acme", "2025-03-01", "2025-03-31").unwrap_err();
+        assert!(err.contains("No reports found"));
+    }
+
+    #[test]
+    fn tracking_disabled_campaigns_are_flagged_not_zeroed() {
+        let disabled = serde_json::json!({ "tracking": { "html_clicks": false, "clicks": false } });
+        assert!(click_tracking_disabled(&disabled));
+        // Missing or partial tracking settings mean tracking is on
+        assert!(!click_tracking_disabled(&serde_json::json!({})));
+        assert!(!click_tracking_disabled(&serde_json::json!({ "tracking": { "html_clicks": true, "clicks": false } })));
+
+        // A flagged campaign stays in the row set even with zero clicks
+        let campaign = serde_json::json!({
+            "id": "c1",
+            "send_time": "2025-01-06T09:00:00+00:00",
+            "emails_sent": 500,
+            "report_summary": { "unique_opens": 400 },
+            "tracking": { "html_clicks": false, "clicks": false },
+        });
+        let row = campaign_report_row(&campaign, &serde_json::json!({}), &["https://example.com/ad".to_string()], "prefix", &[], "us1")
+            .expect("tracking-disabled campaign should still produce a row");
+        assert_eq!(row["tracking_disabled"], true);
+        assert_eq!(row["total_clicks"], 0);
+
+        // And it drops out of the CTR denominator in the totals
+        let entries = vec![
+            serde_json::json!({ "send_date": "2025-01-06", "unique_opens": 100, "total_clicks": 30 }),
+            serde_json::json!({ "send_date": "2025-01-13", "unique_opens": 400, "total_clicks": 0, "tracking_disabled": true }),
+        ];
+        let totals = compute_totals(&entries);
+        assert_eq!(totals["total_clicks"], 30);
+        assert!((totals["ctr"].as_f64().unwrap() - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reexport_writes_each_report_and_collects_failures() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let mut first = sample_report("report-r1");
+        first.data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "unique_opens": 100, "total_opens": 120, "total_recipients": 500, "total_clicks": 30, "ctr": 30.0 }
+            ]
+        });
+        let mut second = sample_report("report-r2");
+        second.advertiser = "Globex".to_string();
+
+        let ids = vec!["report-r1".to_string(), "report-r2".to_string(), "report-gone".to_string()];
+        let (written, errors) = reexport_to_dir(
+            &[first, second], &ids, "csv", dir.path(), &CsvOptions::default(), &HashMap::new(),
+        );
+
+        assert_eq!(written.len(), 2);
+        for path in &written {
+            let content = std::fs::read_to_string(path).expect("failed to read export");
+            assert!(content.starts_with("Date,"));
+        }
+        assert!(written[0].contains("Test_Advertiser"));
+        assert!(written[1].contains("Globex"));
+
+        // The missing report is an error, not a stopper
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("report-gone"));
+    }
+
+    #[test]
+    fn preview_token_pins_the_exact_campaign_set() {
+        let campaigns = vec![
+            serde_json::json!({ "id": "c1", "settings": { "title": "NJUA Weekly" } }),
+            serde_json::json!({ "id": "c2", "settings": { "title": "NJUA Monthly" } }),
+        ];
+        let issued = chrono::Utc::now();
+        let mut tokens = Vec::new();
+
+        let token = issue_preview_token(&mut tokens, "NJUA", campaigns.clone(), issued);
+        let redeemed = redeem_preview_token(&mut tokens, &token, issued + chrono::Duration::minutes(1))
+            .expect("failed to redeem token");
+        assert_eq!(redeemed, campaigns);
+
+        // Single-use: a second confirm is refused
+        let reuse = redeem_preview_token(&mut tokens, &token, issued).unwrap_err();
+        assert!(reuse.contains("already used"));
+
+        // Expired tokens are refused too
+        let stale = issue_preview_token(&mut tokens, "NJUA", campaigns, issued);
+        let expired = redeem_preview_token(&mut tokens, &stale, issued + chrono::Duration::minutes(16)).unwrap_err();
+        assert!(expired.contains("expired"));
+    }
+
+    #[test]
+    fn click_contributions_sum_to_one_hundred_percent() {
+        let data = vec![
+            serde_json::json!({ "send_date": "2025-01-06", "total_clicks": 30 }),
+            serde_json::json!({ "send_date": "2025-01-13", "total_clicks": 50 }),
+            serde_json::json!({ "send_date": "2025-01-20", "total_clicks": 20 }),
+        ];
+
+        let rows = compute_click_contribution(&data);
+
+        // Biggest slice first
+        assert_eq!(rows[0].send_date, "2025-01-13");
+        assert!((rows[0].contribution - 50.0).abs() < 1e-9);
+        let sum: f64 = rows.iter().map(|r| r.contribution).sum();
+        assert!((sum - 100.0).abs() < 1e-9);
+
+        // A report with no clicks yields zeros, not NaN
+        let empty = compute_click_contribution(&[serde_json::json!({ "send_date": "2025-02-03", "total_clicks": 0 })]);
+        assert_eq!(empty[0].contribution, 0.0);
+    }
+
+    #[test]
+    fn document_metadata_rides_in_the_html_head() {
+        let mut report = sample_report("report-meta");
+        report.advertiser = "Acme & Co".to_string();
+
+        let html = standalone_html(&report, &default_export_theme(), "ROI Reporting Desk")
+            .expect("failed to render html");
+
+        assert!(html.contains("<meta name=\"author\" content=\"ROI Reporting Desk\">"));
+        assert!(html.contains("<meta name=\"keywords\""));
+        assert!(html.contains("Acme &amp; Co"));
+
+        // No configured author, no author tag
+        let anonymous = standalone_html(&report, &default_export_theme(), "")
+            .expect("failed to render html");
+        assert!(!anonymous.contains("<meta name=\"author\""));
+    }
+
+    #[test]
+    fn funnel_percentages_chain_stage_by_stage() {
+        let data = vec![
+            serde_json::json!({ "send_date": "2025-01-06", "total_recipients": 1000, "unique_opens": 200, "total_clicks": 50 }),
+            serde_json::json!({ "send_date": "2025-01-13", "total_recipients": 500, "unique_opens": 0, "total_clicks": 0 }),
+        ];
+
+        let funnel = compute_funnel(&data);
+
+        assert_eq!(funnel.len(), 3);
+        assert_eq!(funnel[0].label, "2025-01-06");
+        assert!((funnel[0].open_rate - 20.0).abs() < 1e-9);
+        assert!((funnel[0].click_to_open_rate - 25.0).abs() < 1e-9);
+
+        // No opens means both downstream rates stay at zero
+        assert_eq!(funnel[1].open_rate, 0.0);
+        assert_eq!(funnel[1].click_to_open_rate, 0.0);
+
+        // The aggregate chains from the summed counts, not averaged rates
+        assert_eq!(funnel[2].label, "All campaigns");
+        assert_eq!(funnel[2].recipients, 1500);
+        assert!((funnel[2].open_rate - (200.0 / 1500.0) * 100.0).abs() < 1e-9);
+        assert!((funnel[2].click_to_open_rate - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rounding_mode_decides_the_half_boundary() {
+        assert!((round_rate(2.345, 2, "half_up") - 2.35).abs() < 1e-9);
+        assert!((round_rate(2.345, 2, "half_even") - 2.34).abs() < 1e-9);
+        // 5 is odd, so half_even rounds this one up
+        assert!((round_rate(2.355, 2, "half_even") - 2.36).abs() < 1e-9);
+        // Off the boundary, both modes agree
+        assert!((round_rate(2.346, 2, "half_even") - 2.35).abs() < 1e-9);
+        assert!((round_rate(2.344, 2, "half_up") - 2.34).abs() < 1e-9);
+
+        let half_up = CsvOptions { rounding_mode: "half_up".to_string(), ..Default::default() };
+        assert_eq!(format_decimal(2.345, 2, &half_up), "2.35");
+        assert_eq!(format_decimal(2.345, 2, &CsvOptions::default()), "2.34");
+    }
+
+    #[test]
+    fn manifest_lists_one_row_per_advertiser_report() {
+        let mut first = sample_report("report-m1");
+        first.advertiser = "Acme".to_string();
+        first.created = "2025-02-01".to_string();
+        first.data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "unique_opens": 100, "total_clicks": 30 },
+                { "send_date": "2025-01-13", "unique_opens": 200, "total_clicks": 20 }
+            ]
+        });
+        let mut second = sample_report("report-m2");
+        second.advertiser = "Acme".to_string();
+        second.created = "2025-01-01".to_string();
+        second.campaign_count = Some(5);
+        let mut other = sample_report("report-m3");
+        other.advertiser = "Globex".to_string();
+
+        let rows = advertiser_manifest(&[first, second, other], "Acme");
+
+        assert_eq!(rows.len(), 2);
+        // Oldest first, and a stored campaign count wins over row counting
+        assert_eq!(rows[0].created, "2025-01-01");
+        assert_eq!(rows[0].campaign_count, 5);
+        assert_eq!(rows[1].campaign_count, 2);
+        assert_eq!(rows[1].total_clicks, 50);
+        assert!((rows[1].ctr - (50.0 / 300.0) * 100.0).abs() < 1e-9);
+
+        let csv = manifest_csv(&rows);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.starts_with("Report Name,"));
+    }
+
+    #[test]
+    fn split_window_fetches_merge_to_the_full_campaign_set() {
+        let start = "2025-01-01T00:00:00Z";
+        let end = "2025-06-30T23:59:59Z";
+
+        // One campaign every ten days across the 180-day range; c9 lands
+        // exactly on the 90-day boundary and shows up in both sub-windows
+        let campaigns: Vec<serde_json::Value> = (0..18).map(|i| {
+            let sent = chrono::DateTime::parse_from_rfc3339(start).unwrap().with_timezone(&chrono::Utc)
+                + chrono::Duration::days(i * 10);
+            serde_json::json!({
+                "id": format!("c{}", i),
+                "send_time": sent.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            })
+        }).collect();
+
+        let windows = split_fetch_window(start, end, 90);
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].0, start);
+        assert_eq!(windows[1].1, end);
+
+        let in_window = |c: &serde_json::Value, lo: &str, hi: &str| {
+            let sent = c.get("send_time").and_then(|v| v.as_str()).unwrap();
+            sent >= lo && sent <= hi
+        };
+        let merged = merge_campaign_pages(
+            windows.iter()
+                .map(|(lo, hi)| campaigns.iter().filter(|c| in_window(c, lo, hi)).cloned().collect())
+                .collect(),
+        );
+
+        let ids: Vec<&str> = merged.iter().map(|c| c["id"].as_str().unwrap()).collect();
+        let expected: Vec<String> = (0..18).map(|i| format!("c{}", i)).collect();
+        assert_eq!(ids, expected.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+
+        // Short ranges stay a single untouched window
+        assert_eq!(
+            split_fetch_window(start, "2025-02-01T00:00:00Z", 90),
+            vec![(start.to_string(), "2025-02-01T00:00:00Z".to_string())]
+        );
+    }
+
+    #[test]
+    fn export_fails_loudly_when_a_row_lacks_an_enabled_metric() {
+        let metrics = serde_json::json!({ "total_clicks": true, "ctr": true });
+        let report_data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "total_clicks": 30, "ctr": 6.0 },
+                { "send_date": "2025-01-13", "total_clicks": 25 }
+            ]
+        });
+
+        let err = build_csv(&report_data, &metrics, &CsvOptions::default()).unwrap_err();
+        let message = String::from(err);
+        assert!(message.contains("row 1"));
+        assert!(message.contains("2025-01-13"));
+        assert!(message.contains("'ctr'"));
+
+        // The same rows pass once the missing metric is no longer enabled
+        assert!(validate_report_data(&report_data, &serde_json::json!({ "total_clicks": true })).is_ok());
+    }
+
+    #[test]
+    fn report_notes_persist_and_appear_in_exports() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        save_report_to_dir(dir.path(), sample_report("report-notes")).expect("failed to save report");
+
+        let updated = set_report_notes_in_dir(dir.path(), "report-notes", "  Numbers low due to holiday send  ")
+            .expect("failed to set notes");
+        assert_eq!(updated.notes, "Numbers low due to holiday send");
+
+        // Survives a reload through the lenient loader
+        let reloaded = load_reports_from_dir(dir.path()).expect("failed to load reports");
+        assert_eq!(reloaded[0].notes, "Numbers low due to holiday send");
+
+        // Exports carry the annotation as metadata
+        let csv = csv_with_notes("Date,Total Clicks\n2025-01-06,30\n", &updated.notes);
+        assert!(csv.starts_with("# Notes: Numbers low due to holiday send\n"));
+        assert!(csv.ends_with("2025-01-06,30\n"));
+
+        let html = standalone_html(&reloaded[0], &default_export_theme(), "").expect("failed to render html");
+        assert!(html.contains("Notes: Numbers low due to holiday send"));
+
+        // Empty notes add nothing to either export
+        assert_eq!(csv_with_notes("Date\n", ""), "Date\n");
+    }
+
+    #[test]
+    fn standalone_html_embeds_rows_and_escapes_strings() {
+        let mut report = sample_report("html-1");
+        report.advertiser = "Smith & Sons <Media>".to_string();
+        report.data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "unique_opens": 100, "total_opens": 120, "total_recipients": 500, "total_clicks": 30, "ctr": 30.0 }
+            ]
+        });
+
+        let html = standalone_html(&report, &default_export_theme(), "").expect("failed to render html");
+
+        // Title text is escaped, never raw
+        assert!(html.contains("Smith &amp; Sons &lt;Media&gt;"));
+        assert!(!html.contains("<Media>"));
+
+        // The row data rides along as inline JSON
+        assert!(html.contains("\"send_date\":\"2025-01-06\""));
+        assert!(html.contains("\"total_clicks\":30"));
+
+        // Self-contained: nothing fetched from the network
+        assert!(!html.contains("http://"));
+        assert!(!html.contains("https://"));
+    }
+
+    #[test]
+    fn bounce_split_and_delivery_rate_compute_per_campaign() {
+        let campaign = serde_json::json!({
+            "id": "c1",
+            "send_time": "2025-01-06T09:00:00+00:00",
+            "emails_sent": 900,
+            "bounces": { "hard_bounces": 60, "soft_bounces": 40 },
+            "report_summary": { "unique_opens": 200, "opens": 250 }
+        });
+        let click_data = serde_json::json!({
+            "urls_clicked": [{ "url": "https://example.com/offer", "total_clicks": 10 }]
+        });
+        let urls = vec!["https://example.com/offer".to_string()];
+
+        let row = campaign_report_row(&campaign, &click_data, &urls, "exact", &[], "us1")
+            .expect("row should be produced");
+        assert_eq!(row["hard_bounces"], 60);
+        assert_eq!(row["soft_bounces"], 40);
+        // 900 delivered of 1000 attempted
+        assert_eq!(row["delivery_rate"], 90.0);
+
+        // A campaign with no bounces object counts as fully delivered
+        let clean = serde_json::json!({
+            "id": "c2",
+            "send_time": "2025-01-13T09:00:00+00:00",
+            "emails_sent": 500,
+            "report_summary": { "unique_opens": 100, "opens": 120 }
+        });
+        let row = campaign_report_row(&clean, &click_data, &urls, "exact", &[], "us1")
+            .expect("row should be produced");
+        assert_eq!(row["hard_bounces"], 0);
+        assert_eq!(row["delivery_rate"], 100.0);
+    }
+
+    #[test]
+    fn test_url_match_explains_each_mode() {
+        // prefix: historical substring behavior
+        let hit = explain_url_match("https://example.com/offers-expired", "/offer", "prefix").unwrap();
+        assert!(hit.matches);
+        assert!(hit.reason.contains("contains"));
+        let miss = explain_url_match("https://example.com/deals", "/offer", "prefix").unwrap();
+        assert!(!miss.matches);
+
+        // exact: normalized comparison
+        let hit = explain_url_match("https://example.com/offer/", "https://example.com/offer", "exact").unwrap();
+        assert!(hit.matches);
+        let miss = explain_url_match("https://example.com/offer/thanks", "https://example.com/offer", "exact").unwrap();
+        assert!(!miss.matches);
+        assert!(miss.reason.contains("differ"));
+
+        // segment: /-boundary alignment
+        let hit = explain_url_match("https://example.com/offer/thanks", "/offer", "segment").unwrap();
+        assert!(hit.matches);
+        let miss = explain_url_match("https://example.com/offers-expired", "/offer", "segment").unwrap();
+        assert!(!miss.matches);
+
+        assert!(explain_url_match("https://example.com", "/offer", "fuzzy").is_err());
+        assert!(explain_url_match("https://example.com", "  ", "exact").is_err());
+    }
+
+    #[test]
+    fn run_timings_sort_by_date_and_default_for_old_reports() {
+        let mut fresh = sample_report("fresh");
+        fresh.created = "2025-02-01".to_string();
+        fresh.elapsed_secs = Some(42);
+        fresh.campaign_count = Some(7);
+        let mut old = sample_report("old");
+        old.created = "2025-01-01".to_string();
+
+        let timings = run_timing_history_from(&[fresh, old]);
+        assert_eq!(timings.len(), 2);
+        // Oldest first, with pre-timing reports defaulting to empty fields
+        assert_eq!(timings[0].report_id, "old");
+        assert_eq!(timings[0].elapsed_secs, None);
+        assert_eq!(timings[0].campaign_count, None);
+        assert_eq!(timings[1].report_id, "fresh");
+        assert_eq!(timings[1].elapsed_secs, Some(42));
+        assert_eq!(timings[1].campaign_count, Some(7));
+
+        // Reports saved before the fields existed still parse
+        let legacy: SavedReport = serde_json::from_str(
+            r#"{"id":"r1","name":"n","advertiser":"a","report_type":"AM",
+                "date_range":{"start_date":"2025-01-01","end_date":"2025-01-31"},
+                "created":"2025-01-15","data":{},
+                "metrics":{"unique_opens":true,"total_opens":true,"total_recipients":true,"total_clicks":true,"ctr":true}}"#,
+        ).expect("legacy report should parse");
+        assert_eq!(legacy.elapsed_secs, None);
+        assert_eq!(legacy.campaign_count, None);
+    }
+
+    #[test]
+    fn audience_scope_excludes_same_titled_campaigns_on_other_lists() {
+        let campaigns = vec![
+            serde_json::json!({
+                "id": "a",
+                "settings": { "title": "AM Newsletter" },
+                "recipients": { "list_id": "list-1" }
+            }),
+            serde_json::json!({
+                "id": "b",
+                "settings": { "title": "AM Newsletter" },
+                "recipients": { "list_id": "list-2" }
+            }),
+            serde_json::json!({
+                "id": "c",
+                "settings": { "title": "AM Newsletter" }
+            }),
+        ];
+
+        let scoped = scope_campaigns_to_audience(&campaigns, Some("list-1"));
+        let ids: Vec<&str> = scoped.iter().filter_map(|c| c.get("id").and_then(|v| v.as_str())).collect();
+        // The other list's campaign drops out; the one with no list id is
+        // ambiguous and passes through, like check_audience_mismatches
+        assert_eq!(ids, vec!["a", "c"]);
+
+        // No scope keeps the historical behavior
+        assert_eq!(scope_campaigns_to_audience(&campaigns, None).len(), 3);
+        assert_eq!(scope_campaigns_to_audience(&campaigns, Some("  ")).len(), 3);
+    }
+
+    #[test]
+    fn export_bundle_writes_all_formats_or_nothing() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let mut report = sample_report("bundle-1");
+        report.data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "unique_opens": 100, "total_opens": 120, "total_recipients": 500, "total_clicks": 30, "ctr": 30.0 }
+            ]
+        });
+
+        let formats = vec!["csv".to_string(), "json".to_string()];
+        let paths = export_bundle_to_dir(&report, &formats, dir.path(), &CsvOptions::default(), "20250201_120000", false)
+            .expect("failed to export bundle");
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with(".csv"));
+        assert!(paths[1].ends_with(".json"));
+        for path in &paths {
+            assert!(Path::new(path).exists());
+        }
+
+        // An unsupported format fails before anything is written
+        let bad = vec!["csv".to_string(), "pdf".to_string()];
+        let err = export_bundle_to_dir(&report, &bad, dir.path(), &CsvOptions::default(), "20250201_130000", false)
+            .unwrap_err();
+        assert!(err.contains("Unsupported bundle format"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn rolling_average_smooths_with_partial_leading_window() {
+        let data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-27", "total_clicks": 40 },
+                { "send_date": "2025-01-06", "total_clicks": 10 },
+                { "send_date": "2025-01-20", "total_clicks": 30 },
+                { "send_date": "2025-01-13", "total_clicks": 20 }
+            ]
+        });
+
+        let series = rolling_average_series(&data, "total_clicks", 3).expect("failed to compute series");
+        let averages: Vec<f64> = series.iter().map(|p| p.average).collect();
+
+        // Sorted by date, with the leading edge averaging what's available
+        assert_eq!(series[0].send_date, "2025-01-06");
+        assert_eq!(averages, vec![10.0, 15.0, 20.0, 30.0]);
+
+        assert!(rolling_average_series(&data, "total_clicks", 0).is_err());
+        assert!(rolling_average_series(&data, "bogus", 3).is_err());
+    }
+
+    #[test]
+    fn env_api_key_wins_only_when_opted_in() {
+        let (key, from_env) = resolve_api_key("stored-key", true, Some("env-key"));
+        assert_eq!(key, "env-key");
+        assert!(from_env);
+
+        // Without the flag the env var is ignored
+        let (key, from_env) = resolve_api_key("stored-key", false, Some("env-key"));
+        assert_eq!(key, "stored-key");
+        assert!(!from_env);
+
+        // A blank or missing env value falls back to the stored key
+        let (key, from_env) = resolve_api_key("stored-key", true, Some("  "));
+        assert_eq!(key, "stored-key");
+        assert!(!from_env);
+        let (key, from_env) = resolve_api_key("stored-key", true, None);
+        assert_eq!(key, "stored-key");
+        assert!(!from_env);
+    }
+
+    #[test]
+    fn factory_reset_requires_token_and_spares_unknown_files() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        fs::write(dir.path().join("settings.json"), "{}").unwrap();
+        fs::write(dir.path().join("reports.json"), "[]").unwrap();
+        fs::create_dir_all(dir.path().join("campaign_cache")).unwrap();
+        fs::write(dir.path().join("campaign_cache/a.json"), "{}").unwrap();
+        fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+
+        // Anything but the exact token refuses and deletes nothing
+        let err = factory_reset_in_dir(dir.path(), "delete").unwrap_err();
+        assert!(err.contains("confirmation token"));
+        assert!(dir.path().join("settings.json").exists());
+
+        factory_reset_in_dir(dir.path(), "DELETE").expect("reset failed");
+        assert!(!dir.path().join("settings.json").exists());
+        assert!(!dir.path().join("reports.json").exists());
+        assert!(!dir.path().join("campaign_cache").exists());
+        // Files the app never wrote stay put
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn targeted_count_tracks_segment_size() {
+        let campaign = serde_json::json!({
+            "id": "c1",
+            "send_time": "2025-01-06T09:00:00+00:00",
+            "emails_sent": 800,
+            "recipients": { "recipient_count": 1000 },
+            "report_summary": { "unique_opens": 200, "opens": 250 }
+        });
+        let click_data = serde_json::json!({
+            "urls_clicked": [{ "url": "https://example.com/offer", "total_clicks": 10 }]
+        });
+        let urls = vec!["https://example.com/offer".to_string()];
+
+        let row = campaign_report_row(&campaign, &click_data, &urls, "exact", &[], "us1")
+            .expect("row should be produced");
+
+        // Segmented send: targeted and delivered counts differ
+        assert_eq!(row["total_recipients"], 800);
+        assert_eq!(row["targeted_count"], 1000);
+
+        // The gated column shows up next to Total Recipients
+        let report_data = serde_json::json!({ "report_data": [row] });
+        let metrics = serde_json::json!({ "total_recipients": true, "targeted_count": true });
+        let csv = build_csv(&report_data, &metrics, &CsvOptions::default()).expect("failed to build csv");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Date,Total Recipients,Targeted Count");
+        assert_eq!(lines[1], "2025-01-06,800,1000");
+    }
+
+    #[test]
+    fn highlights_rank_by_ctr_and_clicks_separately() {
+        let data = serde_json::json!({
+            "report_data": [
+                { "campaign_id": "a", "send_date": "2025-01-06", "total_clicks": 10, "ctr": 5.0 },
+                { "campaign_id": "b", "send_date": "2025-01-13", "total_clicks": 40, "ctr": 1.0 },
+                { "campaign_id": "c", "send_date": "2025-01-20", "total_clicks": 20, "ctr": 4.0 },
+                { "campaign_id": "d", "send_date": "2025-01-27", "total_clicks": 30, "ctr": 2.0 }
+            ]
+        });
+
+        let highlights = compute_highlights(&data, 2).expect("failed to compute highlights");
+
+        let ids = |rows: &[HighlightRow]| rows.iter().map(|r| r.campaign_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&highlights.top_by_ctr), vec!["a", "c"]);
+        assert_eq!(ids(&highlights.bottom_by_ctr), vec!["b", "d"]);
+        assert_eq!(ids(&highlights.top_by_clicks), vec!["b", "d"]);
+        assert_eq!(ids(&highlights.bottom_by_clicks), vec!["a", "c"]);
+
+        // Asking for more than exists returns what's available
+        let all = compute_highlights(&data, 10).expect("failed to compute highlights");
+        assert_eq!(all.top_by_ctr.len(), 4);
+    }
+
+    #[test]
+    fn progress_channel_sink_delivers_updates_in_order() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sink = ProgressSink::Channel(tx);
+
+        for (progress, stage) in [(0u8, "Initializing"), (20, "FetchingCampaigns"), (100, "Complete")] {
+            sink.send(ProgressUpdate {
+                stage: stage.to_string(),
+                progress,
+                message: format!("{} at {}%", stage, progress),
+                time_remaining: None,
+                elapsed_secs: None,
+            });
+        }
+        drop(sink);
+
+        let received: Vec<ProgressUpdate> = rx.iter().collect();
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[0].stage, "Initializing");
+        assert_eq!(received[1].stage, "FetchingCampaigns");
+        assert_eq!(received[2].progress, 100);
+    }
+
+    #[test]
+    fn url_audit_flags_stale_urls() {
+        let urls = vec![
+            "https://example.com/live".to_string(),
+            "https://example.com/stale".to_string(),
+        ];
+        let details = vec![
+            (
+                "Campaign A".to_string(),
+                serde_json::json!({ "urls_clicked": [{ "url": "https://example.com/live", "total_clicks": 12 }] }),
+            ),
+            (
+                "Campaign B".to_string(),
+                serde_json::json!({ "urls_clicked": [{ "url": "https://example.com/live", "total_clicks": 3 }] }),
+            ),
+        ];
+
+        let entries = audit_urls_against_clicks(&urls, &details, "exact");
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].url, "https://example.com/live");
+        assert_eq!(entries[0].recent_clicks, 15);
+        assert_eq!(entries[0].campaigns_matched, 2);
+        assert!(!entries[0].possibly_stale);
+
+        assert_eq!(entries[1].url, "https://example.com/stale");
+        assert_eq!(entries[1].recent_clicks, 0);
+        assert_eq!(entries[1].campaigns_matched, 0);
+        assert!(entries[1].possibly_stale);
+    }
+
+    #[test]
+    fn template_export_renders_rows_and_totals() {
+        let mut report = sample_report("tmpl-1");
+        report.data = serde_json::json!({
+            "report_data": [
+                { "send_date": "2025-01-06", "total_clicks": 30, "unique_opens": 100 },
+                { "send_date": "2025-01-13", "total_clicks": 20, "unique_opens": 50 }
+            ]
+        });
+
+        let template = "{{report.advertiser}} ({{report.start_date}} to {{report.end_date}})\n{{#each rows}}{{send_date}}: {{total_clicks}}\n{{/each}}Total: {{totals.total_clicks}}\n";
+        let rendered = render_report_template(template, &report).expect("failed to render");
+        assert_eq!(
+            rendered,
+            "Test Advertiser (2025-01-01 to 2025-01-31)\n2025-01-06: 30\n2025-01-13: 20\nTotal: 50\n"
+        );
+
+        // A broken template surfaces a compile error instead of bad output
+        let err = render_report_template("{{#each rows}}", &report).unwrap_err();
+        assert!(err.starts_with("Template failed to compile"));
+    }
+
+    #[test]
+    fn campaign_cache_round_trips_and_expires() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = campaign_cache_path(dir.path(), "list1", "2025-01-01T00:00:00Z", "2025-01-31T23:59:59Z", None);
+        let campaigns = vec![
+            serde_json::json!({ "id": "c1" }),
+            serde_json::json!({ "id": "c2" }),
+        ];
+
+        write_campaign_cache(&path, &campaigns).expect("failed to write cache");
+
+        let warm = read_campaign_cache(&path, chrono::Utc::now()).expect("cache should be warm");
+        assert_eq!(warm.len(), 2);
+        assert_eq!(warm[0]["id"], "c1");
+
+        // A different window gets its own entry
+        let other = campaign_cache_path(dir.path(), "list1", "2025-02-01T00:00:00Z", "2025-02-28T23:59:59Z", None);
+        assert_ne!(path, other);
+
+        // Entries go stale once the TTL passes
+        let later = chrono::Utc::now() + chrono::Duration::seconds(CAMPAIGN_CACHE_TTL_SECS + 1);
+        assert!(read_campaign_cache(&path, later).is_none());
+    }
+
+    #[test]
+    fn mailchimp_reported_rates_ride_along() {
+        let campaign = serde_json::json!({
+            "id": "c1",
+            "web_id": 42,
+            "send_time": "2025-01-06T09:00:00+00:00",
+            "emails_sent": 1000,
+            "report_summary": {
+                "unique_opens": 200,
+                "opens": 250,
+                "open_rate": 0.25,
+                "click_rate": 0.042
+            }
+        });
+        let click_data = serde_json::json!({
+            "urls_clicked": [{ "url": "https://example.com/offer", "total_clicks": 10 }]
+        });
+        let urls = vec!["https://example.com/offer".to_string()];
+
+        let row = campaign_report_row(&campaign, &click_data, &urls, "exact", &[], "us1")
+            .expect("row should be produced");
+
+        // Both rates come through as percentages next to our computed CTR
+        assert_eq!(row["mailchimp_open_rate"], 25.0);
+        assert!((row["mailchimp_click_rate"].as_f64().unwrap() - 4.2).abs() < 1e-9);
+        assert_eq!(row["ctr"], 5.0);
+
+        // And the gated export shows both views of click-through
+        let report_data = serde_json::json!({ "report_data": [row] });
+        let metrics = serde_json::json!({ "ctr": true, "mailchimp_rates": true });
+        let csv = build_csv(&report_data, &metrics, &CsvOptions::default()).expect("failed to build csv");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Date,CTR,Mailchimp Open Rate,Mailchimp Click Rate");
+        assert_eq!(lines[1], "2025-01-06,5.000000,25.00,4.20");
+    }
+
+    #[test]
+    fn wrong_audience_campaigns_are_flagged() {
+        let campaigns = vec![
+            serde_json::json!({
+                "id": "c1",
+                "settings": { "title": "NJUA AM Daily" },
+                "recipients": { "list_id": "6732b2b110" }
+            }),
+            serde_json::json!({
+                "id": "c2",
+                "settings": { "title": "NJUA AM Special" },
+                "recipients": { "list_id": "deadbeef00" }
+            }),
+        ];
+
+        // Warn-only mode keeps both campaigns but names the offender
+        let (kept, warnings) = check_audience_mismatches(campaigns.clone(), "6732b2b110", false);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("NJUA AM Special"));
+        assert!(warnings[0].contains("deadbeef00"));
+
+        // Exclude mode drops the mismatched campaign
+        let (kept, warnings) = check_audience_mismatches(campaigns, "6732b2b110", true);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0]["id"], "c1");
+        assert!(warnings[0].contains("excluded"));
+    }
+
+    #[test]
+    fn tag_filter_mode_selects_by_tag() {
+        let campaigns = vec![
+            serde_json::json!({ "id": "c1", "settings": { "title": "NJUA HC Weekly" }, "tags": [{ "name": "Sponsored" }] }),
+            serde_json::json!({ "id": "c2", "settings": { "title": "NJUA AM Daily" }, "tags": ["sponsored", "newsletter"] }),
+            serde_json::json!({ "id": "c3", "settings": { "title": "HC Special" } }),
+        ];
+
+        // Tag matching is case-insensitive and handles both tag shapes
+        let tagged = filter_campaigns_by_tag(&campaigns, "SPONSORED");
+        let ids: Vec<&str> = tagged.iter().map(|c| c["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["c1", "c2"]);
+
+        let mut request = ReportRequest {
+            newsletter_type: "HC".to_string(),
+            advertiser: "NJUA".to_string(),
+            tracking_urls: Vec::new(),
+            date_range: DateRange {
+                start_date: "2025-01-01".to_string(),
+                end_date: "2025-01-31".to_string(),
+            },
+            metrics: default_metrics_selection(),
+            path_match: default_path_match(),
+            folder_id: None,
+            allow_empty: false,
+            include_list_activity: false,
+            group_by: None,
+            incremental: false,
+            filter_mode: default_filter_mode(),
+            tag: None,
+            exclude_wrong_audience: false,
+            audience_scope: None,
+            preview_token: None,
+            debug_capture: false,
+        };
+
+        // Default mode still selects by title substring
+        let by_title = select_campaigns(&campaigns, &request).expect("title selection failed");
+        assert_eq!(by_title.len(), 2);
+
+        request.filter_mode = "tag".to_string();
+        assert!(select_campaigns(&campaigns, &request).is_err(), "tag mode without a tag should fail");
+
+        request.tag = Some("newsletter".to_string());
+        let by_tag = select_campaigns(&campaigns, &request).expect("tag selection failed");
+        assert_eq!(by_tag.len(), 1);
+        assert_eq!(by_tag[0]["id"], "c2");
+    }
+
+    #[test]
+    fn explain_matches_gives_correct_reasons() {
+        let campaigns = vec![
+            serde_json::json!({ "id": "c1", "settings": { "title": "NJUA HC Weekly" } }),
+            serde_json::json!({ "id": "c2", "settings": { "title": "Health Care Update" } }),
+            serde_json::json!({ "id": "c3", "settings": { "title": "NJUA AM Daily" } }),
+            serde_json::json!({ "id": "c4", "settings": { "title": "HC Special", "folder_id": "f2" } }),
+            serde_json::json!({ "id": "c5" }),
+        ];
+
+        let explanations = explain_campaign_matches(&campaigns, "HC", Some("f1"));
+        assert_eq!(explanations.len(), 5);
+
+        assert!(explanations[0].included);
+        assert_eq!(explanations[0].reason, "Title contains 'hc'");
+
+        assert!(explanations[1].included);
+        assert_eq!(explanations[1].reason, "Title contains 'health care' (alias for 'hc')");
+
+        assert!(!explanations[2].included);
+        assert_eq!(explanations[2].reason, "Title does not contain 'hc'");
+
+        assert!(!explanations[3].included);
+        assert_eq!(explanations[3].reason, "In folder 'f2', outside the requested folder 'f1'");
+
+        assert!(!explanations[4].included);
+        assert_eq!(explanations[4].reason, "Campaign has no title");
+
+        // The filter keeps exactly the included campaigns
+        let filtered = filter_campaigns_by_type(&campaigns, "HC");
+        let ids: Vec<&str> = filtered.iter().map(|c| c["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["c1", "c2", "c4"]);
+    }
+
+    #[test]
+    fn api_base_override_redirects_request_building() {
+        let mut settings: Settings = serde_json::from_str(r#"{
+            "mailchimp_api_key": "abc123-us21",
+            "mailchimp_audience_id": "aud",
+            "advertisers": [],
+            "download_directory": "/tmp"
+        }"#).expect("settings parse failed");
+
+        // Normally the base comes from the key's data center suffix
+        assert_eq!(mailchimp_base_url(&settings), "https://us21.api.mailchimp.com/3.0");
+
+        // The override wins, with trailing slashes trimmed so URL joins
+        // stay clean
+        settings.api_base_override = Some("http://127.0.0.1:9090/3.0/".to_string());
+        assert_eq!(mailchimp_base_url(&settings), "http://127.0.0.1:9090/3.0");
+
+        // A blank override means "not set"
+        settings.api_base_override = Some("   ".to_string());
+        assert_eq!(mailchimp_base_url(&settings), "https://us21.api.mailchimp.com/3.0");
+    }
+
+    #[test]
+    fn canonical_json_is_byte_stable() {
+        // Same report, different key order and number types
+        let a = serde_json::json!({
+            "report_totals": { "total_clicks": 25, "ctr": 12.5 },
+            "report_data": [{ "send_date": "2025-01-06", "total_clicks": 25.0 }]
+        });
+        let b = serde_json::json!({
+            "report_data": [{ "total_clicks": 25, "send_date": "2025-01-06" }],
+            "report_totals": { "ctr": 12.5, "total_clicks": 25.0 }
+        });
+
+        assert_eq!(to_canonical_json(&a), to_canonical_json(&b));
+        assert_eq!(
+            to_canonical_json(&a),
+            r#"{"report_data":[{"send_date":"2025-01-06","total_clicks":25}],"report_totals":{"ctr":12.5,"total_clicks":25}}"#
+        );
+    }
+
+    #[test]
+    fn sha256_hex_matches_known_digest() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn sigv4_authorization_is_deterministic() {
+        let auth = sigv4_authorization(
+            "AKIDEXAMPLE",
+            "secret",
+            "us-east-1",
+            "localhost:9000",
+            "/reports/NJUA_AM_2025-01-01_2025-01-31.csv",
+            "20250101T000000Z",
+            &sha256_hex(b"Send Date,Total Clicks\n"),
+        );
+
+        assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20250101/us-east-1/s3/aws4_request,"));
+        assert!(auth.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,"));
+
+        // The signature is 64 hex chars and stable for fixed inputs
+        let signature = auth.rsplit("Signature=").next().unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(auth, sigv4_authorization(
+            "AKIDEXAMPLE",
+            "secret",
+            "us-east-1",
+            "localhost:9000",
+            "/reports/NJUA_AM_2025-01-01_2025-01-31.csv",
+            "20250101T000000Z",
+            &sha256_hex(b"Send Date,Total Clicks\n"),
+        ));
+    }
+
+    #[test]
+    fn rebuilt_request_mirrors_the_saved_report() {
+        let report = sample_report("report-1");
+        let urls = vec!["https://example.com/promo".to_string()];
+
+        let request = rebuild_report_request(&report, urls.clone());
+
+        assert_eq!(request.advertiser, "Test Advertiser");
+        assert_eq!(request.newsletter_type, "AM");
+        assert_eq!(request.tracking_urls, urls);
+        assert_eq!(request.date_range.start_date, "2025-01-01");
+        assert_eq!(request.date_range.end_date, "2025-01-31");
+        assert_eq!(
+            serde_json::to_value(&request.metrics).unwrap(),
+            serde_json::to_value(&report.metrics).unwrap()
+        );
+        // A quiet window must not fail the re-run
+        assert!(request.allow_empty);
+        assert_eq!(request.path_match, default_path_match());
+        assert_eq!(request.filter_mode, default_filter_mode());
+    }
 }